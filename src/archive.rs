@@ -0,0 +1,207 @@
+use std::{
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::payload::{form::Form, info::Amendment, section6::Attachment};
+
+const ARCHIVE_FILE: &str = "archive/bao_cao_da_nop.jsonl";
+const ATTACHMENT_ARCHIVE_FILE: &str = "archive/tai_lieu_dinh_kem_da_nop.jsonl";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ArchivedReport {
+    #[serde(rename = "ma_bao_cao")]
+    pub report_id: i64,
+    #[serde(rename = "ma_bao_cao_noi_bo")]
+    pub internal_number: String,
+    #[serde(rename = "ngay_bao_cao")]
+    pub report_date: Option<String>,
+    #[serde(rename = "loai_thay_doi")]
+    pub change_type: i32,
+    #[serde(rename = "file")]
+    pub file_name: String,
+    #[serde(rename = "thoi_gian_nop")]
+    pub submitted_at: String,
+    #[serde(
+        rename = "ly_do_nop_muon",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub late_reason: Option<String>,
+}
+
+impl ArchivedReport {
+    pub fn new(form: &Form, report_id: i64, file_path: &Path) -> Self {
+        Self {
+            report_id,
+            internal_number: form.internal_number.clone(),
+            report_date: form.payload.general_info.report_date.clone(),
+            change_type: form.payload.general_info.amendment.change_type,
+            file_name: file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            submitted_at: chrono::Local::now().to_rfc3339(),
+            late_reason: None,
+        }
+    }
+
+    fn matches(&self, amendment: &Amendment) -> bool {
+        let report_number = amendment.report_number.trim();
+        report_number == self.report_id.to_string() || report_number == self.internal_number
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ArchivedAttachment {
+    #[serde(rename = "ten_file")]
+    pub file_name: String,
+    #[serde(rename = "loai_tai_lieu")]
+    pub attachment_type: String,
+    #[serde(rename = "dung_luong")]
+    pub file_size: i64,
+    #[serde(rename = "sha256")]
+    pub hash: String,
+}
+
+/// The exact files sent for a report, kept as evidence that the filed attachments match the
+/// local archive.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ArchivedAttachments {
+    #[serde(rename = "ma_bao_cao")]
+    pub report_id: i64,
+    #[serde(rename = "file")]
+    pub file_name: String,
+    #[serde(rename = "thoi_gian_gui")]
+    pub sent_at: String,
+    #[serde(rename = "tai_lieu_dinh_kem")]
+    pub attachments: Vec<ArchivedAttachment>,
+    /// Outcome of the check against the list returned by the portal, absent when the portal did
+    /// not return one.
+    #[serde(
+        rename = "khop_voi_website",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub verified: Option<bool>,
+    /// Batch of a report whose attachments are sent in several requests, e.g. `2/3`, recorded as
+    /// soon as the batch is accepted. Absent on the record of the whole upload.
+    #[serde(rename = "lan_gui", default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<String>,
+}
+
+impl ArchivedAttachments {
+    pub fn new(attachments: &[Attachment], report_id: i64, file_path: &Path) -> Self {
+        Self {
+            report_id,
+            file_name: file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            sent_at: chrono::Local::now().to_rfc3339(),
+            attachments: attachments
+                .iter()
+                .map(|attachment| ArchivedAttachment {
+                    file_name: attachment.file_name.clone().unwrap_or_default(),
+                    attachment_type: attachment
+                        .attachment_type
+                        .as_ref()
+                        .map(|t| t.as_str().to_string())
+                        .unwrap_or_default(),
+                    file_size: attachment.file_size.unwrap_or_default(),
+                    hash: attachment.file_hash.clone().unwrap_or_default(),
+                })
+                .collect(),
+            verified: None,
+            batch: None,
+        }
+    }
+}
+
+pub fn load_archive() -> anyhow::Result<Vec<ArchivedReport>> {
+    let archive_path = Path::new(ARCHIVE_FILE);
+    if !archive_path.is_file() {
+        return Ok(vec![]);
+    }
+
+    let file = std::fs::File::open(archive_path)
+        .with_context(|| format!("Không thể mở file lưu trữ {:#?}", archive_path))?;
+
+    std::io::BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map(|l| !l.trim().is_empty()).unwrap_or(true))
+        .map(|(n_line, line)| {
+            let line = line?;
+            serde_json::from_str::<ArchivedReport>(&line).with_context(|| {
+                format!(
+                    "Dòng số {} trong file lưu trữ {:#?} không hợp lệ",
+                    n_line + 1,
+                    archive_path
+                )
+            })
+        })
+        .collect()
+}
+
+fn append_line<T: Serialize>(archive_path: &Path, record: &T) -> anyhow::Result<()> {
+    if let Some(parent) = archive_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Không thể tạo folder {:#?}", parent))?;
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(archive_path)
+        .with_context(|| format!("Không thể mở file lưu trữ {:#?}", archive_path))?;
+
+    writeln!(file, "{}", serde_json::to_string(record)?)
+        .with_context(|| format!("Không thể ghi file lưu trữ {:#?}", archive_path))?;
+
+    Ok(())
+}
+
+pub fn append_to_archive(report: &ArchivedReport) -> anyhow::Result<()> {
+    append_line(Path::new(ARCHIVE_FILE), report)
+}
+
+pub fn append_attachments_to_archive(record: &ArchivedAttachments) -> anyhow::Result<()> {
+    append_line(Path::new(ATTACHMENT_ARCHIVE_FILE), record)
+}
+
+pub fn check_amendment(form: &Form) -> anyhow::Result<()> {
+    let amendment = &form.payload.general_info.amendment;
+    if amendment.change_type == 0 {
+        return Ok(());
+    }
+
+    let archive = load_archive()?;
+    let original = archive.iter().find(|report| report.matches(amendment));
+
+    match original {
+        Some(original) => {
+            if original.report_date.as_deref() != Some(amendment.report_date.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Ngày báo cáo gốc '{}' không khớp với ngày báo cáo '{}' của báo cáo số '{}' đã nộp",
+                    amendment.report_date,
+                    original.report_date.clone().unwrap_or_default(),
+                    amendment.report_number
+                ));
+            }
+            Ok(())
+        }
+        // The portal has no lookup by report number, a report filed outside this tool has to be
+        // added to the archive by hand
+        None => Err(anyhow::anyhow!(
+            "Không tìm thấy báo cáo gốc số '{}' trong file lưu trữ {:#?}. Nếu báo cáo gốc được nộp trực tiếp trên website NHNN, bổ sung thông tin báo cáo gốc vào file lưu trữ trước khi nộp báo cáo sửa đổi, bổ sung",
+            amendment.report_number,
+            ARCHIVE_FILE
+        )),
+    }
+}
@@ -0,0 +1,371 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use calamine::{Data, Reader};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    payload::code::AttachmentType,
+    pdf::{Page, PdfImage, PdfWriter, merge_pdfs},
+    utils::setup::convert_attachments_from_args,
+};
+
+const LOCAL_CONVERSION_CONFIG: &str = "input/cau_hinh_chuyen_doi.json";
+const CONVERTED_FOLDER: &str = "chuyen_doi";
+const ORIGINALS_FOLDER: &str = "ban_goc";
+
+const IMAGE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+const EXCEL_EXTENSIONS: [&str; 2] = ["xlsx", "xlsm"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConversionConfig {
+    #[serde(rename = "chuyển đổi sang PDF")]
+    pub enabled: bool,
+    #[serde(rename = "gộp ảnh thành PDF", default)]
+    pub merge_images: bool,
+    #[serde(rename = "chuyển Excel sang PDF", default)]
+    pub convert_excel: bool,
+    #[serde(rename = "gộp file cùng loại tài liệu", default)]
+    pub merge_same_type: bool,
+}
+
+pub fn load_conversion_config() -> anyhow::Result<ConversionConfig> {
+    const CONVERSION_CONFIG: &str = include_str!("../conversion.json");
+
+    let local_path = Path::new(LOCAL_CONVERSION_CONFIG);
+    if local_path.is_file() {
+        let content = std::fs::read_to_string(local_path)
+            .with_context(|| format!("Không thể đọc file {:#?}", local_path))?;
+        let parsed_result: ConversionConfig =
+            serde_json::from_str(&content).with_context(|| {
+                format!(
+                    "File cấu hình chuyển đổi tài liệu đính kèm {:#?} không hợp lệ",
+                    local_path
+                )
+            })?;
+        return Ok(parsed_result);
+    }
+
+    let parsed_result: ConversionConfig = serde_json::from_str(CONVERSION_CONFIG)?;
+    Ok(parsed_result)
+}
+
+static CONVERSION_CONFIG: OnceLock<ConversionConfig> = OnceLock::new();
+
+/// The conversion settings, read on first use.
+pub fn conversion_config() -> anyhow::Result<&'static ConversionConfig> {
+    if let Some(config) = CONVERSION_CONFIG.get() {
+        return Ok(config);
+    }
+    let config = load_conversion_config()?;
+    Ok(CONVERSION_CONFIG.get_or_init(|| config))
+}
+
+/// Conversion runs when enabled in the configuration or with `--convert-attachments`.
+pub fn conversion_enabled() -> anyhow::Result<bool> {
+    Ok(conversion_config()?.enabled || convert_attachments_from_args())
+}
+
+/// An attachment file with the information resolved from the manifest or the file name.
+#[derive(Debug, Clone)]
+pub struct PlannedAttachment {
+    pub path: PathBuf,
+    pub file_name: String,
+    pub attachment_type: AttachmentType,
+    pub description: String,
+    /// Page count declared in the manifest, dropped once the file is converted
+    pub page_count: Option<i32>,
+    pub order: Option<i32>,
+}
+
+impl PlannedAttachment {
+    fn extension(&self) -> String {
+        self.path
+            .extension()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    }
+
+    fn stem(&self) -> String {
+        self.path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// Folder in the local archive holding the converted attachments of a workbook.
+pub fn conversion_folder(file_path: &Path) -> PathBuf {
+    let file_name = file_path
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut folder = PathBuf::from("archive");
+    folder.push(file_name);
+    folder
+}
+
+/// Converts the attachments to PDF according to the configuration: images of the same document
+/// type are merged into one file, Excel statements are rendered as tables, and optionally every
+/// PDF of the same document type is merged. The originals are copied to the local archive and
+/// never modified. Files that cannot be converted are sent as they are.
+pub fn convert_attachments(
+    files: Vec<PlannedAttachment>,
+    file_path: &Path,
+) -> anyhow::Result<Vec<PlannedAttachment>> {
+    let config = conversion_config()?;
+    let archive_folder = conversion_folder(file_path);
+    let output_folder = archive_folder.join(CONVERTED_FOLDER);
+    let originals_folder = archive_folder.join(ORIGINALS_FOLDER);
+    std::fs::create_dir_all(&output_folder)
+        .with_context(|| format!("Không thể tạo folder {:#?}", output_folder))?;
+    std::fs::create_dir_all(&originals_folder)
+        .with_context(|| format!("Không thể tạo folder {:#?}", originals_folder))?;
+
+    let mut files = files;
+    let mut taken_names = HashSet::new();
+    if config.merge_images {
+        files = merge_groups(
+            files,
+            |file| IMAGE_EXTENSIONS.contains(&file.extension().as_str()),
+            &output_folder,
+            &mut taken_names,
+            "ảnh",
+            images_to_pdf,
+        );
+    }
+    if config.convert_excel {
+        files = files
+            .into_iter()
+            .flat_map(
+                |file| match EXCEL_EXTENSIONS.contains(&file.extension().as_str()) {
+                    true => convert_group(
+                        vec![file],
+                        &output_folder,
+                        &mut taken_names,
+                        "Excel",
+                        excel_to_pdf,
+                    ),
+                    false => vec![file],
+                },
+            )
+            .collect();
+    }
+    if config.merge_same_type {
+        files = merge_groups(
+            files,
+            |file| file.extension() == "pdf",
+            &output_folder,
+            &mut taken_names,
+            "PDF",
+            |sources, _| merge_pdfs(sources),
+        );
+    }
+
+    for file in files.iter() {
+        if file.path.starts_with(&output_folder) {
+            log::info!("Tài liệu đính kèm đã chuyển đổi: {:#?}", file.path);
+        }
+    }
+    Ok(files)
+}
+
+/// Replaces each group of files of the same document type selected by `is_candidate` by a single
+/// converted file, placed where the first file of the group was.
+fn merge_groups(
+    files: Vec<PlannedAttachment>,
+    is_candidate: impl Fn(&PlannedAttachment) -> bool,
+    output_folder: &Path,
+    taken_names: &mut HashSet<String>,
+    kind: &str,
+    convert: impl Fn(&[PathBuf], &str) -> anyhow::Result<Vec<u8>>,
+) -> Vec<PlannedAttachment> {
+    let mut groups: Vec<(Option<AttachmentType>, Vec<PlannedAttachment>)> = vec![];
+    for file in files {
+        let key = is_candidate(&file).then(|| file.attachment_type.clone());
+        match groups
+            .iter_mut()
+            .find(|(group_key, _)| key.is_some() && *group_key == key)
+        {
+            Some((_, group)) => group.push(file),
+            None => groups.push((key, vec![file])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .flat_map(|(key, group)| match key {
+            // A single PDF is already in its final form
+            Some(_) if !(group.len() == 1 && group[0].extension() == "pdf") => {
+                convert_group(group, output_folder, taken_names, kind, &convert)
+            }
+            _ => group,
+        })
+        .collect()
+}
+
+/// Name of the converted file of `file`, unique among the files converted for the workbook.
+/// `STM_a.png` and `STM_a.xlsx` become `STM_a.pdf` and `STM_a_xlsx.pdf`.
+fn converted_file_name(file: &PlannedAttachment, taken_names: &mut HashSet<String>) -> String {
+    let candidates = [
+        format!("{}.pdf", file.stem()),
+        format!("{}_{}.pdf", file.stem(), file.extension()),
+    ];
+    let file_name = candidates
+        .into_iter()
+        .chain((2..).map(|n| format!("{}_{}.pdf", file.stem(), n)))
+        .find(|name| !taken_names.contains(&name.to_lowercase()))
+        .unwrap_or_default();
+    taken_names.insert(file_name.to_lowercase());
+    file_name
+}
+
+fn convert_group(
+    group: Vec<PlannedAttachment>,
+    output_folder: &Path,
+    taken_names: &mut HashSet<String>,
+    kind: &str,
+    convert: impl Fn(&[PathBuf], &str) -> anyhow::Result<Vec<u8>>,
+) -> Vec<PlannedAttachment> {
+    let first = group[0].clone();
+    let sources = group
+        .iter()
+        .map(|file| file.path.clone())
+        .collect::<Vec<_>>();
+
+    // Files converted earlier in the run are replaced by the merged file, their names are free
+    for source in sources.iter().filter(|s| s.starts_with(output_folder)) {
+        if let Some(name) = source.file_name() {
+            taken_names.remove(&name.to_string_lossy().to_lowercase());
+        }
+    }
+    let file_name = converted_file_name(&first, taken_names);
+    let output_path = output_folder.join(&file_name);
+
+    let result = convert(&sources, &first.file_name).and_then(|content| {
+        // Intermediate files converted earlier in the run are not originals
+        for source in sources
+            .iter()
+            .filter(|source| !source.starts_with(output_folder))
+        {
+            let original = output_folder
+                .with_file_name(ORIGINALS_FOLDER)
+                .join(source.file_name().unwrap_or_default());
+            std::fs::copy(source, &original)
+                .with_context(|| format!("Không thể lưu bản gốc của file {:#?}", source))?;
+        }
+        std::fs::write(&output_path, content)
+            .with_context(|| format!("Không thể ghi file {:#?}", output_path))
+    });
+
+    if let Err(err) = result {
+        log::warn!(
+            "Không thể chuyển {} sang PDF, gửi các file gốc {:?}: {:?}",
+            kind,
+            group.iter().map(|file| &file.file_name).collect::<Vec<_>>(),
+            err
+        );
+        return group;
+    }
+
+    let mut descriptions: Vec<String> = vec![];
+    for file in group.iter() {
+        if !file.description.is_empty() && !descriptions.contains(&file.description) {
+            descriptions.push(file.description.clone());
+        }
+    }
+
+    vec![PlannedAttachment {
+        path: output_path,
+        file_name,
+        attachment_type: first.attachment_type,
+        description: descriptions.join("; "),
+        page_count: None,
+        order: group.iter().filter_map(|file| file.order).min(),
+    }]
+}
+
+/// One image per A4 page, in the orientation of the image, scaled to fit the margins.
+fn images_to_pdf(sources: &[PathBuf], _title: &str) -> anyhow::Result<Vec<u8>> {
+    const MARGIN: f32 = 20.0;
+
+    let mut writer = PdfWriter::new();
+    for source in sources {
+        let image = PdfImage::from_file(source)?;
+        let mut page = match image.width > image.height {
+            true => Page::a4_landscape(),
+            false => Page::a4_portrait(),
+        };
+
+        let (max_width, max_height) = (page.width - 2.0 * MARGIN, page.height - 2.0 * MARGIN);
+        let scale = (max_width / image.width as f32).min(max_height / image.height as f32);
+        let (width, height) = (image.width as f32 * scale, image.height as f32 * scale);
+        let (x, y) = ((page.width - width) / 2.0, (page.height - height) / 2.0);
+
+        writer.image(&mut page, image, x, y, width, height);
+        writer.add_page(page)?;
+    }
+    writer.finish()
+}
+
+pub(crate) fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::DateTime(value) => match value.as_datetime() {
+            Some(datetime) if datetime.time() == chrono::NaiveTime::MIN => {
+                datetime.format("%d/%m/%Y").to_string()
+            }
+            Some(datetime) => datetime.format("%d/%m/%Y %H:%M:%S").to_string(),
+            None => value.to_string(),
+        },
+        Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+            format!("{}", *value as i64)
+        }
+        Data::Float(value) => format!("{:.2}", value),
+        Data::String(value) => value.trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Renders each visible sheet as a table on landscape A4 pages, the first row is repeated as the
+/// header of every page and columns are narrowed to fit the page width.
+fn excel_to_pdf(sources: &[PathBuf], title: &str) -> anyhow::Result<Vec<u8>> {
+    let mut writer = PdfWriter::new();
+    for source in sources {
+        let mut workbook = calamine::open_workbook_auto(source)
+            .with_context(|| format!("Không thể mở file Excel {:#?}", source))?;
+        let sheets = workbook
+            .sheets_metadata()
+            .iter()
+            .filter(|sheet| sheet.visible == calamine::SheetVisible::Visible)
+            .map(|sheet| sheet.name.clone())
+            .collect::<Vec<_>>();
+
+        for sheet in sheets {
+            let range = workbook
+                .worksheet_range(&sheet)
+                .with_context(|| format!("Không thể đọc sheet '{}'", sheet))?;
+            let rows = range
+                .rows()
+                .map(|row| row.iter().map(cell_text).collect::<Vec<_>>())
+                .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+                .collect::<Vec<_>>();
+            let Some((header, body)) = rows.split_first() else {
+                continue;
+            };
+
+            writer.table(&format!("{} - {}", title, sheet), header, body, &[])?;
+        }
+    }
+
+    match writer.page_count() {
+        0 => Err(anyhow::anyhow!("File Excel không có dữ liệu")),
+        _ => writer.finish(),
+    }
+}
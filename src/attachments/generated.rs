@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::{
+    attachments::{
+        convert::{PlannedAttachment, conversion_folder},
+        statement::{Statement, find_transactions_file, read_transactions, reconcile_statement},
+    },
+    codes::document_type::DocumentType,
+    diagram::{Diagram, money_flow::money_flow_diagram, relationship::relationship_diagram},
+    excel::attachment_folder,
+    payload::{section2::Section2, section3::Section3, section4::TransactionInfo},
+    rules::report_violations,
+};
+
+const GENERATED_FOLDER: &str = "tao_tu_dong";
+
+/// Folder in the local archive holding the attachments generated from the report data.
+pub fn generated_folder(file_path: &Path) -> PathBuf {
+    conversion_folder(file_path).join(GENERATED_FOLDER)
+}
+
+fn has_type(planned: &[PlannedAttachment], code: &str) -> bool {
+    planned
+        .iter()
+        .any(|file| file.attachment_type.as_str() == code)
+}
+
+/// Content of an attachment generated from the report data, rendered when the report is
+/// submitted.
+#[derive(Debug, Clone)]
+enum GeneratedContent {
+    Diagram(Diagram),
+    Statement(Statement),
+}
+
+/// An illustration the analysts did not supply, drawn from the report data.
+#[derive(Debug, Clone)]
+pub struct GeneratedAttachment {
+    pub code: &'static str,
+    pub description: &'static str,
+    stem: &'static str,
+    content: GeneratedContent,
+}
+
+impl GeneratedAttachment {
+    /// Writes the PDF, and the SVG or Excel version it is drawn from, to `folder`.
+    fn save(&self, folder: &Path) -> anyhow::Result<PathBuf> {
+        match &self.content {
+            GeneratedContent::Diagram(diagram) => diagram.save(folder, self.stem),
+            GeneratedContent::Statement(statement) => statement.save(folder, self.stem),
+        }
+        .with_context(|| format!("Không thể tạo file {}", self.description.to_lowercase()))
+    }
+}
+
+/// Sections of the report the generated attachments are drawn from, as read with the payload.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReportData<'a> {
+    pub section_2: Option<&'a Section2>,
+    pub section_3: Option<&'a Section3>,
+    pub transaction_info: Option<&'a TransactionInfo>,
+}
+
+/// Lists the illustrations the analysts did not supply and that can be drawn from the report
+/// data. Nothing is written until the report is submitted, see [`save_generated_attachments`].
+pub fn plan_generated_attachments(
+    planned: &[PlannedAttachment],
+    data: ReportData,
+    file_path: &Path,
+) -> anyhow::Result<Vec<GeneratedAttachment>> {
+    let mut generated = vec![];
+
+    if !has_type(planned, "FLW")
+        && let Some(diagram) = data.transaction_info.and_then(money_flow_diagram)
+    {
+        generated.push(GeneratedAttachment {
+            code: "FLW",
+            description: "Minh họa dòng tiền",
+            stem: "FLW_Minh họa dòng tiền",
+            content: GeneratedContent::Diagram(diagram),
+        });
+    }
+
+    if !has_type(planned, "REL")
+        && let Some(diagram) =
+            relationship_diagram(data.section_2, data.section_3, data.transaction_info)
+    {
+        generated.push(GeneratedAttachment {
+            code: "REL",
+            description: "Minh họa mối quan hệ khách hàng",
+            stem: "REL_Minh họa mối quan hệ khách hàng",
+            content: GeneratedContent::Diagram(diagram),
+        });
+    }
+
+    if !has_type(planned, "STM")
+        && let Some(info) = data.transaction_info
+        && let Some(transactions_file) = find_transactions_file(&attachment_folder(file_path))
+    {
+        let transactions = read_transactions(&transactions_file).with_context(|| {
+            format!(
+                "Không thể đọc file dữ liệu giao dịch {:#?}",
+                transactions_file
+            )
+        })?;
+        let statement = Statement::from_transactions(transactions, info);
+
+        match statement.is_empty() {
+            true => log::warn!(
+                "File dữ liệu giao dịch {:#?} không có giao dịch nào của các tài khoản tại Phần IV trong thời gian giao dịch đáng ngờ, không tạo bảng kê",
+                transactions_file
+            ),
+            false => {
+                report_violations(
+                    &reconcile_statement(&statement, info),
+                    &format!(
+                        "Bảng kê giao dịch từ file {:#?} không khớp với Phần IV - Thông tin giao dịch",
+                        transactions_file
+                    ),
+                )?;
+
+                generated.push(GeneratedAttachment {
+                    code: "STM",
+                    description: "Bảng kê giao dịch",
+                    stem: "STM_Bảng kê giao dịch",
+                    content: GeneratedContent::Statement(statement),
+                });
+            }
+        }
+    }
+
+    Ok(generated)
+}
+
+/// Writes the generated attachments to the local archive. The SVG version of a diagram is kept
+/// next to its PDF so that it can be edited and supplied by hand instead.
+pub fn save_generated_attachments(
+    generated: &[GeneratedAttachment],
+    file_path: &Path,
+) -> anyhow::Result<Vec<PlannedAttachment>> {
+    let folder = generated_folder(file_path);
+
+    generated
+        .iter()
+        .map(|attachment| {
+            let path = attachment.save(&folder)?;
+            let file_name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            log::info!(
+                "Tự động tạo tài liệu đính kèm '{}': {:#?}",
+                attachment.description,
+                path
+            );
+
+            Ok(PlannedAttachment {
+                path,
+                file_name,
+                attachment_type: attachment.code.to_string().validate_document_type()?,
+                description: format!("{} (tạo tự động)", attachment.description),
+                page_count: None,
+                order: None,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    payload::section6::Attachment,
+    response::UploadedAttachment,
+    rules::{Severity, Violation},
+};
+
+/// Files with the same content attached more than once under different names.
+pub fn check_duplicate_attachments(attachments: &[Attachment]) -> Vec<Violation> {
+    let by_hash = attachments
+        .iter()
+        .filter_map(|attachment| {
+            attachment
+                .file_hash
+                .as_ref()
+                .map(|hash| (hash, attachment.file_name.clone().unwrap_or_default()))
+        })
+        .fold(
+            BTreeMap::<&String, Vec<String>>::new(),
+            |mut acc, (hash, name)| {
+                acc.entry(hash).or_default().push(name);
+                acc
+            },
+        );
+
+    by_hash
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(hash, names)| Violation {
+            rule_id: "ATT-008",
+            severity: Severity::Warning,
+            message: format!(
+                "Các file {} có nội dung giống hệt nhau (SHA-256 {})",
+                names
+                    .iter()
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                hash
+            ),
+        })
+        .collect()
+}
+
+/// Compares the attachment list returned by the portal with the files that were sent: every file
+/// must be listed with the same size, and the same hash when the portal returns one.
+pub fn verify_uploaded_attachments(
+    sent: &[Attachment],
+    uploaded: &[UploadedAttachment],
+) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    for attachment in sent {
+        let file_name = attachment.file_name.clone().unwrap_or_default();
+        let Some(received) = uploaded.iter().find(|received| {
+            received
+                .file_name
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(&file_name))
+        }) else {
+            violations.push(Violation {
+                rule_id: "ATT-009",
+                severity: Severity::Error,
+                message: format!(
+                    "File '{}' đã gửi nhưng không có trong danh sách tài liệu đính kèm của website NHNN",
+                    file_name
+                ),
+            });
+            continue;
+        };
+
+        if let (Some(sent_size), Some(received_size)) = (attachment.file_size, received.file_size)
+            && sent_size != received_size
+        {
+            violations.push(Violation {
+                rule_id: "ATT-010",
+                severity: Severity::Error,
+                message: format!(
+                    "File '{}' có dung lượng {} byte trên website NHNN, khác với {} byte đã gửi",
+                    file_name, received_size, sent_size
+                ),
+            });
+        }
+
+        if let (Some(sent_hash), Some(received_hash)) =
+            (attachment.file_hash.as_ref(), received.hash.as_ref())
+            && !sent_hash.eq_ignore_ascii_case(received_hash.trim())
+        {
+            violations.push(Violation {
+                rule_id: "ATT-010",
+                severity: Severity::Error,
+                message: format!(
+                    "File '{}' có mã SHA-256 {} trên website NHNN, khác với {} đã gửi",
+                    file_name, received_hash, sent_hash
+                ),
+            });
+        }
+    }
+
+    for received in uploaded {
+        let file_name = received.file_name.clone().unwrap_or_default();
+        let was_sent = sent.iter().any(|attachment| {
+            attachment
+                .file_name
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(&file_name))
+        });
+        if !was_sent {
+            violations.push(Violation {
+                rule_id: "ATT-011",
+                severity: Severity::Warning,
+                message: format!(
+                    "Website NHNN có file đính kèm '{}' không nằm trong các file đã gửi",
+                    file_name
+                ),
+            });
+        }
+    }
+
+    violations
+}
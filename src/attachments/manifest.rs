@@ -0,0 +1,273 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes::document_type::{DOCUMENT_TYPES, DocumentType},
+    payload::code::AttachmentType,
+};
+
+/// Manifest in the attachment folder listing the files with their type and description.
+pub const MANIFEST_FILE_STEM: &str = "attachments";
+const MANIFEST_EXTENSIONS: [&str; 2] = ["json", "csv"];
+
+pub const COLUMN_FILE_NAME: &str = "Tên file";
+pub const COLUMN_DOCUMENT_TYPE: &str = "Loại tài liệu";
+pub const COLUMN_DESCRIPTION: &str = "Mô tả";
+pub const COLUMN_PAGE_COUNT: &str = "Số trang";
+pub const COLUMN_ORDER: &str = "Thứ tự";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ManifestEntry {
+    #[serde(rename = "Tên file")]
+    pub file_name: String,
+    #[serde(rename = "Loại tài liệu", default)]
+    pub document_type: Option<String>,
+    #[serde(rename = "Mô tả", default)]
+    pub description: Option<String>,
+    #[serde(rename = "Số trang", default)]
+    pub page_count: Option<i32>,
+    #[serde(rename = "Thứ tự", default)]
+    pub order: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ManifestFile {
+    #[serde(rename = "tài liệu đính kèm")]
+    entries: Vec<ManifestEntry>,
+}
+
+impl ManifestEntry {
+    /// Accepts either the code (`STM`) or the name (`Bảng kê`) of the document type.
+    pub fn attachment_type(&self) -> anyhow::Result<Option<AttachmentType>> {
+        let document_type = self.document_type.clone().unwrap_or_default();
+        let document_type = document_type.trim();
+        if document_type.is_empty() {
+            return Ok(None);
+        }
+
+        let code = document_type.to_uppercase();
+        let attachment_type = match DOCUMENT_TYPES.iter().any(|(c, _)| *c == code) {
+            true => code.validate_document_type(),
+            false => document_type.to_string().to_document_type(),
+        }
+        .with_context(|| {
+            format!(
+                "Loại tài liệu của file '{}' trong danh sách tài liệu đính kèm không hợp lệ",
+                self.file_name
+            )
+        })?;
+        Ok(Some(attachment_type))
+    }
+}
+
+pub fn is_manifest_file(path: &Path) -> bool {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    stem.eq_ignore_ascii_case(MANIFEST_FILE_STEM)
+        && MANIFEST_EXTENSIONS.contains(&extension.as_str())
+}
+
+pub fn find_manifest_file(folder: &Path) -> Option<PathBuf> {
+    MANIFEST_EXTENSIONS.iter().find_map(|extension| {
+        std::fs::read_dir(folder)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| {
+                is_manifest_file(path)
+                    && path
+                        .extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+            })
+    })
+}
+
+pub fn read_manifest_file(path: &Path) -> anyhow::Result<Vec<ManifestEntry>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Không thể đọc file danh sách tài liệu đính kèm {:#?}", path))?;
+    let content = content.trim_start_matches('\u{feff}');
+
+    let is_json = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+    let entries = match is_json {
+        true => serde_json::from_str::<ManifestFile>(content)
+            .map(|manifest| manifest.entries)
+            .map_err(anyhow::Error::from),
+        false => parse_csv_manifest(content),
+    }
+    .with_context(|| format!("File danh sách tài liệu đính kèm {:#?} không hợp lệ", path))?;
+
+    Ok(entries)
+}
+
+/// Splits a CSV line, honouring double-quoted fields.
+pub(crate) fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match (ch, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.trim().is_empty() => in_quotes = true,
+            (ch, false) if ch == delimiter => fields.push(std::mem::take(&mut field)),
+            (ch, _) => field.push(ch),
+        }
+    }
+    fields.push(field);
+
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+fn parse_csv_manifest(content: &str) -> anyhow::Result<Vec<ManifestEntry>> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .context("File không có dòng tiêu đề")?
+        .to_string();
+
+    // Excel saves CSV with semicolons when the decimal separator is a comma
+    let delimiter = match header.contains(';') && !header.contains(',') {
+        true => ';',
+        false => ',',
+    };
+    let columns = split_csv_line(&header, delimiter);
+
+    let rows = lines
+        .map(|line| {
+            let fields = split_csv_line(line, delimiter);
+            columns
+                .iter()
+                .cloned()
+                .zip(fields)
+                .collect::<std::collections::HashMap<_, _>>()
+        })
+        .collect::<Vec<_>>();
+
+    manifest_from_rows(&columns, rows)
+}
+
+/// Builds the manifest from rows keyed by column header, shared by the CSV file and the sheet.
+pub fn manifest_from_rows(
+    columns: &[String],
+    rows: Vec<std::collections::HashMap<String, String>>,
+) -> anyhow::Result<Vec<ManifestEntry>> {
+    let find_column = |name: &str| {
+        columns
+            .iter()
+            .find(|column| column.trim().to_lowercase() == name.to_lowercase())
+            .cloned()
+    };
+    let file_column = find_column(COLUMN_FILE_NAME)
+        .with_context(|| format!("Không tìm thấy cột '{}'", COLUMN_FILE_NAME))?;
+    let type_column = find_column(COLUMN_DOCUMENT_TYPE);
+    let description_column = find_column(COLUMN_DESCRIPTION);
+    let page_column = find_column(COLUMN_PAGE_COUNT);
+    let order_column = find_column(COLUMN_ORDER);
+
+    rows.into_iter()
+        .map(|row| -> anyhow::Result<Option<ManifestEntry>> {
+            let value = |column: &Option<String>| {
+                column
+                    .as_ref()
+                    .and_then(|column| row.get(column))
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            let number = |column: &Option<String>, name: &str| {
+                value(column)
+                    .map(|v| {
+                        v.parse::<f64>()
+                            .ok()
+                            .filter(|n| n.fract() == 0.0 && *n >= 0.0)
+                            .map(|n| n as i32)
+                            .with_context(|| format!("{} '{}' không hợp lệ", name, v))
+                    })
+                    .transpose()
+            };
+
+            let Some(file_name) = value(&Some(file_column.clone())) else {
+                return Ok(None);
+            };
+
+            Ok(Some(ManifestEntry {
+                file_name,
+                document_type: value(&type_column),
+                description: value(&description_column),
+                page_count: number(&page_column, COLUMN_PAGE_COUNT)?,
+                order: number(&order_column, COLUMN_ORDER)?,
+            }))
+        })
+        .enumerate()
+        .filter_map(|(n_row, entry)| {
+            entry
+                .with_context(|| format!("Lỗi dữ liệu khi xử lý dòng số {}", n_row + 2))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Checks the manifest against the files found in the attachment folder: every listed file must
+/// exist and be listed once, files that are not listed only raise a warning.
+pub fn check_manifest(
+    entries: &[ManifestEntry],
+    files: &[String],
+    source: &str,
+) -> anyhow::Result<()> {
+    let mut errors = vec![];
+
+    for (idx, entry) in entries.iter().enumerate() {
+        if entries[..idx]
+            .iter()
+            .any(|other| other.file_name.eq_ignore_ascii_case(&entry.file_name))
+        {
+            errors.push(format!("File '{}' được liệt kê nhiều lần", entry.file_name));
+        }
+        if !files
+            .iter()
+            .any(|file| file.eq_ignore_ascii_case(&entry.file_name))
+        {
+            errors.push(format!(
+                "File '{}' không có trong folder đính kèm",
+                entry.file_name
+            ));
+        }
+    }
+
+    for file in files.iter() {
+        if !entries
+            .iter()
+            .any(|entry| entry.file_name.eq_ignore_ascii_case(file))
+        {
+            log::warn!(
+                "File đính kèm '{}' không có trong {}, loại tài liệu và mô tả được lấy theo tên file",
+                file,
+                source
+            );
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow::anyhow!(
+            "Danh sách tài liệu đính kèm trong {} không khớp với folder đính kèm:\n{}",
+            source,
+            errors.join("\n")
+        )),
+    }
+}
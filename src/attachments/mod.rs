@@ -0,0 +1,9 @@
+pub mod convert;
+pub mod generated;
+pub mod integrity;
+pub mod manifest;
+pub mod pages;
+pub mod policy;
+pub mod scan;
+pub mod statement;
+pub mod upload;
@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use anyhow::Context;
+use quick_xml::events::Event;
+
+/// Number of pages the portal should record for an attachment. Formats without a notion of
+/// pages (images and anything unknown) count as a single page and are not read.
+pub fn count_pages(path: &Path) -> anyhow::Result<i32> {
+    let extension = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let read = || std::fs::read(path).with_context(|| format!("Không thể đọc file {:#?}", path));
+
+    match extension.as_str() {
+        "pdf" => {
+            let doc = lopdf::Document::load_mem(&read()?)
+                .with_context(|| format!("Không thể đọc được file PDF {:#?}", path))?;
+            Ok(doc.get_pages().len() as i32)
+        }
+        "docx" | "docm" => estimate_or_one(path, docx_pages(&read()?)),
+        "xlsx" | "xlsm" => estimate_or_one(path, xlsx_pages(&read()?)),
+        "tif" | "tiff" => estimate_or_one(path, tiff_frames(&read()?)),
+        _ => Ok(1),
+    }
+}
+
+fn estimate_or_one(path: &Path, pages: anyhow::Result<i32>) -> anyhow::Result<i32> {
+    match pages {
+        Ok(pages) => Ok(pages.max(1)),
+        Err(err) => {
+            log::warn!(
+                "Không thể xác định số trang của file {:#?}, tạm tính là 1 trang: {:?}",
+                path,
+                err
+            );
+            Ok(1)
+        }
+    }
+}
+
+fn read_zip_part(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Option<String> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    Some(content)
+}
+
+/// Pages saved by Word in the document properties, otherwise the page breaks in the body.
+fn docx_pages(content: &[u8]) -> anyhow::Result<i32> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(content))?;
+
+    if let Some(app) = read_zip_part(&mut archive, "docProps/app.xml") {
+        let mut reader = quick_xml::Reader::from_str(&app);
+        let mut in_pages = false;
+        loop {
+            match reader.read_event()? {
+                Event::Start(tag) if tag.local_name().as_ref() == b"Pages" => in_pages = true,
+                Event::End(tag) if tag.local_name().as_ref() == b"Pages" => in_pages = false,
+                Event::Text(text) if in_pages => {
+                    if let Ok(pages) = text.decode()?.trim().parse::<i32>()
+                        && pages > 0
+                    {
+                        return Ok(pages);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+    }
+
+    let document = read_zip_part(&mut archive, "word/document.xml")
+        .context("Không tìm thấy nội dung văn bản trong file")?;
+    let mut reader = quick_xml::Reader::from_str(&document);
+    let mut explicit_breaks = 0;
+    let mut rendered_breaks = 0;
+    loop {
+        match reader.read_event()? {
+            Event::Empty(tag) | Event::Start(tag) => match tag.local_name().as_ref() {
+                b"br" => {
+                    let is_page_break = tag.attributes().flatten().any(|attr| {
+                        attr.key.local_name().as_ref() == b"type" && attr.value.as_ref() == b"page"
+                    });
+                    if is_page_break {
+                        explicit_breaks += 1;
+                    }
+                }
+                b"lastRenderedPageBreak" => rendered_breaks += 1,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(explicit_breaks.max(rendered_breaks) + 1)
+}
+
+/// One page per visible sheet, or per print area when the sheet defines them, plus the manual
+/// page breaks of the sheet.
+fn xlsx_pages(content: &[u8]) -> anyhow::Result<i32> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(content))?;
+
+    let workbook = read_zip_part(&mut archive, "xl/workbook.xml")
+        .context("Không tìm thấy danh sách sheet trong file")?;
+    let rels = read_zip_part(&mut archive, "xl/_rels/workbook.xml.rels").unwrap_or_default();
+
+    let mut sheets = vec![];
+    let mut print_areas = HashMap::<usize, i32>::new();
+    let mut reader = quick_xml::Reader::from_str(&workbook);
+    // Sheet index and text of the print area being read, the text is split at entity references
+    let mut print_area: Option<(usize, String)> = None;
+    loop {
+        match reader.read_event()? {
+            Event::Empty(tag) | Event::Start(tag) if tag.local_name().as_ref() == b"sheet" => {
+                let attrs = attributes(&tag, &reader)?;
+                let visible = attrs.get("state").is_none_or(|state| state == "visible");
+                sheets.push((visible, attrs.get("id").cloned().unwrap_or_default()));
+            }
+            Event::Start(tag) if tag.local_name().as_ref() == b"definedName" => {
+                let attrs = attributes(&tag, &reader)?;
+                print_area = match attrs.get("name").map(|name| name.as_str()) {
+                    Some("_xlnm.Print_Area") => attrs
+                        .get("localSheetId")
+                        .and_then(|id| id.parse::<usize>().ok())
+                        .map(|sheet_idx| (sheet_idx, String::new())),
+                    _ => None,
+                };
+            }
+            Event::Text(text) => {
+                if let Some((_, areas)) = print_area.as_mut() {
+                    areas.push_str(&text.decode()?);
+                }
+            }
+            Event::GeneralRef(entity) => {
+                if let Some((_, areas)) = print_area.as_mut() {
+                    match entity.resolve_char_ref()? {
+                        Some(ch) => areas.push(ch),
+                        None => {
+                            let name = entity.decode()?;
+                            areas.push_str(
+                                quick_xml::escape::resolve_xml_entity(&name).unwrap_or_default(),
+                            );
+                        }
+                    }
+                }
+            }
+            Event::End(tag) if tag.local_name().as_ref() == b"definedName" => {
+                if let Some((sheet_idx, areas)) = print_area.take() {
+                    print_areas.insert(sheet_idx, count_print_areas(&areas));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let targets = relationship_targets(&rels)?;
+    let mut pages = 0;
+    for (sheet_idx, (visible, rel_id)) in sheets.iter().enumerate() {
+        if !visible {
+            continue;
+        }
+
+        let breaks = targets
+            .get(rel_id)
+            .map(|target| format!("xl/{}", target.trim_start_matches("/xl/")))
+            .and_then(|part| read_zip_part(&mut archive, &part))
+            .map(|sheet| page_breaks(&sheet))
+            .transpose()?
+            .unwrap_or_default();
+
+        pages += print_areas.get(&sheet_idx).copied().unwrap_or(1) + breaks;
+    }
+
+    Ok(pages)
+}
+
+/// Ranges in a print area definition such as `'R&D, 2024'!$A$1:$F$40,'R&D, 2024'!$H$1:$M$40`,
+/// commas inside a quoted sheet name do not separate ranges.
+fn count_print_areas(definition: &str) -> i32 {
+    let mut quoted = false;
+    let mut areas = 1;
+    for ch in definition.chars() {
+        match ch {
+            '\'' => quoted = !quoted,
+            ',' if !quoted => areas += 1,
+            _ => {}
+        }
+    }
+    areas
+}
+
+fn attributes(
+    tag: &quick_xml::events::BytesStart,
+    reader: &quick_xml::Reader<&[u8]>,
+) -> anyhow::Result<HashMap<String, String>> {
+    tag.attributes()
+        .map(|attr| -> anyhow::Result<(String, String)> {
+            let attr = attr?;
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+            let value = attr.decode_and_unescape_value(reader.decoder())?;
+            Ok((key, value.to_string()))
+        })
+        .collect()
+}
+
+fn relationship_targets(rels: &str) -> anyhow::Result<HashMap<String, String>> {
+    let mut reader = quick_xml::Reader::from_str(rels);
+    let mut targets = HashMap::new();
+    loop {
+        match reader.read_event()? {
+            Event::Empty(tag) | Event::Start(tag)
+                if tag.local_name().as_ref() == b"Relationship" =>
+            {
+                let attrs = attributes(&tag, &reader)?;
+                if let (Some(id), Some(target)) = (attrs.get("Id"), attrs.get("Target")) {
+                    targets.insert(id.clone(), target.clone());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(targets)
+}
+
+/// Manual row and column page breaks of a worksheet.
+fn page_breaks(sheet: &str) -> anyhow::Result<i32> {
+    let mut reader = quick_xml::Reader::from_str(sheet);
+    let mut breaks = 0;
+    loop {
+        match reader.read_event()? {
+            Event::Empty(tag) | Event::Start(tag) if tag.local_name().as_ref() == b"brk" => {
+                let is_manual = attributes(&tag, &reader)?
+                    .get("man")
+                    .is_some_and(|man| man == "1" || man == "true");
+                if is_manual {
+                    breaks += 1;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(breaks)
+}
+
+/// Number of image file directories, i.e. frames, of a TIFF or BigTIFF file.
+fn tiff_frames(content: &[u8]) -> anyhow::Result<i32> {
+    let little_endian = match content.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err(anyhow::anyhow!("File TIFF không hợp lệ")),
+    };
+
+    let corrupted = || anyhow::anyhow!("File TIFF bị hỏng hoặc không đầy đủ");
+    let read_uint = |offset: usize, size: usize| -> anyhow::Result<u64> {
+        let bytes = offset
+            .checked_add(size)
+            .and_then(|end| content.get(offset..end))
+            .ok_or_else(corrupted)?;
+        let value = match little_endian {
+            true => bytes
+                .iter()
+                .rev()
+                .fold(0u64, |acc, b| (acc << 8) | *b as u64),
+            false => bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+        };
+        Ok(value)
+    };
+
+    // Classic TIFF uses 4 bytes offsets and 12 bytes entries, BigTIFF 8 and 20
+    let (offset_size, count_size, entry_size, first_offset) = match read_uint(2, 2)? {
+        42 => (4, 2, 12, 4),
+        43 => (8, 8, 20, 8),
+        _ => return Err(anyhow::anyhow!("File TIFF không hợp lệ")),
+    };
+
+    // Offsets and counts come from the file and are checked before being used
+    let read_usize = |offset: usize, size: usize| -> anyhow::Result<usize> {
+        usize::try_from(read_uint(offset, size)?).map_err(|_| corrupted())
+    };
+
+    let mut frames = 0;
+    let mut offset = read_usize(first_offset, offset_size)?;
+    let mut visited = std::collections::HashSet::new();
+    while offset != 0 && visited.insert(offset) {
+        frames += 1;
+        let n_entries = read_usize(offset, count_size)?;
+        let next_offset = n_entries
+            .checked_mul(entry_size)
+            .and_then(|entries| entries.checked_add(count_size))
+            .and_then(|size| size.checked_add(offset))
+            .ok_or_else(corrupted)?;
+        offset = read_usize(next_offset, offset_size)?;
+    }
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn print_areas_are_split_outside_quoted_sheet_names() {
+        assert_eq!(count_print_areas("'R&D, 2024'!$A$1:$F$40"), 1);
+        assert_eq!(
+            count_print_areas("'R&D, 2024'!$A$1:$F$40,'R&D, 2024'!$H$1:$M$40"),
+            2
+        );
+        assert_eq!(count_print_areas("Sheet1!$A$1:$B$2"), 1);
+    }
+
+    #[test]
+    fn tiff_frames_follow_the_directory_chain() {
+        // Two directories, the first with a single entry
+        let mut little_endian = b"II\x2a\x00\x08\x00\x00\x00\x01\x00".to_vec();
+        little_endian.extend([0u8; 12]);
+        little_endian.extend([26, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(tiff_frames(&little_endian).unwrap(), 2);
+
+        let big_endian = b"MM\x00\x2a\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00";
+        assert_eq!(tiff_frames(big_endian).unwrap(), 1);
+
+        // A directory pointing to itself is counted once
+        let cycle = b"II\x2a\x00\x08\x00\x00\x00\x00\x00\x08\x00\x00\x00";
+        assert_eq!(tiff_frames(cycle).unwrap(), 1);
+    }
+
+    #[test]
+    fn corrupted_tiff_offsets_are_errors() {
+        assert!(tiff_frames(b"GIF89a").is_err());
+        // First directory past the end of the file
+        assert!(tiff_frames(b"II\x2a\x00\xff\x00\x00\x00").is_err());
+        // Entry count larger than the file
+        assert!(tiff_frames(b"II\x2a\x00\x08\x00\x00\x00\xff\xff").is_err());
+        // BigTIFF offset that overflows when added to the entry size
+        let mut big_tiff = b"II\x2b\x00\x08\x00\x00\x00".to_vec();
+        big_tiff.extend(u64::MAX.to_le_bytes());
+        assert!(tiff_frames(&big_tiff).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes::document_type::DocumentType,
+    rules::{Severity, Violation},
+    template::{ReportForm, value_list_from_key},
+};
+
+const LOCAL_ATTACHMENT_POLICY: &str = "input/chinh_sach_dinh_kem.json";
+const REQUIRED_TYPES_KEY: &str = "Phần VI. Tài liệu đính kèm - Tài liệu bắt buộc";
+
+pub const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+const OLE_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AttachmentPolicy {
+    #[serde(rename = "dung lượng tối đa mỗi file (MB)")]
+    pub max_file_size_mb: f64,
+    #[serde(rename = "tổng dung lượng tối đa (MB)")]
+    pub max_total_size_mb: f64,
+    /// Attachments are sent in several requests when together they exceed this size.
+    #[serde(rename = "dung lượng tối đa mỗi lần gửi (MB)", default)]
+    pub max_request_size_mb: Option<f64>,
+    #[serde(rename = "kiểu file được phép")]
+    pub allowed_mime_types: Vec<String>,
+}
+
+pub fn load_attachment_policy() -> anyhow::Result<AttachmentPolicy> {
+    const ATTACHMENT_POLICY: &str = include_str!("../attachment_policy.json");
+
+    let local_path = Path::new(LOCAL_ATTACHMENT_POLICY);
+    if local_path.is_file() {
+        let content = std::fs::read_to_string(local_path)
+            .with_context(|| format!("Không thể đọc file {:#?}", local_path))?;
+        let parsed_result: AttachmentPolicy =
+            serde_json::from_str(&content).with_context(|| {
+                format!(
+                    "File chính sách tài liệu đính kèm {:#?} không hợp lệ",
+                    local_path
+                )
+            })?;
+        return Ok(parsed_result);
+    }
+
+    let parsed_result: AttachmentPolicy = serde_json::from_str(ATTACHMENT_POLICY)?;
+    Ok(parsed_result)
+}
+
+static ATTACHMENT_POLICY: OnceLock<AttachmentPolicy> = OnceLock::new();
+
+/// The attachment policy, loaded once and checked by `initial_setup` so that a malformed local
+/// override is reported before any workbook is read.
+pub fn attachment_policy() -> anyhow::Result<&'static AttachmentPolicy> {
+    if let Some(policy) = ATTACHMENT_POLICY.get() {
+        return Ok(policy);
+    }
+    let policy = load_attachment_policy()?;
+    Ok(ATTACHMENT_POLICY.get_or_init(|| policy))
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Content problems the portal would reject: password protection or a file that cannot be read
+/// in its declared format.
+fn check_content(path: &Path, content: &[u8]) -> Option<Violation> {
+    let extension = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let file_name = file_name_of(path);
+
+    let corrupt = |reason: &str| {
+        Some(Violation {
+            rule_id: "ATT-006",
+            severity: Severity::Error,
+            message: format!(
+                "File '{}' bị hỏng hoặc không đúng định dạng: {}",
+                file_name, reason
+            ),
+        })
+    };
+    let encrypted = || {
+        Some(Violation {
+            rule_id: "ATT-005",
+            severity: Severity::Error,
+            message: format!(
+                "File '{}' được đặt mật khẩu, cần gỡ mật khẩu trước khi nộp",
+                file_name
+            ),
+        })
+    };
+
+    match extension.as_str() {
+        "pdf" => match lopdf::Document::load_mem(content) {
+            Err(err) => corrupt(&err.to_string()),
+            Ok(doc) if doc.is_encrypted() && doc.authenticate_password("").is_err() => encrypted(),
+            Ok(doc) if doc.get_pages().is_empty() => corrupt("file PDF không có trang nào"),
+            Ok(_) => None,
+        },
+        // Password protected Office files are stored as an OLE container instead of a zip archive
+        "docx" | "docm" | "xlsx" | "xlsm" => match content.starts_with(&OLE_MAGIC) {
+            true => encrypted(),
+            false => match zip::ZipArchive::new(Cursor::new(content)) {
+                Err(err) => corrupt(&err.to_string()),
+                Ok(mut archive) => {
+                    let main_part = match extension.starts_with("doc") {
+                        true => "word/document.xml",
+                        false => "xl/workbook.xml",
+                    };
+                    let readable = archive
+                        .by_name(main_part)
+                        .map(|mut part| part.read_to_end(&mut vec![]).is_ok())
+                        .unwrap_or(false);
+                    match readable {
+                        true => None,
+                        false => corrupt(&format!("không đọc được {}", main_part)),
+                    }
+                }
+            },
+        },
+        "doc" | "xls" => match content.starts_with(&OLE_MAGIC) {
+            true => None,
+            false => corrupt("không phải file Office 97-2003"),
+        },
+        "png" => match content.starts_with(b"\x89PNG\r\n\x1a\n") {
+            true => None,
+            false => corrupt("không phải file PNG"),
+        },
+        "jpg" | "jpeg" => match content.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            true => None,
+            false => corrupt("không phải file JPEG"),
+        },
+        "tif" | "tiff" => match content.starts_with(b"II") || content.starts_with(b"MM") {
+            true => None,
+            false => corrupt("không phải file TIFF"),
+        },
+        _ => None,
+    }
+}
+
+/// Size, type and content checks of the files in the attachment folder, run before anything is
+/// sent to the portal.
+pub fn check_attachment_files(files: &[PathBuf]) -> anyhow::Result<Vec<Violation>> {
+    let policy = attachment_policy()?;
+    let mut violations = vec![];
+    let mut total_size = 0u64;
+
+    for path in files {
+        let file_name = file_name_of(path);
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Không thể đọc thông tin file {:#?}", path))?
+            .len();
+        total_size += size;
+
+        if size == 0 {
+            violations.push(Violation {
+                rule_id: "ATT-001",
+                severity: Severity::Error,
+                message: format!("File '{}' không có nội dung (0 byte)", file_name),
+            });
+            continue;
+        }
+
+        let oversized = size as f64 > policy.max_file_size_mb * BYTES_PER_MB;
+        if oversized {
+            violations.push(Violation {
+                rule_id: "ATT-002",
+                severity: Severity::Error,
+                message: format!(
+                    "File '{}' có dung lượng {:.2} MB, vượt quá giới hạn {} MB",
+                    file_name,
+                    size as f64 / BYTES_PER_MB,
+                    policy.max_file_size_mb
+                ),
+            });
+        }
+
+        let mime = mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string();
+        if !policy
+            .allowed_mime_types
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&mime))
+        {
+            violations.push(Violation {
+                rule_id: "ATT-004",
+                severity: Severity::Error,
+                message: format!(
+                    "File '{}' có kiểu '{}' không được phép đính kèm",
+                    file_name, mime
+                ),
+            });
+            continue;
+        }
+        if oversized {
+            continue;
+        }
+
+        let content =
+            std::fs::read(path).with_context(|| format!("Không thể đọc file {:#?}", path))?;
+        violations.extend(check_content(path, &content));
+    }
+
+    if total_size as f64 > policy.max_total_size_mb * BYTES_PER_MB {
+        violations.push(Violation {
+            rule_id: "ATT-003",
+            severity: Severity::Error,
+            message: format!(
+                "Tổng dung lượng các file đính kèm là {:.2} MB, vượt quá giới hạn {} MB",
+                total_size as f64 / BYTES_PER_MB,
+                policy.max_total_size_mb
+            ),
+        });
+    }
+
+    Ok(violations)
+}
+
+/// Document types required by the report form.
+pub fn check_required_types(
+    attachment_types: &[&str],
+    report_form: &ReportForm,
+) -> anyhow::Result<Vec<Violation>> {
+    let mut violations = vec![];
+
+    for doc_type in value_list_from_key(report_form, REQUIRED_TYPES_KEY)?.into_iter() {
+        let doc_type_code = doc_type
+            .to_document_type()
+            .with_context(|| format!("Loại tài liệu bắt buộc không phù hợp {}", doc_type))?;
+
+        let found = attachment_types
+            .iter()
+            .any(|attachment_type| *attachment_type == doc_type_code.as_str());
+
+        if !found {
+            violations.push(Violation {
+                rule_id: "ATT-007",
+                severity: Severity::Error,
+                message: format!(
+                    "Không tìm thấy loại tài liệu bắt buộc \"{}\". Đặt tên các file '{}' chứa tiền tố '{}_' hoặc khai báo trong danh sách tài liệu đính kèm",
+                    doc_type, doc_type, doc_type_code
+                ),
+            });
+        }
+    }
+
+    Ok(violations)
+}
@@ -0,0 +1,396 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rules::{Severity, Violation},
+    utils::setup::scan_attachments_from_args,
+};
+
+const LOCAL_SCAN_CONFIG: &str = "input/cau_hinh_quet_ma_doc.json";
+const FILE_PLACEHOLDER: &str = "{file}";
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+fn default_timeout_secs() -> u64 {
+    300
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ScannerKind {
+    #[serde(rename = "lệnh")]
+    Command,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScanConfig {
+    #[serde(rename = "quét mã độc")]
+    pub enabled: bool,
+    #[serde(rename = "trình quét")]
+    pub kind: ScannerKind,
+    #[serde(rename = "lệnh", default)]
+    pub command: String,
+    /// `{file}` is replaced by the path of the scanned file, which is appended when absent.
+    #[serde(rename = "tham số", default)]
+    pub args: Vec<String>,
+    #[serde(rename = "mã thoát sạch", default)]
+    pub clean_codes: Vec<i32>,
+    #[serde(rename = "mã thoát nhiễm mã độc", default)]
+    pub infected_codes: Vec<i32>,
+    #[serde(rename = "thời gian chờ (giây)", default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+pub fn load_scan_config() -> anyhow::Result<ScanConfig> {
+    const SCAN_CONFIG: &str = include_str!("../scan.json");
+
+    let local_path = Path::new(LOCAL_SCAN_CONFIG);
+    if local_path.is_file() {
+        let content = std::fs::read_to_string(local_path)
+            .with_context(|| format!("Không thể đọc file {:#?}", local_path))?;
+        let parsed_result: ScanConfig = serde_json::from_str(&content).with_context(|| {
+            format!(
+                "File cấu hình quét mã độc tài liệu đính kèm {:#?} không hợp lệ",
+                local_path
+            )
+        })?;
+        return Ok(parsed_result);
+    }
+
+    let parsed_result: ScanConfig = serde_json::from_str(SCAN_CONFIG)?;
+    Ok(parsed_result)
+}
+
+static SCAN_CONFIG: OnceLock<ScanConfig> = OnceLock::new();
+
+/// The scanner settings, read on first use.
+pub fn scan_config() -> anyhow::Result<&'static ScanConfig> {
+    if let Some(config) = SCAN_CONFIG.get() {
+        return Ok(config);
+    }
+    let config = load_scan_config()?;
+    Ok(SCAN_CONFIG.get_or_init(|| config))
+}
+
+/// Scanning runs when enabled in the configuration or with `--scan-attachments`.
+pub fn scan_enabled() -> anyhow::Result<bool> {
+    Ok(scan_config()?.enabled || scan_attachments_from_args())
+}
+
+/// Every file sent to the portal must be scanned, reports are not submitted without a scanner.
+pub fn require_scanner() -> anyhow::Result<()> {
+    if !scan_enabled()? {
+        return Err(anyhow::anyhow!(
+            "Quét mã độc tài liệu đính kèm đang tắt. Bật 'quét mã độc' trong file cấu hình {:#?} hoặc chạy với tham số --scan-attachments, báo cáo không được gửi khi chưa quét mã độc",
+            LOCAL_SCAN_CONFIG
+        ));
+    }
+    if scan_config()?.command.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "Chưa khai báo lệnh quét mã độc trong file cấu hình {:#?}, báo cáo không được gửi khi chưa quét mã độc",
+            LOCAL_SCAN_CONFIG
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanOutcome {
+    Clean,
+    /// The scanner found malware, with its report.
+    Infected(String),
+    /// The scanner could not give a verdict, with the reason.
+    Unscannable(String),
+}
+
+/// Malware scanner run on every attachment before it leaves the network.
+pub trait AttachmentScanner {
+    fn name(&self) -> String;
+    fn scan(&self, path: &Path) -> ScanOutcome;
+}
+
+/// Runs an external command per file and reads the verdict from its exit code.
+pub struct CommandScanner {
+    pub command: String,
+    pub args: Vec<String>,
+    pub clean_codes: Vec<i32>,
+    pub infected_codes: Vec<i32>,
+    /// A scan still running after this long is stopped and the file is reported as unscannable.
+    pub timeout: Duration,
+}
+
+impl CommandScanner {
+    pub fn from_config(config: &ScanConfig) -> Self {
+        CommandScanner {
+            command: config.command.clone(),
+            args: config.args.clone(),
+            clean_codes: config.clean_codes.clone(),
+            infected_codes: config.infected_codes.clone(),
+            timeout: Duration::from_secs(config.timeout_secs),
+        }
+    }
+
+    fn args_for(&self, path: &Path) -> Vec<String> {
+        let file = path.to_string_lossy();
+        let mut args = self
+            .args
+            .iter()
+            .map(|arg| arg.replace(FILE_PLACEHOLDER, &file))
+            .collect::<Vec<_>>();
+        if !self.args.iter().any(|arg| arg.contains(FILE_PLACEHOLDER)) {
+            args.push(file.to_string());
+        }
+        args
+    }
+}
+
+impl AttachmentScanner for CommandScanner {
+    fn name(&self) -> String {
+        self.command.clone()
+    }
+
+    fn scan(&self, path: &Path) -> ScanOutcome {
+        let mut child = match Command::new(&self.command)
+            .args(self.args_for(path))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                return ScanOutcome::Unscannable(format!(
+                    "không chạy được lệnh '{}': {}",
+                    self.command, err
+                ));
+            }
+        };
+
+        // The pipes are drained while waiting so that a verbose scanner does not block on a full pipe
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|pipe| Box::new(pipe) as Box<dyn Read + Send>),
+            child
+                .stderr
+                .take()
+                .map(|pipe| Box::new(pipe) as Box<dyn Read + Send>),
+        ]
+        .map(|pipe| {
+            std::thread::spawn(move || {
+                let mut text = vec![];
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut text);
+                }
+                text
+            })
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() < self.timeout => std::thread::sleep(POLL_INTERVAL),
+                Ok(None) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return ScanOutcome::Unscannable(format!(
+                        "lệnh '{}' chạy quá {} giây",
+                        self.command,
+                        self.timeout.as_secs_f64()
+                    ));
+                }
+                Err(err) => {
+                    let _ = child.kill();
+                    return ScanOutcome::Unscannable(format!(
+                        "không chờ được lệnh '{}': {}",
+                        self.command, err
+                    ));
+                }
+            }
+        };
+
+        let report = readers
+            .map(|reader| reader.join().unwrap_or_default())
+            .iter()
+            .map(|text| String::from_utf8_lossy(text).trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let with_report = |reason: String| match report.is_empty() {
+            true => reason,
+            false => format!("{} - {}", reason, report),
+        };
+
+        match status.code() {
+            Some(code) if self.clean_codes.contains(&code) => ScanOutcome::Clean,
+            Some(code) if self.infected_codes.contains(&code) => ScanOutcome::Infected(report),
+            Some(code) => ScanOutcome::Unscannable(with_report(format!("mã thoát {}", code))),
+            None => ScanOutcome::Unscannable(with_report("lệnh bị dừng".to_string())),
+        }
+    }
+}
+
+/// Local scanner for tests: a file is infected when it contains one of the signatures.
+#[cfg(test)]
+pub struct StubScanner {
+    pub signatures: Vec<Vec<u8>>,
+}
+
+#[cfg(test)]
+impl AttachmentScanner for StubScanner {
+    fn name(&self) -> String {
+        "giả lập".to_string()
+    }
+
+    fn scan(&self, path: &Path) -> ScanOutcome {
+        let content = match std::fs::read(path) {
+            Ok(content) => content,
+            Err(err) => return ScanOutcome::Unscannable(format!("không đọc được file: {}", err)),
+        };
+
+        match self.signatures.iter().find(|signature| {
+            !signature.is_empty()
+                && content
+                    .windows(signature.len())
+                    .any(|window| window == signature.as_slice())
+        }) {
+            Some(signature) => ScanOutcome::Infected(format!(
+                "chứa mẫu nhận diện '{}'",
+                String::from_utf8_lossy(signature)
+            )),
+            None => ScanOutcome::Clean,
+        }
+    }
+}
+
+pub fn configured_scanner() -> anyhow::Result<Box<dyn AttachmentScanner>> {
+    let config = scan_config()?;
+    match config.kind {
+        ScannerKind::Command => Ok(Box::new(CommandScanner::from_config(config))),
+    }
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Scans every file, infected files and files the scanner could not check are both errors.
+pub fn scan_attachment_files(scanner: &dyn AttachmentScanner, files: &[PathBuf]) -> Vec<Violation> {
+    files
+        .iter()
+        .filter_map(|path| match scanner.scan(path) {
+            ScanOutcome::Clean => None,
+            ScanOutcome::Infected(report) => Some(Violation {
+                rule_id: "ATT-012",
+                severity: Severity::Error,
+                message: format!(
+                    "File '{}' bị phát hiện nhiễm mã độc bởi trình quét '{}': {}",
+                    file_name_of(path),
+                    scanner.name(),
+                    report
+                ),
+            }),
+            ScanOutcome::Unscannable(reason) => Some(Violation {
+                rule_id: "ATT-013",
+                severity: Severity::Error,
+                message: format!(
+                    "Không quét được mã độc file '{}' bằng trình quét '{}': {}",
+                    file_name_of(path),
+                    scanner.name(),
+                    reason
+                ),
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Made-up signature, real malware test strings would trip the scanners of the build machine.
+    const TEST_SIGNATURE: &[u8] = b"AML-STUB-SCANNER-TEST-SIGNATURE";
+
+    fn stub_scanner() -> StubScanner {
+        StubScanner {
+            signatures: vec![TEST_SIGNATURE.to_vec()],
+        }
+    }
+
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("aml_scan_{}_{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn stub_scanner_finds_its_signatures() {
+        let infected = temp_file("signature.txt", TEST_SIGNATURE);
+        let clean = temp_file("clean.txt", b"Bao cao giao dich dang ngo");
+        let scanner = stub_scanner();
+
+        assert!(matches!(scanner.scan(&infected), ScanOutcome::Infected(_)));
+        assert_eq!(scanner.scan(&clean), ScanOutcome::Clean);
+
+        std::fs::remove_file(infected).unwrap();
+        std::fs::remove_file(clean).unwrap();
+    }
+
+    #[test]
+    fn infected_and_unreadable_files_are_errors() {
+        let infected = temp_file("infected.pdf", &[b"%PDF-1.4 ", TEST_SIGNATURE].concat());
+        let clean = temp_file("clean.pdf", b"%PDF-1.4");
+        let missing = std::env::temp_dir().join("aml_scan_missing.pdf");
+
+        let violations =
+            scan_attachment_files(&stub_scanner(), &[infected.clone(), clean.clone(), missing]);
+        let rule_ids = violations.iter().map(|v| v.rule_id).collect::<Vec<_>>();
+
+        assert_eq!(rule_ids, vec!["ATT-012", "ATT-013"]);
+        assert!(violations.iter().all(|v| v.severity == Severity::Error));
+
+        std::fs::remove_file(infected).unwrap();
+        std::fs::remove_file(clean).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scans_running_past_the_timeout_are_unscannable() {
+        let file = temp_file("slow.pdf", b"%PDF-1.4");
+        let scanner = CommandScanner {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "sleep 5".to_string(),
+                "{file}".to_string(),
+            ],
+            clean_codes: vec![0],
+            infected_codes: vec![1],
+            timeout: Duration::from_millis(200),
+        };
+
+        let started = Instant::now();
+        assert!(matches!(scanner.scan(&file), ScanOutcome::Unscannable(_)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn stub_scanner_cannot_be_configured() {
+        let config = include_str!("../scan.json").replace("\"lệnh\",", "\"giả lập\",");
+        assert!(serde_json::from_str::<ScanConfig>(&config).is_err());
+    }
+}
@@ -0,0 +1,757 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use calamine::Reader;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attachments::{convert::cell_text, manifest::split_csv_line},
+    payload::section4::{MoneyFlow, TransactionInfo},
+    pdf::PdfWriter,
+    reconcile::{Discrepancy, parse_amount, parse_count, parse_date},
+    rules::{Severity, Violation},
+    utils::number::format_amount,
+};
+
+/// Raw transaction export from the core banking system, placed in the attachment folder.
+pub const TRANSACTIONS_FILE_STEM: &str = "giao_dich";
+const TRANSACTIONS_EXTENSIONS: [&str; 3] = ["csv", "xlsx", "xls"];
+
+const LOCAL_STATEMENT_COLUMNS: &str = "input/cot_giao_dich.json";
+
+// Exports usually start with a few title rows before the header
+const HEADER_SEARCH_ROWS: usize = 20;
+const AMOUNT_TOLERANCE: f64 = 1.0;
+
+/// Header names accepted for each column of the raw export, compared case-insensitively.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatementColumns {
+    #[serde(rename = "Số tài khoản")]
+    pub account: Vec<String>,
+    #[serde(rename = "Ngày giao dịch")]
+    pub date: Vec<String>,
+    #[serde(rename = "Loại tiền", default)]
+    pub currency: Vec<String>,
+    #[serde(rename = "Ghi nợ", default)]
+    pub debit: Vec<String>,
+    #[serde(rename = "Ghi có", default)]
+    pub credit: Vec<String>,
+    #[serde(rename = "Số tiền", default)]
+    pub amount: Vec<String>,
+    #[serde(rename = "Số tham chiếu", default)]
+    pub reference: Vec<String>,
+    #[serde(rename = "Nội dung", default)]
+    pub content: Vec<String>,
+    #[serde(rename = "Tài khoản đối ứng", default)]
+    pub counter_account: Vec<String>,
+    #[serde(rename = "Tên đối ứng", default)]
+    pub counter_name: Vec<String>,
+    /// Decimal separator of the amounts written as text, the other one of `.` and `,` is taken
+    /// as the thousands separator.
+    #[serde(rename = "Dấu thập phân", default = "default_decimal_separator")]
+    pub decimal_separator: char,
+}
+
+fn default_decimal_separator() -> char {
+    ','
+}
+
+pub fn load_statement_columns() -> anyhow::Result<StatementColumns> {
+    const STATEMENT_COLUMNS: &str = include_str!("../statement_columns.json");
+
+    let local_path = Path::new(LOCAL_STATEMENT_COLUMNS);
+    if local_path.is_file() {
+        let content = std::fs::read_to_string(local_path)
+            .with_context(|| format!("Không thể đọc file {:#?}", local_path))?;
+        let parsed_result: StatementColumns =
+            serde_json::from_str(&content).with_context(|| {
+                format!(
+                    "File cấu hình cột dữ liệu giao dịch {:#?} không hợp lệ",
+                    local_path
+                )
+            })?;
+        return check_decimal_separator(parsed_result, local_path);
+    }
+
+    let parsed_result: StatementColumns = serde_json::from_str(STATEMENT_COLUMNS)?;
+    Ok(parsed_result)
+}
+
+fn check_decimal_separator(
+    columns: StatementColumns,
+    path: &Path,
+) -> anyhow::Result<StatementColumns> {
+    match columns.decimal_separator {
+        '.' | ',' => Ok(columns),
+        other => Err(anyhow::anyhow!(
+            "Dấu thập phân '{}' trong file {:#?} không hợp lệ, chỉ chấp nhận '.' hoặc ','",
+            other,
+            path
+        )),
+    }
+}
+
+static STATEMENT_COLUMNS: OnceLock<StatementColumns> = OnceLock::new();
+
+/// Header names of the raw transaction export, read on first use.
+pub fn statement_columns() -> anyhow::Result<&'static StatementColumns> {
+    if let Some(columns) = STATEMENT_COLUMNS.get() {
+        return Ok(columns);
+    }
+    let columns = load_statement_columns()?;
+    Ok(STATEMENT_COLUMNS.get_or_init(|| columns))
+}
+
+pub fn is_transactions_file(path: &Path) -> bool {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    stem.eq_ignore_ascii_case(TRANSACTIONS_FILE_STEM)
+        && TRANSACTIONS_EXTENSIONS.contains(&extension.as_str())
+}
+
+pub fn find_transactions_file(folder: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.is_file() && is_transactions_file(path))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatementRow {
+    pub account: String,
+    pub currency: String,
+    pub date: NaiveDate,
+    pub reference: String,
+    pub content: String,
+    pub counter_account: String,
+    pub counter_name: String,
+    pub debit: f64,
+    pub credit: f64,
+}
+
+/// Transactions of the reported accounts within the reported period, ordered by account,
+/// currency and date.
+#[derive(Debug, Clone, Default)]
+pub struct Statement {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub rows: Vec<StatementRow>,
+}
+
+/// Account numbers are compared without the spaces, dots and dashes added by some exports.
+fn normalize_account(account: &str) -> String {
+    account
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Reads an amount written with the configured decimal separator, e.g. `1.234.567,89` when it
+/// is `,`. Amounts in parentheses or with a leading minus are negative.
+fn parse_raw_amount(value: &str, decimal_separator: char) -> Option<f64> {
+    let value = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '(' | ')'))
+        .collect::<String>();
+    let negative = value.starts_with('-') || value.starts_with('(');
+    let value = value.trim_matches(|c| matches!(c, '-' | '(' | ')'));
+    if value.is_empty() || value.matches(decimal_separator).count() > 1 {
+        return None;
+    }
+
+    let normalized = value
+        .chars()
+        .filter_map(|c| match c {
+            c if c == decimal_separator => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect::<String>();
+
+    normalized
+        .parse::<f64>()
+        .ok()
+        .map(|amount| if negative { -amount } else { amount })
+}
+
+fn parse_raw_date(value: &str) -> Option<NaiveDate> {
+    const DATE_FORMATS: [&str; 5] = ["%d/%m/%Y", "%Y-%m-%d", "%d-%m-%Y", "%d.%m.%Y", "%Y/%m/%d"];
+
+    let value = value.split(['T', ' ']).next().unwrap_or_default().trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// Numbers stored as numbers in Excel are written back with the configured decimal separator, so
+/// that they are read like the amounts stored as text.
+fn raw_cell_text(cell: &calamine::Data, decimal_separator: char) -> String {
+    match cell {
+        calamine::Data::Float(value) => value
+            .to_string()
+            .replace('.', &decimal_separator.to_string()),
+        other => cell_text(other),
+    }
+}
+
+fn read_raw_rows(path: &Path, decimal_separator: char) -> anyhow::Result<Vec<Vec<String>>> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+
+    if is_csv {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Không thể đọc file {:#?}", path))?;
+        let content = content.trim_start_matches('\u{feff}');
+        let delimiter = match content.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) if line.contains(';') && !line.contains(',') => ';',
+            Some(line) if line.contains('\t') => '\t',
+            _ => ',',
+        };
+        return Ok(content
+            .lines()
+            .map(|line| split_csv_line(line, delimiter))
+            .collect());
+    }
+
+    let mut workbook = calamine::open_workbook_auto(path)
+        .with_context(|| format!("Không thể mở file Excel {:#?}", path))?;
+    let sheet = workbook
+        .sheet_names()
+        .first()
+        .cloned()
+        .with_context(|| format!("File {:#?} không có sheet nào", path))?;
+    let range = workbook
+        .worksheet_range(&sheet)
+        .with_context(|| format!("Không thể đọc sheet '{}'", sheet))?;
+    Ok(range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| raw_cell_text(cell, decimal_separator))
+                .collect()
+        })
+        .collect())
+}
+
+/// Reads every transaction of the raw export, the header row is located by its column names.
+pub fn read_transactions(path: &Path) -> anyhow::Result<Vec<StatementRow>> {
+    let columns = statement_columns()?;
+    let rows = read_raw_rows(path, columns.decimal_separator)?;
+
+    let find_column = |header: &[String], names: &[String]| {
+        header.iter().position(|cell| {
+            names
+                .iter()
+                .any(|name| cell.trim().to_lowercase() == name.trim().to_lowercase())
+        })
+    };
+
+    let header_idx = rows
+        .iter()
+        .take(HEADER_SEARCH_ROWS)
+        .position(|row| {
+            find_column(row, &columns.account).is_some() && find_column(row, &columns.date).is_some()
+        })
+        .with_context(|| {
+            format!(
+                "Không tìm thấy dòng tiêu đề có cột '{}' và '{}' trong file dữ liệu giao dịch {:#?}",
+                columns.account.first().cloned().unwrap_or_default(),
+                columns.date.first().cloned().unwrap_or_default(),
+                path
+            )
+        })?;
+    let header = &rows[header_idx];
+
+    let column = |names: &[String]| find_column(header, names);
+    let (account_col, date_col) = (
+        column(&columns.account).unwrap_or_default(),
+        column(&columns.date).unwrap_or_default(),
+    );
+    let (debit_col, credit_col, amount_col) = (
+        column(&columns.debit),
+        column(&columns.credit),
+        column(&columns.amount),
+    );
+    if (debit_col.is_none() || credit_col.is_none()) && amount_col.is_none() {
+        return Err(anyhow::anyhow!(
+            "File dữ liệu giao dịch {:#?} phải có cột ghi nợ và ghi có, hoặc cột số tiền (số âm là ghi nợ)",
+            path
+        ));
+    }
+    let (currency_col, reference_col, content_col, counter_account_col, counter_name_col) = (
+        column(&columns.currency),
+        column(&columns.reference),
+        column(&columns.content),
+        column(&columns.counter_account),
+        column(&columns.counter_name),
+    );
+
+    let mut transactions = vec![];
+    for (row_idx, row) in rows.iter().enumerate().skip(header_idx + 1) {
+        let cell = |idx: Option<usize>| {
+            idx.and_then(|idx| row.get(idx))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        let amount = |idx: Option<usize>| -> anyhow::Result<f64> {
+            let value = cell(idx);
+            match value.is_empty() {
+                true => Ok(0.0),
+                false => parse_raw_amount(&value, columns.decimal_separator).with_context(|| {
+                    format!("Số tiền '{}' tại dòng {} không hợp lệ", value, row_idx + 1)
+                }),
+            }
+        };
+
+        // Footer rows with totals or signatures have no account number
+        let account = cell(Some(account_col));
+        if account.is_empty() {
+            continue;
+        }
+        let date_text = cell(Some(date_col));
+        let date = parse_raw_date(&date_text).with_context(|| {
+            format!(
+                "Ngày giao dịch '{}' tại dòng {} của file {:#?} không hợp lệ",
+                date_text,
+                row_idx + 1,
+                path
+            )
+        })?;
+
+        let (debit, credit) = match (debit_col, credit_col) {
+            (Some(debit_col), Some(credit_col)) => (
+                amount(Some(debit_col))?.abs(),
+                amount(Some(credit_col))?.abs(),
+            ),
+            _ => {
+                let value = amount(amount_col)?;
+                match value < 0.0 {
+                    true => (-value, 0.0),
+                    false => (0.0, value),
+                }
+            }
+        };
+
+        let currency = cell(currency_col).to_uppercase();
+        transactions.push(StatementRow {
+            account,
+            currency: match currency.is_empty() {
+                true => "VND".to_string(),
+                false => currency,
+            },
+            date,
+            reference: cell(reference_col),
+            content: cell(content_col),
+            counter_account: cell(counter_account_col),
+            counter_name: cell(counter_name_col),
+            debit,
+            credit,
+        });
+    }
+
+    Ok(transactions)
+}
+
+/// Per account and currency totals of the statement.
+#[derive(Debug, Clone, Default)]
+struct StatementTotals {
+    debit: f64,
+    credit: f64,
+    debit_count: i64,
+    credit_count: i64,
+}
+
+impl Statement {
+    /// Keeps the transactions of the Section IV accounts within the Section IV period.
+    pub fn from_transactions(rows: Vec<StatementRow>, info: &TransactionInfo) -> Self {
+        let time_range = info.time_range.clone().unwrap_or_default();
+        let from = parse_date(time_range.from.as_ref());
+        let to = parse_date(time_range.to.as_ref());
+        let accounts = info
+            .money_flows
+            .iter()
+            .flatten()
+            .filter_map(|flow| flow.account_number.as_ref())
+            .map(|account| normalize_account(account))
+            .filter(|account| !account.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut rows = rows
+            .into_iter()
+            .filter(|row| accounts.contains(&normalize_account(&row.account)))
+            .filter(|row| from.is_none_or(|from| row.date >= from))
+            .filter(|row| to.is_none_or(|to| row.date <= to))
+            .collect::<Vec<_>>();
+        // Stable sort keeps the export order of the transactions on the same day
+        rows.sort_by(|a, b| {
+            (normalize_account(&a.account), &a.currency, a.date).cmp(&(
+                normalize_account(&b.account),
+                &b.currency,
+                b.date,
+            ))
+        });
+
+        Statement { from, to, rows }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn title(&self) -> String {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) => format!(
+                "Bảng kê giao dịch từ ngày {} đến ngày {}",
+                from.format("%d/%m/%Y"),
+                to.format("%d/%m/%Y")
+            ),
+            _ => "Bảng kê giao dịch".to_string(),
+        }
+    }
+
+    /// Rows grouped by account and currency, in statement order.
+    fn groups(&self) -> Vec<&[StatementRow]> {
+        self.rows
+            .chunk_by(|a, b| {
+                normalize_account(&a.account) == normalize_account(&b.account)
+                    && a.currency == b.currency
+            })
+            .collect()
+    }
+
+    fn totals(&self) -> BTreeMap<(String, String), StatementTotals> {
+        self.rows.iter().fold(BTreeMap::new(), |mut acc, row| {
+            let totals: &mut StatementTotals = acc
+                .entry((normalize_account(&row.account), row.currency.clone()))
+                .or_default();
+            totals.debit += row.debit;
+            totals.credit += row.credit;
+            totals.debit_count += (row.debit > 0.0) as i64;
+            totals.credit_count += (row.credit > 0.0) as i64;
+            acc
+        })
+    }
+
+    fn header() -> Vec<String> {
+        [
+            "STT",
+            "Số tài khoản",
+            "Loại tiền",
+            "Ngày giao dịch",
+            "Số tham chiếu",
+            "Nội dung",
+            "Tài khoản đối ứng",
+            "Tên đối ứng",
+            "Ghi nợ",
+            "Ghi có",
+            "Lũy kế ghi nợ",
+            "Lũy kế ghi có",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    pub fn to_xlsx(&self, path: &Path) -> anyhow::Result<()> {
+        use rust_xlsxwriter::{Format, FormatBorder, Workbook};
+
+        const HEADER_ROW: u32 = 3;
+
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("Bảng kê")?;
+
+        let bold = Format::new().set_bold();
+        let header_format = Format::new()
+            .set_bold()
+            .set_background_color(0xD9D9D9)
+            .set_border(FormatBorder::Thin);
+        let date_format = Format::new().set_num_format("dd/mm/yyyy");
+        let amount_format = |currency: &str, bold: bool| {
+            let format = Format::new().set_num_format(match currency {
+                "VND" => "#,##0",
+                _ => "#,##0.00",
+            });
+            match bold {
+                true => format.set_bold(),
+                false => format,
+            }
+        };
+
+        sheet.write_string_with_format(0, 0, self.title(), &bold)?;
+        sheet.write_string(
+            1,
+            0,
+            "Lũy kế được tính riêng theo từng tài khoản và loại tiền",
+        )?;
+        for (col, title) in Self::header().iter().enumerate() {
+            sheet.write_string_with_format(HEADER_ROW, col as u16, title, &header_format)?;
+        }
+
+        let mut row_idx = HEADER_ROW + 1;
+        let mut number = 0;
+        for group in self.groups() {
+            let (mut debit_total, mut credit_total) = (0.0, 0.0);
+            for row in group {
+                number += 1;
+                debit_total += row.debit;
+                credit_total += row.credit;
+                let amount = amount_format(&row.currency, false);
+
+                sheet.write_number(row_idx, 0, number)?;
+                sheet.write_string(row_idx, 1, &row.account)?;
+                sheet.write_string(row_idx, 2, &row.currency)?;
+                sheet.write_datetime_with_format(row_idx, 3, row.date, &date_format)?;
+                sheet.write_string(row_idx, 4, &row.reference)?;
+                sheet.write_string(row_idx, 5, &row.content)?;
+                sheet.write_string(row_idx, 6, &row.counter_account)?;
+                sheet.write_string(row_idx, 7, &row.counter_name)?;
+                sheet.write_number_with_format(row_idx, 8, row.debit, &amount)?;
+                sheet.write_number_with_format(row_idx, 9, row.credit, &amount)?;
+                sheet.write_number_with_format(row_idx, 10, debit_total, &amount)?;
+                sheet.write_number_with_format(row_idx, 11, credit_total, &amount)?;
+                row_idx += 1;
+            }
+
+            let first = &group[0];
+            let amount = amount_format(&first.currency, true);
+            sheet.write_string_with_format(
+                row_idx,
+                1,
+                format!("Cộng tài khoản {} ({})", first.account, first.currency),
+                &bold,
+            )?;
+            sheet.write_number_with_format(row_idx, 8, debit_total, &amount)?;
+            sheet.write_number_with_format(row_idx, 9, credit_total, &amount)?;
+            row_idx += 2;
+        }
+
+        sheet.set_freeze_panes(HEADER_ROW + 1, 0)?;
+        sheet.autofit();
+        workbook
+            .save(path)
+            .with_context(|| format!("Không thể ghi file {:#?}", path))?;
+        Ok(())
+    }
+
+    pub fn to_pdf(&self) -> anyhow::Result<Vec<u8>> {
+        let mut body = vec![];
+        let mut number = 0;
+        for group in self.groups() {
+            let (mut debit_total, mut credit_total) = (0.0, 0.0);
+            for row in group {
+                number += 1;
+                debit_total += row.debit;
+                credit_total += row.credit;
+                body.push(vec![
+                    number.to_string(),
+                    row.account.clone(),
+                    row.currency.clone(),
+                    row.date.format("%d/%m/%Y").to_string(),
+                    row.reference.clone(),
+                    row.content.clone(),
+                    row.counter_account.clone(),
+                    row.counter_name.clone(),
+                    format_amount(row.debit),
+                    format_amount(row.credit),
+                    format_amount(debit_total),
+                    format_amount(credit_total),
+                ]);
+            }
+
+            let first = &group[0];
+            let mut total_row = vec![String::new(); 12];
+            total_row[1] = format!("Cộng tài khoản {} ({})", first.account, first.currency);
+            total_row[8] = format_amount(debit_total);
+            total_row[9] = format_amount(credit_total);
+            body.push(total_row);
+        }
+
+        let mut writer = PdfWriter::new();
+        writer.table(&self.title(), &Self::header(), &body, &[8, 9, 10, 11])?;
+        writer.finish()
+    }
+
+    /// Writes the statement workbook and its PDF rendering, returns the path of the PDF.
+    pub fn save(&self, folder: &Path, stem: &str) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(folder)
+            .with_context(|| format!("Không thể tạo folder {:#?}", folder))?;
+
+        self.to_xlsx(&folder.join(format!("{}.xlsx", stem)))?;
+        let pdf_path = folder.join(format!("{}.pdf", stem));
+        std::fs::write(&pdf_path, self.to_pdf()?)
+            .with_context(|| format!("Không thể ghi file {:#?}", pdf_path))?;
+        Ok(pdf_path)
+    }
+}
+
+fn flow_location(flow: &MoneyFlow) -> String {
+    format!(
+        "Bảng kê giao dịch - tài khoản {} ({})",
+        flow.account_number.clone().unwrap_or_default(),
+        flow.subject_name.clone().unwrap_or_default()
+    )
+}
+
+/// Compares the statement with the Section IV flow summaries: the number of credit and debit
+/// transactions of each account, and the credited and debited amounts per currency. A statement
+/// that contradicts the report is not sent.
+pub fn reconcile_statement(statement: &Statement, info: &TransactionInfo) -> Vec<Violation> {
+    let totals = statement.totals();
+    let mut discrepancies = vec![];
+
+    for flow in info.money_flows.iter().flatten() {
+        let Some(account) = flow.account_number.as_ref().map(|a| normalize_account(a)) else {
+            continue;
+        };
+        let location = flow_location(flow);
+        let account_totals = totals
+            .iter()
+            .filter(|((acc, _), _)| *acc == account)
+            .map(|((_, currency), totals)| (currency.clone(), totals))
+            .collect::<HashMap<_, _>>();
+
+        let counts = [
+            (
+                "Tổng số giao dịch vào",
+                flow.total_transactions_in.as_ref(),
+                account_totals.values().map(|t| t.credit_count).sum::<i64>(),
+            ),
+            (
+                "Tổng số giao dịch ra",
+                flow.total_transactions_out.as_ref(),
+                account_totals.values().map(|t| t.debit_count).sum::<i64>(),
+            ),
+        ];
+        for (field, declared, computed) in counts {
+            if let Some(declared_count) = parse_count(declared)
+                && declared_count != computed
+            {
+                discrepancies.push(Discrepancy {
+                    location: location.clone(),
+                    field,
+                    declared: declared_count.to_string(),
+                    computed: computed.to_string(),
+                });
+            }
+        }
+
+        let declared_by_currency = |entries: Vec<(Option<String>, Option<&String>)>| {
+            entries.into_iter().fold(
+                BTreeMap::<String, f64>::new(),
+                |mut acc, (currency, amount)| {
+                    *acc.entry(currency.unwrap_or_else(|| "VND".to_string()))
+                        .or_insert(0.0) += parse_amount(amount).unwrap_or(0.0);
+                    acc
+                },
+            )
+        };
+        let declared_in = declared_by_currency(
+            flow.inflows
+                .iter()
+                .flatten()
+                .map(|e| {
+                    (
+                        e.currency.as_ref().map(|c| c.to_string()),
+                        e.total_amount.as_ref(),
+                    )
+                })
+                .collect(),
+        );
+        let declared_out = declared_by_currency(
+            flow.outflows
+                .iter()
+                .flatten()
+                .map(|e| {
+                    (
+                        e.currency.as_ref().map(|c| c.to_string()),
+                        e.total_amount.as_ref(),
+                    )
+                })
+                .collect(),
+        );
+
+        let currencies = declared_in
+            .keys()
+            .chain(declared_out.keys())
+            .chain(account_totals.keys())
+            .cloned()
+            .collect::<std::collections::BTreeSet<_>>();
+        for currency in currencies {
+            let computed = account_totals.get(&currency);
+            let amounts = [
+                (
+                    "Tổng số tiền vào theo loại tiền",
+                    declared_in.get(&currency),
+                    computed.map(|t| t.credit).unwrap_or(0.0),
+                ),
+                (
+                    "Tổng số tiền ra theo loại tiền",
+                    declared_out.get(&currency),
+                    computed.map(|t| t.debit).unwrap_or(0.0),
+                ),
+            ];
+            for (field, declared, computed) in amounts {
+                let declared = declared.copied().unwrap_or(0.0);
+                if (declared - computed).abs() >= AMOUNT_TOLERANCE {
+                    discrepancies.push(Discrepancy {
+                        location: location.clone(),
+                        field,
+                        declared: format!("{} {}", format_amount(declared), currency),
+                        computed: format!("{} {}", format_amount(computed), currency),
+                    });
+                }
+            }
+        }
+    }
+
+    discrepancies
+        .into_iter()
+        .map(|discrepancy| Violation {
+            rule_id: "STM-001",
+            severity: Severity::Error,
+            message: format!(
+                "{} - {}: giá trị khai báo '{}', giá trị tính từ file dữ liệu giao dịch '{}'",
+                discrepancy.location, discrepancy.field, discrepancy.declared, discrepancy.computed
+            ),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_are_read_with_the_configured_decimal_separator() {
+        assert_eq!(parse_raw_amount("1.234.567,89", ','), Some(1234567.89));
+        assert_eq!(parse_raw_amount("1,234,567.89", '.'), Some(1234567.89));
+        assert_eq!(parse_raw_amount("0,500", ','), Some(0.5));
+        assert_eq!(parse_raw_amount("0.500", '.'), Some(0.5));
+        assert_eq!(parse_raw_amount("1.500", ','), Some(1500.0));
+        assert_eq!(parse_raw_amount("2.000.000 VND", ','), Some(2000000.0));
+    }
+
+    #[test]
+    fn negative_and_invalid_amounts() {
+        assert_eq!(parse_raw_amount("-1.500,5", ','), Some(-1500.5));
+        assert_eq!(parse_raw_amount("(1.500)", ','), Some(-1500.0));
+        assert_eq!(parse_raw_amount("1,5,0", ','), None);
+        assert_eq!(parse_raw_amount("không có", ','), None);
+    }
+}
@@ -0,0 +1,259 @@
+use std::{io::Read, path::Path};
+
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    archive::{ArchivedAttachments, append_attachments_to_archive},
+    attachments::policy::{BYTES_PER_MB, attachment_policy},
+    payload::section6::Attachment,
+    response::{UploadedAttachment, uploaded_attachments},
+};
+
+pub const SAVE_ATTACHMENT_URL: &str =
+    "https://amlstr.sbv.gov.vn/strcreator/api/attachment/saveAttachment";
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Size and SHA-256 of a file, read in chunks so that large scans are never held in memory.
+pub fn hash_file(path: &Path) -> anyhow::Result<(u64, String)> {
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Không thể mở file {:#?}", path))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut size = 0u64;
+
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Không thể đọc file {:#?}", path))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    Ok((size, hash))
+}
+
+/// Splits the attachments into consecutive batches that stay under the request size limit of
+/// the attachment policy. A file larger than the limit is sent on its own.
+pub fn upload_batches(attachments: &[Attachment]) -> anyhow::Result<Vec<&[Attachment]>> {
+    let Some(limit) = attachment_policy()?
+        .max_request_size_mb
+        .map(|mb| (mb * BYTES_PER_MB) as i64)
+    else {
+        return Ok(vec![attachments]);
+    };
+
+    let mut batches = vec![];
+    let (mut start, mut batch_size) = (0, 0i64);
+    for (idx, attachment) in attachments.iter().enumerate() {
+        let size = attachment.file_size.unwrap_or_default();
+        if idx > start && batch_size + size > limit {
+            batches.push(&attachments[start..idx]);
+            (start, batch_size) = (idx, 0);
+        }
+        batch_size += size;
+    }
+    if start < attachments.len() {
+        batches.push(&attachments[start..]);
+    }
+    Ok(batches)
+}
+
+async fn file_part(attachment: &Attachment) -> anyhow::Result<reqwest::multipart::Part> {
+    let file_name = attachment.file_name.clone().unwrap_or_default();
+    let path = attachment
+        .file_path
+        .as_ref()
+        .with_context(|| format!("Không xác định được đường dẫn của file {}", file_name))?;
+
+    // The archive records the size and hash read when the report was checked as the bytes that
+    // were sent, the file must not change in the meantime
+    let (size, hash) = hash_file(path)?;
+    let changed = attachment
+        .file_size
+        .is_some_and(|declared| declared as u64 != size)
+        || attachment
+            .file_hash
+            .as_ref()
+            .is_some_and(|declared| !declared.eq_ignore_ascii_case(&hash));
+    if changed {
+        return Err(anyhow::anyhow!(
+            "File {:#?} đã bị thay đổi sau khi kiểm tra (dung lượng {} byte, SHA-256 {}; đã khai báo {} byte, SHA-256 {})",
+            path,
+            size,
+            hash,
+            attachment.file_size.unwrap_or_default(),
+            attachment.file_hash.clone().unwrap_or_default()
+        ));
+    }
+
+    let file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Không thể mở file {:#?}", path))?;
+
+    reqwest::multipart::Part::stream_with_length(file, size)
+        .file_name(file_name.clone())
+        .mime_str(&attachment.file_mime.clone().unwrap_or_default())
+        .with_context(|| format!("Lỗi khi xác định kiểu file của {}", &file_name))
+}
+
+async fn upload_batch(
+    client: &reqwest::Client,
+    batch: &[Attachment],
+    auth_key_value: &str,
+    report_id: i64,
+) -> anyhow::Result<Option<Vec<UploadedAttachment>>> {
+    let metadata = batch
+        .iter()
+        .cloned()
+        .map(|attachment| Attachment {
+            str_id: report_id.into(),
+            ..attachment
+        })
+        .collect::<Vec<_>>();
+
+    let mut body = reqwest::multipart::Form::new()
+        .text("strId", report_id.to_string())
+        .part(
+            "attachments",
+            reqwest::multipart::Part::text(serde_json::to_string(&metadata)?)
+                .file_name("blob")
+                .mime_str("application/json")?,
+        );
+    for attachment in batch {
+        body = body.part("files", file_part(attachment).await?);
+    }
+
+    let response = client
+        .post(SAVE_ATTACHMENT_URL)
+        .bearer_auth(auth_key_value)
+        .multipart(body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Website NHNN trả về lỗi: {}-{}",
+            response.status(),
+            response.status().canonical_reason().unwrap_or_default()
+        ));
+    }
+
+    let resp_text = response.text().await.unwrap_or_default();
+    Ok(uploaded_attachments(&resp_text))
+}
+
+/// Uploads the attachments of a report, streaming each file from disk into the multipart body.
+/// Batches are sent one after another, each with the metadata of its own files, and each accepted
+/// batch is recorded in the archive at once. When a batch fails, the error lists the files that
+/// are not on the portal yet. Returns the attachment list of the responses when the portal sends
+/// one back.
+pub async fn upload_attachments(
+    attachments: &[Attachment],
+    auth_key_value: &str,
+    report_id: i64,
+    file_path: &Path,
+) -> anyhow::Result<Option<Vec<UploadedAttachment>>> {
+    let client = reqwest::Client::new();
+    let batches = upload_batches(attachments)?;
+    let batch_total = batches.len();
+    let mut uploaded: Option<Vec<UploadedAttachment>> = None;
+
+    for (batch_idx, batch) in batches.iter().enumerate() {
+        if batch_total > 1 {
+            log::info!(
+                "Gửi các file đính kèm lần {}/{} ({} file)",
+                batch_idx + 1,
+                batch_total,
+                batch.len()
+            );
+        }
+
+        let listed = upload_batch(&client, batch, auth_key_value, report_id)
+            .await
+            .with_context(|| {
+                let pending = batches[batch_idx..]
+                    .iter()
+                    .flat_map(|batch| batch.iter())
+                    .map(|attachment| attachment.file_name.clone().unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "Có lỗi xảy ra khi gửi các file đính kèm lần {}/{} cho báo cáo '{}'. Các file chưa được gửi lên website NHNN: {}",
+                    batch_idx + 1,
+                    batch_total,
+                    report_id,
+                    pending
+                )
+            })?;
+
+        if batch_total > 1 {
+            let archived_batch = ArchivedAttachments {
+                batch: Some(format!("{}/{}", batch_idx + 1, batch_total)),
+                ..ArchivedAttachments::new(batch, report_id, file_path)
+            };
+            if let Err(err) = append_attachments_to_archive(&archived_batch) {
+                log::warn!(
+                    "Không thể lưu thông tin file đính kèm lần {}/{} của báo cáo '{}': {:?}",
+                    batch_idx + 1,
+                    batch_total,
+                    report_id,
+                    err
+                );
+            }
+        }
+
+        if let Some(listed) = listed {
+            uploaded.get_or_insert_default().extend(listed);
+        }
+    }
+
+    Ok(uploaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(size_mb: f64) -> Attachment {
+        Attachment {
+            file_size: Some((size_mb * BYTES_PER_MB) as i64),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn batches_stay_under_the_request_limit() {
+        let limit = attachment_policy().unwrap().max_request_size_mb.unwrap();
+        let attachments = [
+            attachment(limit * 0.6),
+            attachment(limit * 0.6),
+            attachment(limit * 0.2),
+            attachment(limit * 1.5),
+            attachment(limit * 0.1),
+        ];
+
+        let sizes = upload_batches(&attachments)
+            .unwrap()
+            .iter()
+            .map(|batch| batch.len())
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![1, 2, 1, 1]);
+    }
+
+    #[test]
+    fn small_attachments_are_sent_together() {
+        let attachments = [attachment(1.0), attachment(2.0), attachment(3.0)];
+        assert_eq!(upload_batches(&attachments).unwrap().len(), 1);
+        assert!(upload_batches(&[]).unwrap().is_empty());
+    }
+}
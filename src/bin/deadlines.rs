@@ -0,0 +1,96 @@
+use std::io::BufRead;
+
+use aml::{
+    archive::load_archive,
+    build::print_build_info,
+    deadline::{PendingReport, today},
+    utils::setup::{get_input_excel_files, initial_setup, select_report_form_from_args},
+};
+use anyhow::Context;
+use calamine::{Xlsx, open_workbook};
+use colored::Colorize;
+
+#[tokio::main]
+async fn main() {
+    print_build_info();
+
+    if let Err(err) = _main().await {
+        let error_message =
+            format!("Đã xảy ra lỗi khi tính hạn nộp báo cáo: {:?}", err).bright_red();
+        log::error!("{}", error_message);
+    }
+
+    println!("Press Enter to exit...");
+    let stdin = std::io::stdin();
+    let _ = stdin.lock().lines().next();
+}
+
+async fn _main() -> anyhow::Result<()> {
+    let _progress_bar = initial_setup()?;
+    let form_override = select_report_form_from_args()?;
+    let excel_files = get_input_excel_files()?;
+    let archive = load_archive()?;
+    let today = today();
+
+    let mut pending_reports = vec![];
+    for excel_file in excel_files {
+        let excel_path = excel_file.path();
+
+        let pending_report = open_workbook(excel_path.clone())
+            .with_context(|| format!("Không thể mở file {:#?}", excel_path))
+            .and_then(|mut workbook: Xlsx<_>| {
+                PendingReport::from_excel(&mut workbook, &excel_path, today, form_override)
+            });
+
+        match pending_report {
+            Ok(report) => pending_reports.push(report),
+            Err(err) => log::warn!(
+                "Không thể xác định hạn nộp của file {:#?}: {:?}",
+                excel_path,
+                err
+            ),
+        }
+    }
+
+    pending_reports.retain(|report| {
+        !archive.iter().any(|archived| {
+            !report.internal_number.trim().is_empty()
+                && archived.internal_number == report.internal_number
+                && archived.change_type == report.change_type
+        })
+    });
+    pending_reports.sort_by_key(|report| {
+        (
+            report.deadline.is_none(),
+            report.deadline.as_ref().map(|d| d.working_days_left),
+            report.file_name.clone(),
+        )
+    });
+
+    if pending_reports.is_empty() {
+        log::info!("Không có báo cáo nào đang chờ nộp trong thư mục `input/`");
+        return Ok(());
+    }
+
+    println!("Các báo cáo chờ nộp (ngày {}):", today.format("%d/%m/%Y"));
+    for report in pending_reports {
+        let line = match &report.deadline {
+            Some(deadline) => format!(
+                "  {} | {} | {}",
+                report.file_name, report.internal_number, deadline
+            ),
+            None => format!(
+                "  {} | {} | không có hạn nộp (báo cáo sửa đổi, bổ sung hoặc chưa có ngày phát hiện)",
+                report.file_name, report.internal_number
+            ),
+        };
+
+        match report.deadline.as_ref().map(|d| d.working_days_left) {
+            Some(n) if n < 0 => println!("{}", line.bright_red()),
+            Some(0 | 1) => println!("{}", line.yellow()),
+            _ => println!("{}", line),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use aml::{
+    payload::schema::form_schema, template::default_report_form,
+    utils::setup::select_report_form_from_args,
+};
+
+fn main() -> anyhow::Result<()> {
+    let report_form = select_report_form_from_args()?.unwrap_or(default_report_form());
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&form_schema(report_form)?)?
+    );
+    Ok(())
+}
@@ -1,10 +1,26 @@
 use aml::{
+    archive::{
+        ArchivedAttachments, ArchivedReport, append_attachments_to_archive, append_to_archive,
+        check_amendment,
+    },
+    attachments::{
+        integrity::verify_uploaded_attachments, scan::require_scanner, upload::upload_attachments,
+    },
     auth::get_auth_code,
     build::print_build_info,
+    deadline::check_deadline,
+    entity_graph::check_entity_links,
     launch::launch_web_automation_task,
-    payload::{form::Form, section6::Section6},
+    ownership::check_ownership,
+    payload::{form::Form, schema::validate_form, section6::Attachment},
+    reconcile::check_money_flows,
     response::{ErrorResponse, SuccessResponse},
-    utils::setup::{get_input_excel_files, initial_setup},
+    rules::{Severity, check_payload_rules, report_violations},
+    template::ReportForm,
+    utils::setup::{
+        fill_computed_from_args, get_input_excel_files, initial_setup, late_reason_from_args,
+        select_report_form_from_args,
+    },
 };
 use anyhow::Context;
 use colored::Colorize;
@@ -32,17 +48,71 @@ async fn create_report_from_excel(
     excel_file: &DirEntry,
     api_url: &str,
     auth_key_value: &str,
-) -> anyhow::Result<i64> {
+    form_override: Option<&'static ReportForm>,
+) -> anyhow::Result<(i64, Vec<Attachment>)> {
     let mut workbook: calamine::Xlsx<_> = calamine::open_workbook(excel_file.path())
         .with_context(|| format!("Không thể mở file Excel {:#?}", excel_file))?;
 
-    let form_payload = Form::from_excel(&mut workbook, &excel_file.path()).with_context(|| {
+    let mut form_payload = Form::from_excel(&mut workbook, &excel_file.path(), form_override)
+        .with_context(|| {
+            format!(
+                "Phát sinh lỗi khi tạo biểu mẫu gửi NHNN từ file Excel {:#?}",
+                excel_file.path()
+            )
+        })?;
+
+    check_money_flows(&mut form_payload.payload, fill_computed_from_args());
+
+    check_entity_links(&mut form_payload.payload).with_context(|| {
+        format!(
+            "Thông tin khách hàng, tài khoản trong file {:#?} không khớp giữa các sheet",
+            excel_file.path()
+        )
+    })?;
+
+    validate_form(&form_payload).with_context(|| {
+        format!(
+            "Dữ liệu báo cáo trong file {:#?} không khớp với định dạng của website NHNN",
+            excel_file.path()
+        )
+    })?;
+
+    check_ownership(&form_payload.payload).with_context(|| {
+        format!(
+            "Thông tin chủ sở hữu hưởng lợi trong file {:#?} không hợp lệ",
+            excel_file.path()
+        )
+    })?;
+
+    check_payload_rules(&form_payload.payload).with_context(|| {
+        format!(
+            "Dữ liệu báo cáo trong file {:#?} không hợp lệ",
+            excel_file.path()
+        )
+    })?;
+
+    check_amendment(&form_payload).with_context(|| {
         format!(
-            "Phát sinh lỗi khi tạo biểu mẫu gửi NHNN từ file Excel {:#?}",
+            "Thông tin sửa đổi, bổ sung báo cáo trong file {:#?} không hợp lệ",
             excel_file.path()
         )
     })?;
 
+    let late_reason = late_reason_from_args()?;
+    let deadline = check_deadline(&form_payload, true, late_reason.as_deref())
+        .with_context(|| format!("Báo cáo trong file {:#?} đã quá hạn nộp", excel_file.path()))?;
+
+    if let Some(section_6) = form_payload.payload.section_6.as_mut() {
+        section_6
+            .save_generated_attachments(&excel_file.path())
+            .with_context(|| {
+                format!(
+                    "Không thể tạo các tài liệu đính kèm tự động cho file {:#?}",
+                    excel_file.path()
+                )
+            })?;
+    }
+
     let response = reqwest::Client::new()
         .post(api_url)
         .bearer_auth(&auth_key_value)
@@ -109,72 +179,86 @@ async fn create_report_from_excel(
             .unwrap_or_default()
     };
 
-    Ok(report_id)
+    let archived_report = ArchivedReport {
+        late_reason: late_reason.filter(|_| deadline.is_some_and(|d| d.is_overdue())),
+        ..ArchivedReport::new(&form_payload, report_id, &excel_file.path())
+    };
+    if let Err(err) = append_to_archive(&archived_report) {
+        log::warn!("Không thể lưu thông tin báo cáo '{}': {:?}", report_id, err);
+    }
+
+    // The attachments read with the form are uploaded as is, the workbook is not parsed again
+    let attachments = form_payload
+        .payload
+        .section_6
+        .take()
+        .map(|section_6| section_6.attachments)
+        .unwrap_or_default();
+
+    Ok((report_id, attachments))
 }
 
 async fn save_attachments(
     excel_file: &DirEntry,
     auth_key_value: &str,
     report_id: i64,
+    attachments: Vec<Attachment>,
 ) -> anyhow::Result<()> {
-    let mut workbook: calamine::Xlsx<_> = calamine::open_workbook(excel_file.path())
-        .with_context(|| format!("Lỗi khi mở file {:#?}", excel_file.path()))?;
+    if attachments.is_empty() {
+        log::warn!(
+            "Không có file đính kèm nào được gửi cho file `{:?}`",
+            excel_file.path()
+        );
+        return Ok(());
+    }
 
-    let mut attachments = Section6::from_excel(&mut workbook, &excel_file.path())
+    let uploaded = upload_attachments(&attachments, auth_key_value, report_id, &excel_file.path())
+        .await
         .with_context(|| {
             format!(
-                "Lỗi khi đọc/xử lý dữ liệu từ file {:#?} để lưu các file đính kèm",
+                "Có lỗi xảy ra khi gửi các file đính kèm lên website NHNN file `{:?}`",
                 excel_file.path()
             )
-        })?
-        .attachments;
+        })?;
 
-    for attachment in attachments.iter_mut() {
-        attachment.str_id = report_id.into();
-    }
+    let violations = match uploaded.as_ref() {
+        Some(uploaded) => verify_uploaded_attachments(&attachments, uploaded),
+        None => {
+            log::warn!(
+                "Website NHNN không trả về danh sách tài liệu đính kèm của báo cáo '{}'. Các file đã gửi CHƯA được đối chiếu với website, kiểm tra lại trên website NHNN",
+                report_id
+            );
+            vec![]
+        }
+    };
 
-    let mut body = reqwest::multipart::Form::new()
-        .text("strId", report_id.to_string())
-        .part(
-            "attachments",
-            reqwest::multipart::Part::text(serde_json::to_string(&attachments)?)
-                .file_name("blob")
-                .mime_str("application/json")?,
+    let archived_attachments = ArchivedAttachments {
+        verified: uploaded
+            .as_ref()
+            .map(|_| violations.iter().all(|v| v.severity != Severity::Error)),
+        ..ArchivedAttachments::new(&attachments, report_id, &excel_file.path())
+    };
+    if let Err(err) = append_attachments_to_archive(&archived_attachments) {
+        log::warn!(
+            "Không thể lưu thông tin file đính kèm của báo cáo '{}': {:?}",
+            report_id,
+            err
         );
-
-    for attachment in attachments.into_iter() {
-        let file_content = attachment.file_content.unwrap_or_default().clone();
-        let file_name = attachment.file_name.unwrap_or_default().clone();
-        let file_mime = attachment.file_mime.unwrap_or_default().clone();
-        let part_data = reqwest::multipart::Part::bytes(file_content)
-            .file_name(file_name.clone())
-            .mime_str(&file_mime)
-            .with_context(|| format!("Lỗi khi xác định kiểu file của {}", &file_name))?;
-
-        body = body.part("files", part_data);
-    }
-
-    let response = reqwest::Client::new()
-        .post("https://amlstr.sbv.gov.vn/strcreator/api/attachment/saveAttachment")
-        .bearer_auth(&auth_key_value)
-        .multipart(body)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "Có lỗi xảy ra khi gửi các file đính kèm lên website NHNN file `{:?}` với lỗi: {}-{}",
-            excel_file.path(),
-            response.status(),
-            response.status().canonical_reason().unwrap_or_default()
-        ));
     }
 
-    Ok(())
+    report_violations(
+        &violations,
+        &format!(
+            "Các file đính kèm trên website NHNN không khớp với các file đã gửi cho báo cáo '{}'",
+            report_id
+        ),
+    )
 }
 
 async fn _main() -> anyhow::Result<()> {
     let progress_bar = initial_setup()?;
+    let form_override = select_report_form_from_args()?;
+    require_scanner()?;
 
     let excel_files = get_input_excel_files()?;
     if excel_files.is_empty() {
@@ -194,9 +278,10 @@ async fn _main() -> anyhow::Result<()> {
 
     progress_bar.set_length(excel_files.iter().len() as u64);
     for excel_file in excel_files {
-        let report_id = create_report_from_excel(&excel_file, api_url, &auth_key_value)
-            .await
-            .with_context(|| format!("Lỗi khi tạo báo cáo từ file {:?}", excel_file.path()))?;
+        let (report_id, attachments) =
+            create_report_from_excel(&excel_file, api_url, &auth_key_value, form_override)
+                .await
+                .with_context(|| format!("Lỗi khi tạo báo cáo từ file {:?}", excel_file.path()))?;
 
         log::info!(
             "Đã nộp biểu mẫu thành công cho file `{:?}`. Mã báo cáo: '{}'.",
@@ -204,7 +289,7 @@ async fn _main() -> anyhow::Result<()> {
             report_id
         );
 
-        save_attachments(&excel_file, &auth_key_value, report_id)
+        save_attachments(&excel_file, &auth_key_value, report_id, attachments)
             .await
             .with_context(|| {
                 format!(
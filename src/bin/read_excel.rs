@@ -1,6 +1,7 @@
 use aml::{
+    ownership::log_ownership_table,
     payload,
-    utils::setup::{get_input_excel_files, initial_setup},
+    utils::setup::{get_input_excel_files, initial_setup, select_report_form_from_args},
 };
 use anyhow::Context;
 use calamine::{Xlsx, open_workbook};
@@ -16,6 +17,7 @@ async fn main() {
 
 async fn _main() -> anyhow::Result<()> {
     let progress_bar = initial_setup()?;
+    let form_override = select_report_form_from_args()?;
     let excel_files = get_input_excel_files()?;
     progress_bar.set_length(excel_files.len() as u64);
 
@@ -25,7 +27,7 @@ async fn _main() -> anyhow::Result<()> {
         let mut workbook: Xlsx<_> = open_workbook(excel_path.clone())
             .with_context(|| format!("Không thể mở file {:#?}", excel_path))?;
 
-        let form = payload::form::Form::from_excel(&mut workbook, &excel_path)
+        let form = payload::form::Form::from_excel(&mut workbook, &excel_path, form_override)
             .with_context(|| format!("Lỗi khi đọc và xử lý dữ liệu từ file {:#?}", excel_path))?;
 
         let json_form = serde_json::to_string_pretty(&form).with_context(|| {
@@ -36,6 +38,7 @@ async fn _main() -> anyhow::Result<()> {
         })?;
 
         println!("{}", json_form);
+        log_ownership_table(&form.payload);
         progress_bar.inc(1);
         log::info!("Đã xử lý xong file {:#?}", excel_path);
     }
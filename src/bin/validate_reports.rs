@@ -1,9 +1,18 @@
-use std::io::BufRead;
+use std::{io::BufRead, path::Path};
 
 use aml::{
+    archive::check_amendment,
     build::print_build_info,
-    payload,
-    utils::setup::{get_input_excel_files, initial_setup},
+    deadline::check_deadline,
+    entity_graph::check_entity_links,
+    ownership::check_ownership,
+    payload::{self, form::Form},
+    reconcile::check_money_flows,
+    rules::check_payload_rules,
+    utils::setup::{
+        fill_computed_from_args, get_input_excel_files, initial_setup, json_inputs_from_args,
+        select_report_form_from_args,
+    },
 };
 use anyhow::Context;
 use calamine::{Xlsx, open_workbook};
@@ -14,7 +23,8 @@ async fn main() {
     print_build_info();
 
     if let Err(err) = _main().await {
-        let error_message = format!("Đã xảy ra lỗi khi đọc các file Excel: {:?}", err).bright_red();
+        let error_message =
+            format!("Đã xảy ra lỗi khi kiểm tra các báo cáo: {:?}", err).bright_red();
         log::error!("{}", error_message);
     }
 
@@ -23,8 +33,85 @@ async fn main() {
     let _ = stdin.lock().lines().next();
 }
 
+/// Runs every check of the submit path on a report, without sending it.
+fn check_form(form: &mut Form, path: &Path) -> anyhow::Result<()> {
+    check_money_flows(&mut form.payload, fill_computed_from_args());
+
+    check_entity_links(&mut form.payload).with_context(|| {
+        format!(
+            "Thông tin khách hàng, tài khoản trong file {:#?} không khớp giữa các sheet",
+            path
+        )
+    })?;
+
+    payload::schema::validate_form(form).with_context(|| {
+        format!(
+            "Dữ liệu báo cáo trong file {:#?} không khớp với định dạng của website NHNN",
+            path
+        )
+    })?;
+
+    check_ownership(&form.payload).with_context(|| {
+        format!(
+            "Thông tin chủ sở hữu hưởng lợi trong file {:#?} không hợp lệ",
+            path
+        )
+    })?;
+
+    check_payload_rules(&form.payload)
+        .with_context(|| format!("Dữ liệu báo cáo trong file {:#?} không hợp lệ", path))?;
+
+    check_amendment(form).with_context(|| {
+        format!(
+            "Thông tin sửa đổi, bổ sung báo cáo trong file {:#?} không hợp lệ",
+            path
+        )
+    })?;
+
+    check_deadline(form, false, None)
+        .with_context(|| format!("Không thể xác định hạn nộp báo cáo trong file {:#?}", path))?;
+
+    Ok(())
+}
+
+/// Reads a report payload saved as JSON, e.g. by `read-excel`, checked against the schema of its
+/// form before it is deserialized.
+fn form_from_json(path: &Path) -> anyhow::Result<Form> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Không thể đọc file {:#?}", path))?;
+    let instance: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("File {:#?} không phải là file JSON hợp lệ", path))?;
+
+    payload::schema::validate_form_value(&instance).with_context(|| {
+        format!(
+            "Dữ liệu báo cáo trong file {:#?} không khớp với định dạng của website NHNN",
+            path
+        )
+    })?;
+
+    serde_json::from_value(instance)
+        .with_context(|| format!("Lỗi khi đọc dữ liệu báo cáo từ file {:#?}", path))
+}
+
 async fn _main() -> anyhow::Result<()> {
     let progress_bar = initial_setup()?;
+    let form_override = select_report_form_from_args()?;
+    let json_files = json_inputs_from_args()?;
+
+    if !json_files.is_empty() {
+        progress_bar.set_length(json_files.len() as u64);
+        for json_path in json_files {
+            let mut form = form_from_json(&json_path)?;
+            check_form(&mut form, &json_path)?;
+
+            progress_bar.inc(1);
+            log::info!("Đã xử lý xong file {:#?}", json_path);
+        }
+
+        progress_bar.finish_with_message("DONE!!!".green().to_string());
+        return Ok(());
+    }
+
     let excel_files = get_input_excel_files()?;
     progress_bar.set_length(excel_files.len() as u64);
 
@@ -34,9 +121,11 @@ async fn _main() -> anyhow::Result<()> {
         let mut workbook: Xlsx<_> = open_workbook(excel_path.clone())
             .with_context(|| format!("Không thể mở file {:#?}", excel_path))?;
 
-        let form = payload::form::Form::from_excel(&mut workbook, &excel_path)
+        let mut form = Form::from_excel(&mut workbook, &excel_path, form_override)
             .with_context(|| format!("Lỗi khi đọc và xử lý dữ liệu từ file {:#?}", excel_path))?;
 
+        check_form(&mut form, &excel_path)?;
+
         let _ = serde_json::to_string_pretty(&form).with_context(|| {
             format!(
                 "Lỗi khi chuyển đổi dữ liệu thành file {:#?} thành định dạng JSON",
@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::AccountStatus};
 
-const ACCOUNT_STATUS_CODES: [(&str, &str); 5] = [
+pub const ACCOUNT_STATUS_CODES: [(&str, &str); 5] = [
     ("ACTIV", "Đang hoạt động"),
     ("CLOSE", "Đã đóng"),
     ("BLOCK", "Bị phong tỏa"),
@@ -9,13 +9,13 @@ const ACCOUNT_STATUS_CODES: [(&str, &str); 5] = [
 ];
 
 pub trait AccountStatusCode {
-    fn to_account_status_code(&self) -> anyhow::Result<String>;
+    fn to_account_status_code(&self) -> anyhow::Result<AccountStatus>;
 }
 
 impl AccountStatusCode for String {
-    fn to_account_status_code(&self) -> anyhow::Result<String> {
+    fn to_account_status_code(&self) -> anyhow::Result<AccountStatus> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(AccountStatus::blank()),
             status => {
                 let status_code = search_for_code(&ACCOUNT_STATUS_CODES, status);
                 if status_code.is_empty() {
@@ -24,7 +24,7 @@ impl AccountStatusCode for String {
                         status
                     ))
                 } else {
-                    Ok(status_code)
+                    AccountStatus::new(status_code)
                 }
             }
         }
@@ -32,10 +32,10 @@ impl AccountStatusCode for String {
 }
 
 impl AccountStatusCode for Option<String> {
-    fn to_account_status_code(&self) -> anyhow::Result<String> {
+    fn to_account_status_code(&self) -> anyhow::Result<AccountStatus> {
         match self {
             Some(status) => status.to_account_status_code(),
-            None => Ok(String::new()),
+            None => Ok(AccountStatus::blank()),
         }
     }
 }
@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::AccountType};
 
-const ACCOUNT_TYPE_CODES: [(&str, &str); 9] = [
+pub const ACCOUNT_TYPE_CODES: [(&str, &str); 9] = [
     ("CURRE", "TK thanh toán"),
     ("SAVIN", "TK tiết kiệm"),
     ("TERMD", "Tiền gửi có kỳ hạn"),
@@ -13,19 +13,19 @@ const ACCOUNT_TYPE_CODES: [(&str, &str); 9] = [
 ];
 
 pub trait AccountTypeCode {
-    fn to_account_type_code(&self) -> anyhow::Result<String>;
+    fn to_account_type_code(&self) -> anyhow::Result<AccountType>;
 }
 
 impl AccountTypeCode for String {
-    fn to_account_type_code(&self) -> anyhow::Result<String> {
+    fn to_account_type_code(&self) -> anyhow::Result<AccountType> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(AccountType::blank()),
             _ => {
                 let code = search_for_code(&ACCOUNT_TYPE_CODES, &self);
                 if code.is_empty() {
                     Err(anyhow::anyhow!("Loại tài khoản không hợp lệ: {}", self))
                 } else {
-                    Ok(code)
+                    AccountType::new(code)
                 }
             }
         }
@@ -33,10 +33,10 @@ impl AccountTypeCode for String {
 }
 
 impl AccountTypeCode for Option<String> {
-    fn to_account_type_code(&self) -> anyhow::Result<String> {
+    fn to_account_type_code(&self) -> anyhow::Result<AccountType> {
         match self {
             Some(value) => value.to_account_type_code(),
-            None => Ok(String::new()),
+            None => Ok(AccountType::blank()),
         }
     }
 }
@@ -0,0 +1,251 @@
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes::utils::{fold_vietnamese, warn_once},
+    payload::{
+        entities::AddrSimple,
+        section1::{Address, TransactionLocation},
+    },
+};
+
+const VIETNAM_COUNTRY_CODE: &str = "VN";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Province {
+    #[serde(rename = "mã")]
+    pub code: String,
+    #[serde(rename = "tên")]
+    pub name: String,
+    #[serde(rename = "tên cũ", default)]
+    pub former_names: Vec<String>,
+    #[serde(rename = "tên khác", default)]
+    pub aliases: Vec<String>,
+    #[serde(rename = "phường/xã", default)]
+    pub wards: Vec<String>,
+    /// Whether `wards` lists every ward of the province. A name missing from a partial list is
+    /// not reported.
+    #[serde(rename = "đủ phường/xã", default)]
+    pub complete_wards: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AdminUnits {
+    #[serde(rename = "tỉnh/thành phố")]
+    pub provinces: Vec<Province>,
+}
+
+pub fn load_admin_units() -> anyhow::Result<AdminUnits> {
+    const ADMIN_UNITS: &str = include_str!("../admin_units.json");
+    let parsed_result: AdminUnits = serde_json::from_str(ADMIN_UNITS)?;
+    Ok(parsed_result)
+}
+
+pub static ADMIN_UNITS: LazyLock<AdminUnits> =
+    LazyLock::new(|| load_admin_units().expect("Failed to load administrative units"));
+
+fn unit_key(name: &str) -> String {
+    const PREFIXES: [&str; 11] = [
+        "thanh pho ",
+        "tp. ",
+        "tp.",
+        "tp ",
+        "tinh ",
+        "phuong ",
+        "p. ",
+        "xa ",
+        "x. ",
+        "dac khu ",
+        "thi tran ",
+    ];
+
+    let folded = fold_vietnamese(name);
+    PREFIXES
+        .iter()
+        .find_map(|prefix| folded.strip_prefix(prefix))
+        .unwrap_or(&folded)
+        .trim()
+        .to_string()
+}
+
+impl Province {
+    fn find_ward(&self, ward: &str) -> Option<&String> {
+        let key = unit_key(ward);
+        self.wards.iter().find(|name| unit_key(name) == key)
+    }
+}
+
+impl AdminUnits {
+    pub fn find_province(&self, name: &str) -> Option<(&Province, bool)> {
+        let key = unit_key(name);
+        let matches = |names: &[String]| names.iter().any(|n| unit_key(n) == key);
+
+        self.provinces
+            .iter()
+            .find(|p| unit_key(&p.name) == key || matches(&p.aliases))
+            .map(|p| (p, false))
+            .or_else(|| {
+                self.provinces
+                    .iter()
+                    .find(|p| matches(&p.former_names))
+                    .map(|p| (p, true))
+            })
+    }
+
+    pub fn normalize(
+        &self,
+        country: &str,
+        province: &str,
+        ward: &str,
+    ) -> anyhow::Result<(String, String)> {
+        let is_vietnam = country == VIETNAM_COUNTRY_CODE;
+        if province.trim().is_empty() || !(is_vietnam || country.is_empty()) {
+            return Ok((province.to_string(), ward.to_string()));
+        }
+
+        let (matched_province, is_former_name) = match self.find_province(province) {
+            Some(found) => found,
+            // The reference list is partial, an unknown name is kept as typed
+            None => {
+                if is_vietnam {
+                    warn_once(format!(
+                        "Tỉnh/Thành phố '{}' không có trong danh mục đơn vị hành chính, giữ nguyên giá trị đã nhập",
+                        province
+                    ));
+                }
+                return Ok((province.to_string(), ward.to_string()));
+            }
+        };
+
+        if is_former_name {
+            warn_once(format!(
+                "Tỉnh/Thành phố '{}' đã được sắp xếp lại thành '{}' theo Nghị quyết 202/2025/QH15. Tự động chuyển sang tên mới.",
+                province, matched_province.name
+            ));
+        }
+
+        if ward.trim().is_empty() {
+            return Ok((matched_province.name.clone(), ward.to_string()));
+        }
+
+        let canonical_ward = match matched_province.find_ward(ward) {
+            Some(name) => name.clone(),
+            None if !matched_province.complete_wards => ward.to_string(),
+            None => {
+                let other_provinces = self
+                    .provinces
+                    .iter()
+                    .filter(|p| p.find_ward(ward).is_some())
+                    .map(|p| p.name.as_str())
+                    .collect::<Vec<_>>();
+
+                match other_provinces.is_empty() {
+                    true => warn_once(format!(
+                        "Phường/Xã '{}' không có trong danh mục đơn vị hành chính của '{}', giữ nguyên giá trị đã nhập",
+                        ward, matched_province.name
+                    )),
+                    false => warn_once(format!(
+                        "Phường/Xã '{}' không thuộc '{}'. Đơn vị có tên tương ứng thuộc: {}",
+                        ward,
+                        matched_province.name,
+                        other_provinces.join(", ")
+                    )),
+                }
+                ward.to_string()
+            }
+        };
+
+        Ok((matched_province.name.clone(), canonical_ward))
+    }
+}
+
+pub trait AdminUnitCode {
+    fn normalize_admin_units(self) -> anyhow::Result<Self>
+    where
+        Self: Sized;
+}
+
+impl AdminUnitCode for AddrSimple {
+    fn normalize_admin_units(self) -> anyhow::Result<Self> {
+        let (city_province, district) = ADMIN_UNITS.normalize(
+            self.country.as_deref().unwrap_or_default(),
+            self.city_province.as_deref().unwrap_or_default(),
+            self.district.as_deref().unwrap_or_default(),
+        )?;
+
+        Ok(Self {
+            city_province: self.city_province.map(|_| city_province),
+            district: self.district.map(|_| district),
+            ..self
+        })
+    }
+}
+
+impl AdminUnitCode for Address {
+    fn normalize_admin_units(self) -> anyhow::Result<Self> {
+        let (city_province, district) =
+            ADMIN_UNITS.normalize(&self.country, &self.city_province, &self.district)?;
+
+        Ok(Self {
+            city_province,
+            district,
+            ..self
+        })
+    }
+}
+
+impl AdminUnitCode for TransactionLocation {
+    fn normalize_admin_units(self) -> anyhow::Result<Self> {
+        let (city_province, district) =
+            ADMIN_UNITS.normalize(&self.country, &self.city_province, &self.district)?;
+
+        Ok(Self {
+            city_province,
+            district,
+            ..self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unicode_normalization::UnicodeNormalization;
+
+    use super::*;
+
+    #[test]
+    fn unknown_province_is_kept() {
+        let (province, ward) = ADMIN_UNITS
+            .normalize("VN", "Tỉnh Không Tồn Tại", "Phường 1")
+            .unwrap();
+        assert_eq!(province, "Tỉnh Không Tồn Tại");
+        assert_eq!(ward, "Phường 1");
+    }
+
+    #[test]
+    fn wards_of_partial_lists_are_kept() {
+        let hanoi = &ADMIN_UNITS.provinces[0];
+        assert!(!hanoi.complete_wards);
+
+        let (_, ward) = ADMIN_UNITS
+            .normalize("VN", &hanoi.name, "phuong hoan kiem")
+            .unwrap();
+        assert_eq!(ward, "Phường Hoàn Kiếm");
+
+        let (_, ward) = ADMIN_UNITS
+            .normalize("VN", &hanoi.name, "Xã Không Có Trong Danh Sách")
+            .unwrap();
+        assert_eq!(ward, "Xã Không Có Trong Danh Sách");
+    }
+
+    #[test]
+    fn decomposed_names_match() {
+        let composed = &ADMIN_UNITS.provinces[0].name;
+        let decomposed = composed.nfd().collect::<String>();
+        assert_ne!(composed, &decomposed);
+
+        let (province, _) = ADMIN_UNITS.normalize("VN", &decomposed, "").unwrap();
+        assert_eq!(&province, composed);
+    }
+}
@@ -1,11 +1,21 @@
 use chrono::Datelike;
 
+use crate::payload::code::AgeRange;
+
+pub const AGE_RANGE_CODES: [(&str, &str); 5] = [
+    ("1", "Dưới 20 tuổi"),
+    ("2", "Từ 20 đến dưới 30 tuổi"),
+    ("3", "Từ 30 đến dưới 40 tuổi"),
+    ("4", "Từ 40 đến dưới 50 tuổi"),
+    ("5", "Từ 50 tuổi trở lên"),
+];
+
 pub trait AgeRangeCode {
-    fn to_age_range_code(&self) -> Option<String>;
+    fn to_age_range_code(&self) -> Option<AgeRange>;
 }
 
 impl AgeRangeCode for String {
-    fn to_age_range_code(&self) -> Option<String> {
+    fn to_age_range_code(&self) -> Option<AgeRange> {
         let parse_result = chrono::NaiveDate::parse_from_str(self.trim().as_ref(), "%d/%m/%Y");
         let dob = match parse_result {
             Ok(d) => d,
@@ -14,20 +24,21 @@ impl AgeRangeCode for String {
 
         let age = chrono::Utc::now().year() as i32 - dob.year() as i32;
 
-        match age {
+        let code = match age {
             i32::MIN..0 => return None,
-            0..20 => "1".to_string(),
-            20..30 => "2".to_string(),
-            30..40 => "3".to_string(),
-            40..50 => "4".to_string(),
-            50..=i32::MAX => "5".to_string(),
-        }
-        .into()
+            0..20 => "1",
+            20..30 => "2",
+            30..40 => "3",
+            40..50 => "4",
+            50..=i32::MAX => "5",
+        };
+
+        AgeRange::new(code).ok()
     }
 }
 
 impl AgeRangeCode for Option<String> {
-    fn to_age_range_code(&self) -> Option<String> {
+    fn to_age_range_code(&self) -> Option<AgeRange> {
         match self {
             Some(age_range) => age_range.to_age_range_code(),
             None => None,
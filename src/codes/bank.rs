@@ -0,0 +1,291 @@
+use std::{path::Path, sync::OnceLock};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codes::utils::{fold_vietnamese, warn_once},
+    payload::{
+        entities::Bank,
+        section4::{FlowEntryIn, FlowEntryOut, MoneyFlow},
+    },
+};
+
+const LOCAL_BANK_DIRECTORY: &str = "input/danh_muc_ngan_hang.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BankInfo {
+    #[serde(rename = "mã NHNN")]
+    pub sbv_code: Option<String>,
+    #[serde(rename = "BIN NAPAS")]
+    pub napas_bin: Option<String>,
+    #[serde(rename = "tên viết tắt")]
+    pub short_name: String,
+    #[serde(rename = "tên đầy đủ")]
+    pub full_name: String,
+    #[serde(rename = "SWIFT BIC")]
+    pub swift_bic: Option<String>,
+    #[serde(rename = "tên khác", default)]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BankDirectory {
+    #[serde(rename = "ngân hàng")]
+    pub banks: Vec<BankInfo>,
+}
+
+pub fn load_bank_directory() -> anyhow::Result<BankDirectory> {
+    const BANK_DIRECTORY: &str = include_str!("../banks.json");
+
+    let local_path = Path::new(LOCAL_BANK_DIRECTORY);
+    if local_path.is_file() {
+        let content = std::fs::read_to_string(local_path)
+            .with_context(|| format!("Không thể đọc file {:#?}", local_path))?;
+        let parsed_result: BankDirectory = serde_json::from_str(&content)
+            .with_context(|| format!("File danh mục ngân hàng {:#?} không hợp lệ", local_path))?;
+        return Ok(parsed_result);
+    }
+
+    let parsed_result: BankDirectory = serde_json::from_str(BANK_DIRECTORY)?;
+    Ok(parsed_result)
+}
+
+static BANK_DIRECTORY: OnceLock<BankDirectory> = OnceLock::new();
+
+/// The bank directory, read on first use.
+pub fn bank_directory() -> anyhow::Result<&'static BankDirectory> {
+    if let Some(directory) = BANK_DIRECTORY.get() {
+        return Ok(directory);
+    }
+    let directory = load_bank_directory()?;
+    Ok(BANK_DIRECTORY.get_or_init(|| directory))
+}
+
+fn bank_name_key(name: &str) -> String {
+    const PREFIXES: [&str; 5] = [
+        "ngan hang tmcp ",
+        "ngan hang tnhh mtv ",
+        "ngan hang ",
+        "nh tmcp ",
+        "nh ",
+    ];
+
+    let folded = fold_vietnamese(name);
+    PREFIXES
+        .iter()
+        .find_map(|prefix| folded.strip_prefix(prefix))
+        .unwrap_or(&folded)
+        .trim()
+        .to_string()
+}
+
+impl BankInfo {
+    fn matches_code(&self, code: &str) -> bool {
+        let code = code.trim().to_uppercase().replace(' ', "");
+        let same = |value: &Option<String>| value.as_deref().is_some_and(|v| v == code);
+
+        // Citad branch codes carry the SBV bank code in positions 3-5, e.g. 01310001
+        let citad_bank_code = match code.len() == 8 && code.chars().all(|c| c.is_ascii_digit()) {
+            true => Some(code[2..5].to_string()),
+            false => None,
+        };
+
+        same(&self.sbv_code)
+            || same(&self.napas_bin)
+            || self
+                .swift_bic
+                .as_deref()
+                .is_some_and(|bic| code.get(..8).is_some_and(|prefix| bic == prefix))
+            || (citad_bank_code.is_some() && self.sbv_code == citad_bank_code)
+    }
+
+    fn matches_name(&self, name: &str) -> bool {
+        let key = bank_name_key(name);
+        std::iter::once(&self.short_name)
+            .chain(std::iter::once(&self.full_name))
+            .chain(self.aliases.iter())
+            .any(|n| bank_name_key(n) == key)
+    }
+
+    fn primary_code(&self) -> Option<String> {
+        self.sbv_code.clone().or(self.napas_bin.clone())
+    }
+}
+
+impl BankDirectory {
+    pub fn find_by_code(&self, code: &str) -> Option<&BankInfo> {
+        self.banks.iter().find(|bank| bank.matches_code(code))
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&BankInfo> {
+        self.banks.iter().find(|bank| bank.matches_name(name))
+    }
+
+    pub fn complete(
+        &self,
+        bank_code: Option<String>,
+        bank_name: Option<String>,
+    ) -> (Option<String>, Option<String>) {
+        let bank_code = bank_code.filter(|v| !v.trim().is_empty());
+        let bank_name = bank_name.filter(|v| !v.trim().is_empty());
+
+        match (bank_code, bank_name) {
+            (None, None) => (None, None),
+            (Some(code), None) => match self.find_by_code(&code) {
+                Some(bank) => (Some(code), Some(bank.full_name.clone())),
+                None => {
+                    warn_once(format!(
+                        "Mã ngân hàng '{}' không có trong danh mục ngân hàng",
+                        code
+                    ));
+                    (Some(code), None)
+                }
+            },
+            (None, Some(name)) => match self.find_by_name(&name) {
+                Some(bank) => (bank.primary_code(), Some(name)),
+                None => {
+                    warn_once(format!(
+                        "Tên ngân hàng '{}' không có trong danh mục ngân hàng",
+                        name
+                    ));
+                    (None, Some(name))
+                }
+            },
+            (Some(code), Some(name)) => {
+                match (self.find_by_code(&code), self.find_by_name(&name)) {
+                    (None, _) => {
+                        warn_once(format!(
+                            "Mã ngân hàng '{}' không có trong danh mục ngân hàng",
+                            code
+                        ));
+                    }
+                    (Some(by_code), Some(by_name)) if by_code.full_name != by_name.full_name => {
+                        warn_once(format!(
+                            "Mã ngân hàng '{}' ({}) không khớp với tên ngân hàng '{}'",
+                            code, by_code.full_name, name
+                        ));
+                    }
+                    _ => {}
+                }
+                (Some(code), Some(name))
+            }
+        }
+    }
+}
+
+pub trait BankCode {
+    fn complete_bank_info(self) -> anyhow::Result<Self>
+    where
+        Self: Sized;
+}
+
+impl BankCode for Bank {
+    fn complete_bank_info(self) -> anyhow::Result<Self> {
+        let (bank_code, bank_name) = bank_directory()?.complete(self.bank_code, self.bank_name);
+        Ok(Bank {
+            bank_code,
+            bank_name,
+        })
+    }
+}
+
+impl BankCode for MoneyFlow {
+    fn complete_bank_info(self) -> anyhow::Result<Self> {
+        let (bank_code, bank_name) = bank_directory()?.complete(self.bank_code, self.bank_name);
+        Ok(MoneyFlow {
+            bank_code,
+            bank_name,
+            ..self
+        })
+    }
+}
+
+impl BankCode for FlowEntryIn {
+    fn complete_bank_info(self) -> anyhow::Result<Self> {
+        let (source_bank_code, source_bank_name) =
+            bank_directory()?.complete(self.source_bank_code, self.source_bank_name);
+        Ok(FlowEntryIn {
+            source_bank_code,
+            source_bank_name,
+            ..self
+        })
+    }
+}
+
+impl BankCode for FlowEntryOut {
+    fn complete_bank_info(self) -> anyhow::Result<Self> {
+        let (dest_bank_code, dest_bank_name) =
+            bank_directory()?.complete(self.dest_bank_code, self.dest_bank_name);
+        Ok(FlowEntryOut {
+            dest_bank_code,
+            dest_bank_name,
+            ..self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directory() -> BankDirectory {
+        load_bank_directory().unwrap()
+    }
+
+    #[test]
+    fn banks_are_found_by_any_code() {
+        let directory = directory();
+        let short_name = |code: &str| directory.find_by_code(code).map(|b| b.short_name.as_str());
+
+        assert_eq!(short_name("203"), Some("Vietcombank"));
+        assert_eq!(short_name("970436"), Some("Vietcombank"));
+        assert_eq!(short_name("bftv vnvx xxx"), Some("Vietcombank"));
+        // Citad branch code of a Vietcombank branch
+        assert_eq!(short_name("01203001"), Some("Vietcombank"));
+        assert_eq!(short_name("999"), None);
+    }
+
+    #[test]
+    fn non_ascii_codes_do_not_panic() {
+        let directory = directory();
+        assert!(directory.find_by_code("Bản Việt").is_none());
+        assert!(directory.find_by_code("Ngân hàng").is_none());
+    }
+
+    #[test]
+    fn banks_are_found_by_name() {
+        let directory = directory();
+        let short_name = |name: &str| directory.find_by_name(name).map(|b| b.short_name.as_str());
+
+        assert_eq!(
+            short_name("NH TMCP Ngoại Thương Việt Nam"),
+            Some("Vietcombank")
+        );
+        assert_eq!(short_name("VCB"), Some("Vietcombank"));
+        assert_eq!(short_name("ngan hang vietinbank"), Some("VietinBank"));
+        assert_eq!(short_name("Ngân hàng Không Tồn Tại"), None);
+    }
+
+    #[test]
+    fn missing_code_or_name_is_completed() {
+        let directory = directory();
+
+        assert_eq!(
+            directory.complete(Some("970436".to_string()), None),
+            (
+                Some("970436".to_string()),
+                Some("Ngân hàng TMCP Ngoại thương Việt Nam".to_string())
+            )
+        );
+        assert_eq!(
+            directory.complete(None, Some("BIDV".to_string())),
+            (Some("202".to_string()), Some("BIDV".to_string()))
+        );
+        assert_eq!(
+            directory.complete(Some("Bản Việt".to_string()), Some(" ".to_string())),
+            (Some("Bản Việt".to_string()), None)
+        );
+        assert_eq!(directory.complete(None, Some("".to_string())), (None, None));
+    }
+}
@@ -0,0 +1,24 @@
+use crate::codes::utils::search_for_code;
+
+pub const CHANGE_TYPES: [(&str, &str); 3] =
+    [("0", "Báo cáo mới"), ("1", "Sửa đổi"), ("2", "Bổ sung")];
+
+pub trait ChangeTypeCode {
+    fn to_change_type_code(&self) -> anyhow::Result<i32>;
+}
+
+impl ChangeTypeCode for String {
+    fn to_change_type_code(&self) -> anyhow::Result<i32> {
+        match self.trim() {
+            "" => Ok(0),
+            change_type => {
+                let code = match CHANGE_TYPES.iter().any(|(code, _)| *code == change_type) {
+                    true => change_type.to_string(),
+                    false => search_for_code(&CHANGE_TYPES, change_type),
+                };
+                code.parse::<i32>()
+                    .map_err(|_| anyhow::anyhow!("Loại thay đổi không hợp lệ: {}", change_type))
+            }
+        }
+    }
+}
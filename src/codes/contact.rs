@@ -0,0 +1,183 @@
+use crate::{codes::utils::warn_once, utils::excel::CellAddress};
+
+const VN_MOBILE_PREFIXES: [&str; 5] = ["03", "05", "07", "08", "09"];
+const VN_LANDLINE_PREFIX: &str = "02";
+const VN_HOTLINE_PREFIXES: [&str; 2] = ["1800", "1900"];
+
+fn split_contacts(value: &str) -> Vec<&str> {
+    value
+        .split([',', ';', '/'])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn normalize_phone(value: &str) -> Option<String> {
+    // Excel stores numeric phone cells as floats, e.g. 912345678.0
+    let value = value.strip_suffix(".0").unwrap_or(value);
+    let compact = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '.' | '-' | '(' | ')'))
+        .collect::<String>();
+
+    let (international, digits) = match (compact.strip_prefix('+'), compact.strip_prefix("00")) {
+        (Some(digits), _) | (None, Some(digits)) => (true, digits.to_string()),
+        (None, None) => (false, compact),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if international {
+        return match (8..=15).contains(&digits.len()) && !digits.starts_with('0') {
+            true => Some(format!("+{}", digits)),
+            false => None,
+        };
+    }
+
+    if VN_HOTLINE_PREFIXES.iter().any(|p| digits.starts_with(p)) {
+        return match digits.len() == 8 || digits.len() == 10 {
+            true => Some(digits),
+            false => None,
+        };
+    }
+
+    // Excel drops the leading zero when the number is stored as a number
+    let digits = match digits.starts_with('0') {
+        true => digits,
+        false => format!("0{}", digits),
+    };
+
+    let is_mobile = digits.len() == 10 && VN_MOBILE_PREFIXES.iter().any(|p| digits.starts_with(p));
+    let is_landline = digits.len() == 11 && digits.starts_with(VN_LANDLINE_PREFIX);
+
+    match is_mobile || is_landline {
+        true => Some(digits),
+        false => None,
+    }
+}
+
+fn normalize_email(value: &str) -> Option<String> {
+    let (local, domain) = value.split_once('@')?;
+
+    let valid_local = !local.is_empty()
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._%+-".contains(c));
+
+    let labels = domain.split('.').collect::<Vec<_>>();
+    let valid_domain = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+
+    match valid_local && valid_domain {
+        true => Some(format!("{}@{}", local, domain.to_lowercase())),
+        false => None,
+    }
+}
+
+fn normalize_contacts(
+    value: &str,
+    normalize: fn(&str) -> Option<String>,
+    kind: &str,
+    source: &CellAddress,
+) -> String {
+    split_contacts(value)
+        .into_iter()
+        .map(|contact| match normalize(contact) {
+            Some(normalized) => normalized,
+            None => {
+                warn_once(format!(
+                    "{} '{}' tại ô {} không hợp lệ",
+                    kind, contact, source
+                ));
+                contact.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub trait ContactInfo {
+    fn to_phone_number(&self, source: &CellAddress) -> Self;
+    fn to_email(&self, source: &CellAddress) -> Self;
+}
+
+impl ContactInfo for String {
+    fn to_phone_number(&self, source: &CellAddress) -> Self {
+        normalize_contacts(self, normalize_phone, "Số điện thoại", source)
+    }
+
+    fn to_email(&self, source: &CellAddress) -> Self {
+        normalize_contacts(self, normalize_email, "Địa chỉ email", source)
+    }
+}
+
+impl ContactInfo for Option<String> {
+    fn to_phone_number(&self, source: &CellAddress) -> Self {
+        self.as_ref().map(|value| value.to_phone_number(source))
+    }
+
+    fn to_email(&self, source: &CellAddress) -> Self {
+        self.as_ref().map(|value| value.to_email(source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phone_numbers_are_normalized() {
+        assert_eq!(
+            normalize_phone("912345678.0").as_deref(),
+            Some("0912345678")
+        );
+        assert_eq!(
+            normalize_phone("091 234-5678").as_deref(),
+            Some("0912345678")
+        );
+        assert_eq!(
+            normalize_phone("024.3825.1234").as_deref(),
+            Some("02438251234")
+        );
+        assert_eq!(normalize_phone("1900 1234").as_deref(), Some("19001234"));
+        assert_eq!(
+            normalize_phone("+84 912 345 678").as_deref(),
+            Some("+84912345678")
+        );
+        assert_eq!(
+            normalize_phone("0084912345678").as_deref(),
+            Some("+84912345678")
+        );
+    }
+
+    #[test]
+    fn invalid_phone_numbers_are_rejected() {
+        assert_eq!(normalize_phone("0112345678"), None);
+        assert_eq!(normalize_phone("09123"), None);
+        assert_eq!(normalize_phone("+0912345678"), None);
+        assert_eq!(normalize_phone("không có"), None);
+    }
+
+    #[test]
+    fn emails_are_normalized() {
+        assert_eq!(
+            normalize_email("Nguyen.Van@Example.COM").as_deref(),
+            Some("Nguyen.Van@example.com")
+        );
+        assert_eq!(normalize_email(".nguyen@example.com"), None);
+        assert_eq!(normalize_email("nguyen@example"), None);
+        assert_eq!(normalize_email("nguyen@example.c0m"), None);
+    }
+}
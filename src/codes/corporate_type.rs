@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::CorporateType};
 
-const CORPORATE_TYPES: [(&str, &str); 7] = [
+pub const CORPORATE_TYPES: [(&str, &str); 7] = [
     ("1", "Công ty TNHH Một thành viên"),
     ("2", "Công ty TNHH Hai thành viên trở lên"),
     ("3", "Công ty cổ phần"),
@@ -11,13 +11,13 @@ const CORPORATE_TYPES: [(&str, &str); 7] = [
 ];
 
 pub trait CorporateTypeCode {
-    fn to_corporate_type_code(&self) -> anyhow::Result<String>;
+    fn to_corporate_type_code(&self) -> anyhow::Result<CorporateType>;
 }
 
 impl CorporateTypeCode for String {
-    fn to_corporate_type_code(&self) -> anyhow::Result<String> {
+    fn to_corporate_type_code(&self) -> anyhow::Result<CorporateType> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(CorporateType::blank()),
             _ => {
                 let code = search_for_code(&CORPORATE_TYPES, &self);
                 if code.is_empty() {
@@ -26,7 +26,7 @@ impl CorporateTypeCode for String {
                         self
                     ))
                 } else {
-                    Ok(code)
+                    CorporateType::new(code)
                 }
             }
         }
@@ -34,10 +34,10 @@ impl CorporateTypeCode for String {
 }
 
 impl CorporateTypeCode for Option<String> {
-    fn to_corporate_type_code(&self) -> anyhow::Result<String> {
+    fn to_corporate_type_code(&self) -> anyhow::Result<CorporateType> {
         match self {
             Some(value) => value.to_corporate_type_code(),
-            None => Ok(String::new()),
+            None => Ok(CorporateType::blank()),
         }
     }
 }
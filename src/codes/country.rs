@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::Country};
 
-const COUNTRY_CODES: [(&str, &str); 253] = [
+pub const COUNTRY_CODES: [(&str, &str); 253] = [
     ("AD", "ANDORRA"),
     ("AE", "UNITED ARAB EMIRATES"),
     ("AF", "AFGHANISTAN"),
@@ -257,13 +257,13 @@ const COUNTRY_CODES: [(&str, &str); 253] = [
 ];
 
 pub trait CountryCode {
-    fn to_country_code(&self) -> anyhow::Result<String>;
+    fn to_country_code(&self) -> anyhow::Result<Country>;
 }
 
 impl CountryCode for String {
-    fn to_country_code(&self) -> anyhow::Result<String> {
+    fn to_country_code(&self) -> anyhow::Result<Country> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(Country::blank()),
             country_name => {
                 let country_code = search_for_code(&COUNTRY_CODES, country_name);
                 if country_code.is_empty() {
@@ -272,7 +272,7 @@ impl CountryCode for String {
                         country_name
                     ))
                 } else {
-                    Ok(country_code)
+                    Country::new(country_code)
                 }
             }
         }
@@ -280,10 +280,10 @@ impl CountryCode for String {
 }
 
 impl CountryCode for Option<String> {
-    fn to_country_code(&self) -> anyhow::Result<String> {
+    fn to_country_code(&self) -> anyhow::Result<Country> {
         match self {
             Some(country_name) => country_name.to_country_code(),
-            None => Ok(String::new()),
+            None => Ok(Country::blank()),
         }
     }
 }
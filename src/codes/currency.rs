@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::Currency};
 
-const CURRENCY_CODES: [(&'static str, &'static str); 9] = [
+pub const CURRENCY_CODES: [(&str, &str); 9] = [
     ("VND", "VND - Việt Nam Đồng"),
     ("USD", "USD - United States Dollar"),
     ("AUD", "AUD - Australian Dollar"),
@@ -13,13 +13,13 @@ const CURRENCY_CODES: [(&'static str, &'static str); 9] = [
 ];
 
 pub trait CurrencyCode {
-    fn to_currency_code(&self) -> anyhow::Result<String>;
+    fn to_currency_code(&self) -> anyhow::Result<Currency>;
 }
 
 impl CurrencyCode for String {
-    fn to_currency_code(&self) -> anyhow::Result<String> {
+    fn to_currency_code(&self) -> anyhow::Result<Currency> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(Currency::blank()),
             currency_name => {
                 let currency_code = search_for_code(&CURRENCY_CODES, currency_name);
                 if currency_code.is_empty() {
@@ -28,7 +28,7 @@ impl CurrencyCode for String {
                         currency_name
                     ))
                 } else {
-                    Ok(currency_code)
+                    Currency::new(currency_code)
                 }
             }
         }
@@ -36,10 +36,10 @@ impl CurrencyCode for String {
 }
 
 impl CurrencyCode for Option<String> {
-    fn to_currency_code(&self) -> anyhow::Result<String> {
+    fn to_currency_code(&self) -> anyhow::Result<Currency> {
         match self {
             Some(currency_name) => currency_name.to_currency_code(),
-            None => Ok(String::new()),
+            None => Ok(Currency::blank()),
         }
     }
 }
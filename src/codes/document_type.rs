@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::AttachmentType};
 
-const DOCUMENT_TYPES: [(&'static str, &'static str); 7] = [
+pub const DOCUMENT_TYPES: [(&str, &str); 7] = [
     ("STM", "Bảng kê"),
     ("FLW", "Minh họa dòng tiền"),
     ("REL", "Minh họa mối quan hệ khách hàng"),
@@ -11,26 +11,26 @@ const DOCUMENT_TYPES: [(&'static str, &'static str); 7] = [
 ];
 
 pub trait DocumentType {
-    fn to_document_type(&self) -> anyhow::Result<String>;
-    fn validate_document_type(&self) -> anyhow::Result<String>;
+    fn to_document_type(&self) -> anyhow::Result<AttachmentType>;
+    fn validate_document_type(&self) -> anyhow::Result<AttachmentType>;
 }
 
 impl DocumentType for String {
-    fn to_document_type(&self) -> anyhow::Result<String> {
+    fn to_document_type(&self) -> anyhow::Result<AttachmentType> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(AttachmentType::blank()),
             _ => {
                 let code = search_for_code(&DOCUMENT_TYPES, &self);
                 if code.is_empty() {
                     Err(anyhow::anyhow!("Loại tài liệu không hợp lệ: {}", self))
                 } else {
-                    Ok(code)
+                    AttachmentType::new(code)
                 }
             }
         }
     }
 
-    fn validate_document_type(&self) -> anyhow::Result<String> {
+    fn validate_document_type(&self) -> anyhow::Result<AttachmentType> {
         let check = DOCUMENT_TYPES
             .into_iter()
             .map(|d| d.0)
@@ -52,23 +52,23 @@ impl DocumentType for String {
                 "Cần đặt tên file có tiền tố tương ứng. Ví dụ Bảng kê: STM_CIF100_202502.xlsx"
             ))
         } else {
-            Ok(self.clone())
+            AttachmentType::new(self.as_str())
         }
     }
 }
 
 impl DocumentType for Option<String> {
-    fn validate_document_type(&self) -> anyhow::Result<String> {
+    fn validate_document_type(&self) -> anyhow::Result<AttachmentType> {
         match self {
             Some(document_name) => document_name.validate_document_type(),
-            None => Ok(String::new()),
+            None => Ok(AttachmentType::blank()),
         }
     }
 
-    fn to_document_type(&self) -> anyhow::Result<String> {
+    fn to_document_type(&self) -> anyhow::Result<AttachmentType> {
         match self {
             Some(document_name) => document_name.to_document_type(),
-            None => Ok(String::new()),
+            None => Ok(AttachmentType::blank()),
         }
     }
 }
@@ -0,0 +1,157 @@
+use anyhow::Context;
+
+use crate::{
+    codes::utils::warn_once,
+    payload::entities::{EnterpriseCode, License},
+    utils::datetime::ensure_not_after,
+};
+
+const VIETNAM_COUNTRY_CODE: &str = "VN";
+const CHECKSUM_WEIGHTS: [u32; 9] = [31, 29, 23, 19, 17, 13, 7, 5, 3];
+
+fn checksum_digit(digits: &[u32]) -> Option<u32> {
+    let weighted_sum = digits
+        .iter()
+        .zip(CHECKSUM_WEIGHTS.iter())
+        .map(|(digit, weight)| digit * weight)
+        .sum::<u32>();
+
+    match 10 - (weighted_sum % 11) as i32 {
+        10 => None,
+        digit => Some(digit as u32),
+    }
+}
+
+pub fn is_valid_vn_enterprise_code(code: &str) -> bool {
+    code.to_vn_enterprise_code().is_ok()
+}
+
+pub trait VnEnterpriseCode {
+    fn to_vn_enterprise_code(&self) -> anyhow::Result<String>;
+}
+
+impl VnEnterpriseCode for str {
+    fn to_vn_enterprise_code(&self) -> anyhow::Result<String> {
+        let compact = self
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '.')
+            .collect::<String>();
+
+        if compact.is_empty() || !compact.chars().all(|c| c.is_ascii_digit()) {
+            return Err(anyhow::anyhow!(
+                "Mã số doanh nghiệp/mã số thuế '{}' chỉ được chứa chữ số",
+                self
+            ));
+        }
+
+        // Excel drops the leading zero when the code is stored as a number
+        let compact = match compact.len() {
+            9 | 12 => format!("0{}", compact),
+            _ => compact,
+        };
+
+        let (main_code, branch_code) = match compact.len() {
+            10 => (&compact[..10], None),
+            13 => (&compact[..10], Some(&compact[10..])),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Mã số doanh nghiệp/mã số thuế '{}' phải gồm 10 chữ số hoặc 13 chữ số (mã đơn vị phụ thuộc)",
+                    self
+                ));
+            }
+        };
+
+        let digits = main_code
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .collect::<Vec<_>>();
+
+        if checksum_digit(&digits[..9]) != Some(digits[9]) {
+            return Err(anyhow::anyhow!(
+                "Mã số doanh nghiệp/mã số thuế '{}' có chữ số kiểm tra không hợp lệ",
+                self
+            ));
+        }
+
+        match branch_code {
+            None => Ok(main_code.to_string()),
+            Some("000") => Err(anyhow::anyhow!(
+                "Mã đơn vị phụ thuộc của mã số thuế '{}' không hợp lệ",
+                self
+            )),
+            Some(branch_code) => Ok(format!("{}-{}", main_code, branch_code)),
+        }
+    }
+}
+
+pub trait EnterpriseCodeCheck {
+    fn validate_enterprise_code(self, report_date: &Option<String>) -> anyhow::Result<Self>
+    where
+        Self: Sized;
+}
+
+impl EnterpriseCodeCheck for EnterpriseCode {
+    fn validate_enterprise_code(self, report_date: &Option<String>) -> anyhow::Result<Self> {
+        ensure_not_after(&self.issue_date, report_date)
+            .context("Ngày cấp MST không được sau ngày báo cáo")?;
+
+        let code = match self.code.as_deref().map(str::trim) {
+            None | Some("") => return Ok(self),
+            Some(code) => code,
+        };
+
+        let issue_place = self.issue_place.as_deref().unwrap_or_default();
+        let code = match issue_place {
+            VIETNAM_COUNTRY_CODE => code.to_vn_enterprise_code()?,
+            "" => code
+                .to_vn_enterprise_code()
+                .unwrap_or_else(|_| code.to_string()),
+            _ => {
+                if is_valid_vn_enterprise_code(code) {
+                    warn_once(format!(
+                        "Mã số thuế '{}' có định dạng của Việt Nam nhưng Quốc gia cấp MST là '{}'",
+                        code, issue_place
+                    ));
+                }
+                code.to_string()
+            }
+        };
+
+        Ok(EnterpriseCode {
+            code: code.into(),
+            ..self
+        })
+    }
+}
+
+impl EnterpriseCodeCheck for License {
+    fn validate_enterprise_code(self, report_date: &Option<String>) -> anyhow::Result<Self> {
+        ensure_not_after(&self.issue_date, report_date)
+            .context("Ngày cấp giấy phép thành lập không được sau ngày báo cáo")?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_digit_is_checked() {
+        assert_eq!("0100109106".to_vn_enterprise_code().unwrap(), "0100109106");
+        assert!("0100109105".to_vn_enterprise_code().is_err());
+        // The weighted sum leaves no valid check digit for this prefix
+        assert!("0101686890".to_vn_enterprise_code().is_err());
+    }
+
+    #[test]
+    fn dropped_leading_zero_and_branch_code() {
+        assert_eq!("100109106".to_vn_enterprise_code().unwrap(), "0100109106");
+        assert_eq!(
+            "0100109106 001".to_vn_enterprise_code().unwrap(),
+            "0100109106-001"
+        );
+        assert!("0100109106-000".to_vn_enterprise_code().is_err());
+        assert!("01001091".to_vn_enterprise_code().is_err());
+    }
+}
@@ -1,22 +1,21 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::Gender};
 
-const GENDER_CODES: [(&'static str, &'static str); 3] =
-    [("male", "Nam"), ("female", "Nữ"), ("other", "Khác")];
+pub const GENDER_CODES: [(&str, &str); 3] = [("male", "Nam"), ("female", "Nữ"), ("other", "Khác")];
 
 pub trait GenderCode {
-    fn to_gender_code(&self) -> anyhow::Result<String>;
+    fn to_gender_code(&self) -> anyhow::Result<Gender>;
 }
 
 impl GenderCode for String {
-    fn to_gender_code(&self) -> anyhow::Result<String> {
+    fn to_gender_code(&self) -> anyhow::Result<Gender> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(Gender::blank()),
             _ => {
                 let code = search_for_code(&GENDER_CODES, &self);
                 if code.is_empty() {
                     Err(anyhow::anyhow!("Giới tính không hợp lệ: {}", self))
                 } else {
-                    Ok(code)
+                    Gender::new(code)
                 }
             }
         }
@@ -24,10 +23,10 @@ impl GenderCode for String {
 }
 
 impl GenderCode for Option<String> {
-    fn to_gender_code(&self) -> anyhow::Result<String> {
+    fn to_gender_code(&self) -> anyhow::Result<Gender> {
         match self {
             Some(gender) => gender.to_gender_code(),
-            None => Ok(String::new()),
+            None => Ok(Gender::blank()),
         }
     }
 }
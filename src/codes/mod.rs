@@ -1,11 +1,17 @@
 pub mod account_status;
 pub mod account_type;
+pub mod admin_unit;
 pub mod age_range;
+pub mod bank;
+pub mod change_type;
+pub mod contact;
 pub mod corporate_type;
 pub mod country;
 pub mod currency;
 pub mod document_type;
+pub mod enterprise_code;
 pub mod gender;
 pub mod occupation;
 pub mod personal_id;
+pub mod processed_task;
 pub mod utils;
@@ -1,4 +1,4 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::OccupationType};
 
 pub const OCCUPATION_CODES: [(&'static str, &'static str); 13] = [
     ("1", "Công chức/viên chức"),
@@ -17,19 +17,19 @@ pub const OCCUPATION_CODES: [(&'static str, &'static str); 13] = [
 ];
 
 pub trait OccupationCode {
-    fn to_occupation_code(&self) -> anyhow::Result<String>;
+    fn to_occupation_code(&self) -> anyhow::Result<OccupationType>;
 }
 
 impl OccupationCode for String {
-    fn to_occupation_code(&self) -> anyhow::Result<String> {
+    fn to_occupation_code(&self) -> anyhow::Result<OccupationType> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(OccupationType::blank()),
             _ => {
                 let code = search_for_code(&OCCUPATION_CODES, &self);
                 if code.is_empty() {
                     Err(anyhow::anyhow!("Nghề nghiệp không hợp lệ: {}", self))
                 } else {
-                    Ok(code)
+                    OccupationType::new(code)
                 }
             }
         }
@@ -37,10 +37,10 @@ impl OccupationCode for String {
 }
 
 impl OccupationCode for Option<String> {
-    fn to_occupation_code(&self) -> anyhow::Result<String> {
+    fn to_occupation_code(&self) -> anyhow::Result<OccupationType> {
         match self {
             Some(occupation) => occupation.to_occupation_code(),
-            None => Ok(String::new()),
+            None => Ok(OccupationType::blank()),
         }
     }
 }
@@ -1,6 +1,6 @@
-use crate::codes::utils::search_for_code;
+use crate::{codes::utils::search_for_code, payload::code::PersonalIdType};
 
-const PERSONAL_ID_CODES: [(&'static str, &'static str); 11] = [
+pub const PERSONAL_ID_CODES: [(&str, &str); 11] = [
     ("101", "CMTND"),
     ("100", "CCCD"),
     ("103", "Hộ chiếu"),
@@ -15,13 +15,13 @@ const PERSONAL_ID_CODES: [(&'static str, &'static str); 11] = [
 ];
 
 pub trait PersonalIdCode {
-    fn to_personal_id_code(&self) -> anyhow::Result<String>;
+    fn to_personal_id_code(&self) -> anyhow::Result<PersonalIdType>;
 }
 
 impl PersonalIdCode for String {
-    fn to_personal_id_code(&self) -> anyhow::Result<String> {
+    fn to_personal_id_code(&self) -> anyhow::Result<PersonalIdType> {
         match self.as_str() {
-            "" => Ok(String::new()),
+            "" => Ok(PersonalIdType::blank()),
             _ => {
                 let code = search_for_code(&PERSONAL_ID_CODES, self);
                 if code.is_empty() {
@@ -30,7 +30,7 @@ impl PersonalIdCode for String {
                         self
                     ))
                 } else {
-                    Ok(code)
+                    PersonalIdType::new(code)
                 }
             }
         }
@@ -38,10 +38,10 @@ impl PersonalIdCode for String {
 }
 
 impl PersonalIdCode for Option<String> {
-    fn to_personal_id_code(&self) -> anyhow::Result<String> {
+    fn to_personal_id_code(&self) -> anyhow::Result<PersonalIdType> {
         match self {
             Some(id_type) => id_type.to_personal_id_code(),
-            None => Ok(String::new()),
+            None => Ok(PersonalIdType::blank()),
         }
     }
 }
@@ -0,0 +1,17 @@
+pub const PROCESSED_TASKS: [(&str, &str); 9] = [
+    ("1", "Từ chối thực hiện giao dịch"),
+    ("2", "Tạm khóa tài khoản"),
+    ("3", "Chấm dứt thiết lập giao dịch với khách hàng"),
+    ("4", "Giám sát sau giao dịch"),
+    ("5", "Đưa vào hệ thống cảnh báo của đối tượng báo cáo"),
+    (
+        "6",
+        "Ngân hàng đã có công văn gửi Cơ quan nhà nước có thẩm quyền",
+    ),
+    (
+        "7",
+        "Ngân hàng nhận được công văn của Cơ quan nhà nước có thẩm quyền yêu cầu cung cấp thông tin, tài liệu",
+    ),
+    ("8", "Tạm ngừng cung cấp dịch vụ ngân hàng điện tử"),
+    ("0", "Công việc khác"),
+];
@@ -1,3 +1,11 @@
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::{LazyLock, Mutex},
+};
+
+use unicode_normalization::UnicodeNormalization;
+
 pub fn search_for_code(code_list: &[(&str, &str)], name: &str) -> String {
     code_list
         .iter()
@@ -10,3 +18,79 @@ pub fn search_for_code(code_list: &[(&str, &str)], name: &str) -> String {
         })
         .unwrap_or_default()
 }
+
+pub fn fold_vietnamese(name: &str) -> String {
+    const VIETNAMESE_CHARS: [(&str, char); 14] = [
+        ("àáạảãâầấậẩẫăằắặẳẵ", 'a'),
+        ("ÀÁẠẢÃÂẦẤẬẨẪĂẰẮẶẲẴ", 'a'),
+        ("èéẹẻẽêềếệểễ", 'e'),
+        ("ÈÉẸẺẼÊỀẾỆỂỄ", 'e'),
+        ("ìíịỉĩ", 'i'),
+        ("ÌÍỊỈĨ", 'i'),
+        ("òóọỏõôồốộổỗơờớợởỡ", 'o'),
+        ("ÒÓỌỎÕÔỒỐỘỔỖƠỜỚỢỞỠ", 'o'),
+        ("ùúụủũưừứựửữ", 'u'),
+        ("ÙÚỤỦŨƯỪỨỰỬỮ", 'u'),
+        ("ỳýỵỷỹ", 'y'),
+        ("ỲÝỴỶỸ", 'y'),
+        ("đ", 'd'),
+        ("Đ", 'd'),
+    ];
+
+    // Text pasted from some sources is decomposed (NFD), the table lists composed characters
+    name.nfc()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .map(|c| {
+            VIETNAMESE_CHARS
+                .iter()
+                .find(|(chars, _)| chars.contains(c))
+                .map(|(_, base)| *base)
+                .unwrap_or(c.to_ascii_lowercase())
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct WarningScope {
+    file_name: Option<String>,
+    warned: HashSet<String>,
+}
+
+static WARNING_SCOPE: LazyLock<Mutex<WarningScope>> = LazyLock::new(Default::default);
+
+/// Starts deduplicating warnings for a new workbook, whose file name prefixes every message.
+pub fn start_warning_scope(file_path: &Path) {
+    let mut scope = WARNING_SCOPE.lock().unwrap_or_else(|err| err.into_inner());
+    *scope = WarningScope {
+        file_name: Some(
+            file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        ),
+        warned: HashSet::new(),
+    };
+}
+
+/// Logs a warning once per workbook.
+pub fn warn_once(message: String) {
+    let mut scope = WARNING_SCOPE.lock().unwrap_or_else(|err| err.into_inner());
+    if scope.warned.insert(message.clone()) {
+        match &scope.file_name {
+            Some(file_name) => log::warn!("{}: {}", file_name, message),
+            None => log::warn!("{}", message),
+        }
+    }
+}
+
+pub fn search_for_name(code_list: &[(&str, &str)], code: &str) -> Option<String> {
+    code_list
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| name.to_string())
+}
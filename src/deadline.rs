@@ -0,0 +1,365 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::payload::form::Form;
+
+const LOCAL_HOLIDAY_CALENDAR: &str = "input/lich_nghi_le.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Holiday {
+    #[serde(rename = "ngày")]
+    date: String,
+    #[serde(rename = "tên", default)]
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CalendarFile {
+    #[serde(rename = "thời hạn báo cáo")]
+    filing_period: u32,
+    /// ISO weekday numbers, Monday is 1
+    #[serde(rename = "ngày nghỉ hằng tuần")]
+    weekly_days_off: Vec<u32>,
+    #[serde(rename = "ngày nghỉ lễ", default)]
+    holidays: Vec<Holiday>,
+    #[serde(rename = "ngày làm bù", default)]
+    working_weekends: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HolidayCalendar {
+    /// Number of working days after the detection date within which the report must be filed
+    pub filing_period: u32,
+    weekly_days_off: Vec<u32>,
+    holidays: BTreeMap<NaiveDate, String>,
+    working_weekends: HashSet<NaiveDate>,
+}
+
+fn parse_calendar_date(value: &str) -> anyhow::Result<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Ngày '{}' không theo định dạng 'yyyy-mm-dd'", value))
+}
+
+impl TryFrom<CalendarFile> for HolidayCalendar {
+    type Error = anyhow::Error;
+
+    fn try_from(file: CalendarFile) -> anyhow::Result<Self> {
+        if let Some(day) = file.weekly_days_off.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(anyhow::anyhow!(
+                "Ngày nghỉ hằng tuần '{}' không hợp lệ, chỉ nhận giá trị từ 1 (thứ Hai) đến 7 (Chủ nhật)",
+                day
+            ));
+        }
+
+        Ok(HolidayCalendar {
+            filing_period: file.filing_period,
+            weekly_days_off: file.weekly_days_off,
+            holidays: file
+                .holidays
+                .into_iter()
+                .map(|holiday| Ok((parse_calendar_date(&holiday.date)?, holiday.name)))
+                .collect::<anyhow::Result<_>>()?,
+            working_weekends: file
+                .working_weekends
+                .iter()
+                .map(|date| parse_calendar_date(date))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+pub fn load_holiday_calendar() -> anyhow::Result<HolidayCalendar> {
+    const HOLIDAY_CALENDAR: &str = include_str!("holidays.json");
+
+    let local_path = Path::new(LOCAL_HOLIDAY_CALENDAR);
+    if local_path.is_file() {
+        let content = std::fs::read_to_string(local_path)
+            .with_context(|| format!("Không thể đọc file {:#?}", local_path))?;
+        let parsed_result: CalendarFile = serde_json::from_str(&content)
+            .with_context(|| format!("File lịch nghỉ lễ {:#?} không hợp lệ", local_path))?;
+        return HolidayCalendar::try_from(parsed_result)
+            .with_context(|| format!("File lịch nghỉ lễ {:#?} không hợp lệ", local_path));
+    }
+
+    let parsed_result: CalendarFile = serde_json::from_str(HOLIDAY_CALENDAR)?;
+    HolidayCalendar::try_from(parsed_result)
+}
+
+static HOLIDAY_CALENDAR: OnceLock<HolidayCalendar> = OnceLock::new();
+
+/// The holiday calendar, loaded on first use. Called at startup so that a malformed local
+/// calendar stops the run before any report is read.
+pub fn holiday_calendar() -> anyhow::Result<&'static HolidayCalendar> {
+    if let Some(calendar) = HOLIDAY_CALENDAR.get() {
+        return Ok(calendar);
+    }
+    let calendar = load_holiday_calendar()?;
+    Ok(HOLIDAY_CALENDAR.get_or_init(|| calendar))
+}
+
+impl HolidayCalendar {
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        if self.working_weekends.contains(&date) {
+            return true;
+        }
+        let weekday = date.weekday().number_from_monday();
+        !self.weekly_days_off.contains(&weekday) && !self.holidays.contains_key(&date)
+    }
+
+    /// The `n`-th working day after `date`, `date` itself is not counted.
+    pub fn add_working_days(&self, date: NaiveDate, n: u32) -> NaiveDate {
+        let mut current = date;
+        let mut remaining = n;
+        while remaining > 0 {
+            current = current + Days::new(1);
+            if self.is_working_day(current) {
+                remaining -= 1;
+            }
+        }
+        current
+    }
+
+    /// Working days in `(from, to]`, negative when `to` is before `from`.
+    pub fn working_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let (start, end, sign) = match from <= to {
+            true => (from, to, 1),
+            false => (to, from, -1),
+        };
+        let count = start
+            .iter_days()
+            .skip(1)
+            .take_while(|date| *date <= end)
+            .filter(|date| self.is_working_day(*date))
+            .count() as i64;
+        sign * count
+    }
+
+    /// Whether the calendar lists any holiday in the year of `date`, used to warn about an
+    /// outdated calendar file.
+    pub fn covers_year(&self, date: NaiveDate) -> bool {
+        self.holidays.keys().any(|d| d.year() == date.year())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FilingDeadline {
+    pub detection_date: NaiveDate,
+    pub due_date: NaiveDate,
+    /// Working days from `today` until the due date, negative once overdue
+    pub working_days_left: i64,
+}
+
+impl FilingDeadline {
+    pub fn new(detection_date: NaiveDate, today: NaiveDate) -> anyhow::Result<Self> {
+        Ok(Self::with_calendar(
+            holiday_calendar()?,
+            detection_date,
+            today,
+        ))
+    }
+
+    /// Deadline of a detection date written as `yyyy-mm-dd`, as stored in Section IV.
+    pub fn from_iso_date(detection_date: &str, today: NaiveDate) -> anyhow::Result<Self> {
+        let date = parse_calendar_date(detection_date)
+            .context("Ngày phát hiện giao dịch đáng ngờ không hợp lệ")?;
+        Self::new(date, today)
+    }
+
+    fn with_calendar(
+        calendar: &HolidayCalendar,
+        detection_date: NaiveDate,
+        today: NaiveDate,
+    ) -> Self {
+        if !calendar.covers_year(detection_date) || !calendar.covers_year(today) {
+            log::warn!(
+                "Lịch nghỉ lễ chưa có dữ liệu cho năm {}, hạn nộp báo cáo chỉ tính theo ngày nghỉ hằng tuần. Vui lòng cập nhật file {}",
+                detection_date.year().max(today.year()),
+                LOCAL_HOLIDAY_CALENDAR
+            );
+        }
+
+        let due_date = calendar.add_working_days(detection_date, calendar.filing_period);
+        FilingDeadline {
+            detection_date,
+            due_date,
+            working_days_left: calendar.working_days_between(today, due_date),
+        }
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        self.working_days_left < 0
+    }
+
+    pub fn status(&self) -> String {
+        match self.working_days_left {
+            0 => "đến hạn hôm nay".to_string(),
+            n if n > 0 => format!("còn {} ngày làm việc", n),
+            n => format!("quá hạn {} ngày làm việc", -n),
+        }
+    }
+}
+
+impl std::fmt::Display for FilingDeadline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "phát hiện ngày {}, hạn nộp ngày {} ({})",
+            self.detection_date.format("%d/%m/%Y"),
+            self.due_date.format("%d/%m/%Y"),
+            self.status()
+        )
+    }
+}
+
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Deadline of a new report, amendments and reports without Section IV have none.
+pub fn filing_deadline(form: &Form, today: NaiveDate) -> anyhow::Result<Option<FilingDeadline>> {
+    if form.payload.general_info.amendment.change_type != 0 {
+        return Ok(None);
+    }
+
+    let detection_date = form
+        .payload
+        .section_4
+        .as_ref()
+        .and_then(|section_4| section_4.detection_date.clone())
+        .filter(|date| !date.trim().is_empty());
+
+    detection_date
+        .map(|date| FilingDeadline::from_iso_date(&date, today))
+        .transpose()
+}
+
+/// Logs the filing deadline of the report. Overdue reports are rejected when `block_overdue` is
+/// set, unless a reason for the late submission is given.
+pub fn check_deadline(
+    form: &Form,
+    block_overdue: bool,
+    late_reason: Option<&str>,
+) -> anyhow::Result<Option<FilingDeadline>> {
+    let Some(deadline) = filing_deadline(form, today())? else {
+        return Ok(None);
+    };
+
+    if !deadline.is_overdue() {
+        log::info!("Báo cáo '{}': {}", form.internal_number, deadline);
+        return Ok(Some(deadline));
+    }
+
+    match (block_overdue, late_reason) {
+        (true, None) => Err(anyhow::anyhow!(
+            "Báo cáo '{}' đã quá hạn nộp: {}. Để vẫn nộp báo cáo, chạy lại với tham số --late-reason \"<lý do nộp muộn>\"",
+            form.internal_number,
+            deadline
+        )),
+        (_, Some(reason)) => {
+            log::warn!(
+                "Báo cáo '{}' đã quá hạn nộp: {}. Lý do nộp muộn: {}",
+                form.internal_number,
+                deadline,
+                reason
+            );
+            Ok(Some(deadline))
+        }
+        (false, None) => {
+            log::warn!(
+                "Báo cáo '{}' đã quá hạn nộp: {}",
+                form.internal_number,
+                deadline
+            );
+            Ok(Some(deadline))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingReport {
+    pub file_name: String,
+    pub internal_number: String,
+    pub change_type: i32,
+    pub deadline: Option<FilingDeadline>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_calendar_date(value).unwrap()
+    }
+
+    fn calendar() -> HolidayCalendar {
+        let holiday = |date: &str| Holiday {
+            date: date.to_string(),
+            name: "Ngày lễ".to_string(),
+        };
+        HolidayCalendar::try_from(CalendarFile {
+            filing_period: 3,
+            weekly_days_off: vec![6, 7],
+            holidays: vec![holiday("2024-04-30"), holiday("2024-05-01")],
+            working_weekends: vec!["2024-05-04".to_string()],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn working_days_skip_weekends_and_holidays() {
+        let calendar = calendar();
+        // Friday, the following Monday is the only working day before the holidays
+        assert_eq!(
+            calendar.add_working_days(date("2024-04-26"), 3),
+            date("2024-05-03")
+        );
+        // The Saturday worked in place of the holidays is counted
+        assert_eq!(
+            calendar.add_working_days(date("2024-05-02"), 3),
+            date("2024-05-06")
+        );
+        assert_eq!(
+            calendar.add_working_days(date("2024-05-02"), 0),
+            date("2024-05-02")
+        );
+    }
+
+    #[test]
+    fn working_days_between_is_signed() {
+        let calendar = calendar();
+        assert_eq!(
+            calendar.working_days_between(date("2024-04-26"), date("2024-05-03")),
+            3
+        );
+        assert_eq!(
+            calendar.working_days_between(date("2024-05-03"), date("2024-04-26")),
+            -3
+        );
+        assert_eq!(
+            calendar.working_days_between(date("2024-05-03"), date("2024-05-03")),
+            0
+        );
+    }
+
+    #[test]
+    fn deadline_is_overdue_after_the_filing_period() {
+        let calendar = calendar();
+        let deadline =
+            FilingDeadline::with_calendar(&calendar, date("2024-04-26"), date("2024-05-06"));
+        assert_eq!(deadline.due_date, date("2024-05-03"));
+        assert_eq!(deadline.working_days_left, -2);
+        assert!(deadline.is_overdue());
+
+        let deadline =
+            FilingDeadline::with_calendar(&calendar, date("2024-04-26"), date("2024-04-29"));
+        assert_eq!(deadline.working_days_left, 2);
+        assert!(!deadline.is_overdue());
+    }
+}
@@ -0,0 +1,542 @@
+pub mod money_flow;
+pub mod relationship;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::pdf::{Color, Page, PdfWriter, fit_text, text_width};
+
+const MARGIN: f32 = 30.0;
+const TITLE_SIZE: f32 = 14.0;
+const NODE_TITLE_SIZE: f32 = 9.0;
+const NODE_DETAIL_SIZE: f32 = 7.5;
+const EDGE_LABEL_SIZE: f32 = 7.0;
+const LINE_SPACING: f32 = 1.35;
+const NODE_PADDING: f32 = 7.0;
+const NODE_GAP: f32 = 22.0;
+const MIN_NODE_WIDTH: f32 = 130.0;
+const MAX_NODE_WIDTH: f32 = 240.0;
+const MIN_COLUMN_GAP: f32 = 90.0;
+const SAME_COLUMN_BEND: f32 = 50.0;
+const ARROW_SIZE: f32 = 6.0;
+
+pub const NODE_FILL: Color = Color(0.91, 0.95, 1.0);
+pub const HIGHLIGHT_FILL: Color = Color(1.0, 0.95, 0.78);
+const NODE_STROKE: Color = Color(0.35, 0.4, 0.5);
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub key: String,
+    /// Columns are laid out from left to right
+    pub column: usize,
+    pub title: String,
+    pub details: Vec<String>,
+    pub fill: Color,
+}
+
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub label: Vec<String>,
+    pub color: Color,
+}
+
+/// A directed graph drawn as boxes in columns with labelled arrows, written as SVG and PDF.
+#[derive(Debug, Clone, Default)]
+pub struct Diagram {
+    pub title: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    /// Additional keys under which a node can be found
+    aliases: Vec<(String, usize)>,
+}
+
+enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        fill: Color,
+        stroke: Option<Color>,
+    },
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        color: Color,
+        text: String,
+    },
+    Curve {
+        points: [(f32, f32); 4],
+        color: Color,
+    },
+    Polygon {
+        points: Vec<(f32, f32)>,
+        color: Color,
+    },
+}
+
+/// Shapes in a top-left based coordinate system, with the size of the drawing.
+struct Drawing {
+    width: f32,
+    height: f32,
+    shapes: Vec<Shape>,
+}
+
+fn line_height(size: f32) -> f32 {
+    size * LINE_SPACING
+}
+
+fn bezier_point(points: &[(f32, f32); 4], t: f32) -> (f32, f32) {
+    let u = 1.0 - t;
+    let coefficients = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    points
+        .iter()
+        .zip(coefficients)
+        .fold((0.0, 0.0), |(x, y), (point, c)| {
+            (x + point.0 * c, y + point.1 * c)
+        })
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Diagram {
+    pub fn new(title: impl Into<String>) -> Self {
+        Diagram {
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Index of the node with `key`, the node is added when it does not exist yet.
+    pub fn add_node(
+        &mut self,
+        key: impl Into<String>,
+        column: usize,
+        title: impl Into<String>,
+        details: Vec<String>,
+        fill: Color,
+    ) -> usize {
+        let key = key.into();
+        if let Some(idx) = self.find_node(&key) {
+            return idx;
+        }
+
+        self.nodes.push(Node {
+            key,
+            column,
+            title: title.into(),
+            details: details.into_iter().filter(|d| !d.is_empty()).collect(),
+            fill,
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn find_node(&self, key: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.key == key)
+            .or_else(|| {
+                self.aliases
+                    .iter()
+                    .find(|(alias, _)| alias == key)
+                    .map(|(_, idx)| *idx)
+            })
+    }
+
+    pub fn add_alias(&mut self, node: usize, key: impl Into<String>) {
+        self.aliases.push((key.into(), node));
+    }
+
+    /// Adds an arrow from `from` to `to`, an identical arrow is only drawn once.
+    pub fn add_edge(&mut self, from: usize, to: usize, label: Vec<String>, color: Color) {
+        let label = label
+            .into_iter()
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>();
+        if self
+            .edges
+            .iter()
+            .any(|edge| edge.from == from && edge.to == to && edge.label == label)
+        {
+            return;
+        }
+
+        self.edges.push(Edge {
+            from,
+            to,
+            label,
+            color,
+        });
+    }
+
+    fn node_lines(node: &Node) -> Vec<(String, f32)> {
+        std::iter::once((node.title.clone(), NODE_TITLE_SIZE))
+            .chain(node.details.iter().map(|d| (d.clone(), NODE_DETAIL_SIZE)))
+            .map(|(text, size)| {
+                let text = fit_text(&text, size, MAX_NODE_WIDTH - 2.0 * NODE_PADDING);
+                (text, size)
+            })
+            .collect()
+    }
+
+    fn layout(&self) -> Drawing {
+        // Columns without any node are skipped
+        let mut used_columns = self.nodes.iter().map(|n| n.column).collect::<Vec<_>>();
+        used_columns.sort();
+        used_columns.dedup();
+        let column_of = |node: &Node| {
+            used_columns
+                .iter()
+                .position(|c| *c == node.column)
+                .unwrap_or_default()
+        };
+        let column_count = used_columns.len();
+        let node_lines = self.nodes.iter().map(Self::node_lines).collect::<Vec<_>>();
+        let node_heights = node_lines
+            .iter()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .map(|(_, size)| line_height(*size))
+                    .sum::<f32>()
+                    + 2.0 * NODE_PADDING
+            })
+            .collect::<Vec<_>>();
+
+        let mut column_widths = vec![MIN_NODE_WIDTH; column_count];
+        for (node, lines) in self.nodes.iter().zip(node_lines.iter()) {
+            let width = lines
+                .iter()
+                .map(|(text, size)| text_width(text, *size))
+                .fold(0.0, f32::max)
+                + 2.0 * NODE_PADDING;
+            column_widths[column_of(node)] = column_widths[column_of(node)].max(width);
+        }
+
+        let label_width = |edge: &Edge| {
+            edge.label
+                .iter()
+                .map(|line| text_width(line, EDGE_LABEL_SIZE))
+                .fold(0.0, f32::max)
+        };
+        let column_gap = self
+            .edges
+            .iter()
+            .map(|edge| label_width(edge) + 40.0)
+            .fold(MIN_COLUMN_GAP, f32::max);
+        let same_column_room = match self
+            .edges
+            .iter()
+            .any(|edge| self.nodes[edge.from].column == self.nodes[edge.to].column)
+        {
+            true => SAME_COLUMN_BEND,
+            false => 0.0,
+        };
+
+        let mut column_x = vec![];
+        let mut x = MARGIN;
+        for width in column_widths.iter() {
+            column_x.push(x);
+            x += width + column_gap;
+        }
+        let width = (x - column_gap + MARGIN + same_column_room)
+            .max(text_width(&self.title, TITLE_SIZE) + 2.0 * MARGIN);
+
+        let column_heights = (0..column_count)
+            .map(|column| {
+                let heights = self
+                    .nodes
+                    .iter()
+                    .zip(node_heights.iter())
+                    .filter(|(node, _)| column_of(node) == column)
+                    .map(|(_, height)| *height)
+                    .collect::<Vec<_>>();
+                heights.iter().sum::<f32>() + NODE_GAP * heights.len().saturating_sub(1) as f32
+            })
+            .collect::<Vec<_>>();
+        let content_height = column_heights.iter().cloned().fold(0.0, f32::max);
+        let top = MARGIN + TITLE_SIZE * 2.0;
+        let height = top + content_height + MARGIN;
+
+        // Boxes of each node: x, y, width, height
+        let mut boxes = vec![(0.0, 0.0, 0.0, 0.0); self.nodes.len()];
+        let mut column_y = column_heights
+            .iter()
+            .map(|h| top + (content_height - h) / 2.0)
+            .collect::<Vec<_>>();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let column = column_of(node);
+            boxes[idx] = (
+                column_x[column],
+                column_y[column],
+                column_widths[column],
+                node_heights[idx],
+            );
+            column_y[column] += node_heights[idx] + NODE_GAP;
+        }
+
+        let mut shapes = vec![Shape::Text {
+            x: MARGIN,
+            y: MARGIN + TITLE_SIZE,
+            size: TITLE_SIZE,
+            color: Color::BLACK,
+            text: self.title.clone(),
+        }];
+
+        let mut labels = vec![];
+        for edge in self.edges.iter() {
+            let (fx, fy, fw, fh) = boxes[edge.from];
+            let (tx, ty, tw, th) = boxes[edge.to];
+            let (from_column, to_column) =
+                (self.nodes[edge.from].column, self.nodes[edge.to].column);
+
+            let points = match from_column.cmp(&to_column) {
+                std::cmp::Ordering::Less => {
+                    let (start, end) = ((fx + fw, fy + fh / 2.0), (tx, ty + th / 2.0));
+                    let bend = (end.0 - start.0) / 2.0;
+                    [start, (start.0 + bend, start.1), (end.0 - bend, end.1), end]
+                }
+                std::cmp::Ordering::Greater => {
+                    let (start, end) = ((fx, fy + fh / 2.0), (tx + tw, ty + th / 2.0));
+                    let bend = (start.0 - end.0) / 2.0;
+                    [start, (start.0 - bend, start.1), (end.0 + bend, end.1), end]
+                }
+                std::cmp::Ordering::Equal => {
+                    let (start, end) = ((fx + fw, fy + fh / 2.0), (tx + tw, ty + th / 2.0));
+                    [
+                        start,
+                        (start.0 + SAME_COLUMN_BEND, start.1),
+                        (end.0 + SAME_COLUMN_BEND, end.1),
+                        end,
+                    ]
+                }
+            };
+
+            let (end, control) = (points[3], points[2]);
+            let (dx, dy) = (end.0 - control.0, end.1 - control.1);
+            let length = (dx * dx + dy * dy).sqrt().max(0.001);
+            let (ux, uy) = (dx / length, dy / length);
+            let base = (end.0 - ux * ARROW_SIZE, end.1 - uy * ARROW_SIZE);
+            let normal = (-uy * ARROW_SIZE / 2.0, ux * ARROW_SIZE / 2.0);
+
+            shapes.push(Shape::Curve {
+                points,
+                color: edge.color,
+            });
+            shapes.push(Shape::Polygon {
+                points: vec![
+                    end,
+                    (base.0 + normal.0, base.1 + normal.1),
+                    (base.0 - normal.0, base.1 - normal.1),
+                ],
+                color: edge.color,
+            });
+
+            if !edge.label.is_empty() {
+                let (cx, cy) = bezier_point(&points, 0.5);
+                let label_height = line_height(EDGE_LABEL_SIZE) * edge.label.len() as f32;
+                let label_width = label_width(edge);
+                let (lx, ly) = (cx - label_width / 2.0, cy - label_height / 2.0);
+                labels.push(Shape::Rect {
+                    x: lx - 2.0,
+                    y: ly - 1.0,
+                    width: label_width + 4.0,
+                    height: label_height + 2.0,
+                    fill: Color::WHITE,
+                    stroke: None,
+                });
+                for (idx, line) in edge.label.iter().enumerate() {
+                    labels.push(Shape::Text {
+                        x: cx - text_width(line, EDGE_LABEL_SIZE) / 2.0,
+                        y: ly + line_height(EDGE_LABEL_SIZE) * (idx as f32 + 0.8),
+                        size: EDGE_LABEL_SIZE,
+                        color: edge.color,
+                        text: line.clone(),
+                    });
+                }
+            }
+        }
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let (x, y, width, height) = boxes[idx];
+            shapes.push(Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill: node.fill,
+                stroke: Some(NODE_STROKE),
+            });
+            let mut line_y = y + NODE_PADDING;
+            for (text, size) in node_lines[idx].iter() {
+                line_y += line_height(*size);
+                shapes.push(Shape::Text {
+                    x: x + NODE_PADDING,
+                    y: line_y - size * (LINE_SPACING - 1.0) - 1.0,
+                    size: *size,
+                    color: Color::BLACK,
+                    text: text.clone(),
+                });
+            }
+        }
+
+        // Labels are drawn last so that arrows never hide them
+        shapes.extend(labels);
+
+        Drawing {
+            width,
+            height,
+            shapes,
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let drawing = self.layout();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.1} {h:.1}\" font-family=\"Arial, 'DejaVu Sans', sans-serif\">\n<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n",
+            w = drawing.width,
+            h = drawing.height
+        );
+
+        for shape in drawing.shapes.iter() {
+            let element = match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill,
+                    stroke,
+                } => format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"3\" fill=\"{}\" stroke=\"{}\"/>",
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill.to_hex(),
+                    stroke.map(|s| s.to_hex()).unwrap_or("none".to_string())
+                ),
+                Shape::Text {
+                    x,
+                    y,
+                    size,
+                    color,
+                    text,
+                } => format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" fill=\"{}\">{}</text>",
+                    x,
+                    y,
+                    size,
+                    color.to_hex(),
+                    escape_xml(text)
+                ),
+                Shape::Curve { points, color } => format!(
+                    "<path d=\"M {:.1} {:.1} C {:.1} {:.1}, {:.1} {:.1}, {:.1} {:.1}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.2\"/>",
+                    points[0].0,
+                    points[0].1,
+                    points[1].0,
+                    points[1].1,
+                    points[2].0,
+                    points[2].1,
+                    points[3].0,
+                    points[3].1,
+                    color.to_hex()
+                ),
+                Shape::Polygon { points, color } => format!(
+                    "<polygon points=\"{}\" fill=\"{}\"/>",
+                    points
+                        .iter()
+                        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    color.to_hex()
+                ),
+            };
+            svg.push_str(&element);
+            svg.push('\n');
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// A single landscape A4 page, the drawing is scaled down when it does not fit.
+    pub fn to_pdf(&self) -> anyhow::Result<Vec<u8>> {
+        let drawing = self.layout();
+        let mut writer = PdfWriter::new();
+        let mut page = Page::a4_landscape();
+
+        let scale = (page.width / drawing.width)
+            .min(page.height / drawing.height)
+            .min(1.0);
+        let offset_x = (page.width - drawing.width * scale) / 2.0;
+        let page_height = page.height;
+        let point = |(x, y): (f32, f32)| (offset_x + x * scale, page_height - y * scale);
+
+        for shape in drawing.shapes.iter() {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill,
+                    stroke,
+                } => {
+                    let (px, py) = point((*x, y + height));
+                    page.rect(px, py, width * scale, height * scale, Some(*fill), *stroke);
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    size,
+                    color,
+                    text,
+                } => {
+                    let (px, py) = point((*x, *y));
+                    writer.text(&mut page, px, py, size * scale, *color, text);
+                }
+                Shape::Curve { points, color } => {
+                    let [p0, p1, p2, p3] = points.map(point);
+                    page.curve(p0, p1, p2, p3, 1.2 * scale, *color);
+                }
+                Shape::Polygon { points, color } => {
+                    let points = points.iter().map(|p| point(*p)).collect::<Vec<_>>();
+                    page.path(&points, true, Some(*color), None);
+                }
+            }
+        }
+
+        writer.add_page(page)?;
+        writer.finish()
+    }
+
+    /// Writes `<stem>.svg` and `<stem>.pdf` to `folder`, returning the path of the PDF.
+    pub fn save(&self, folder: &Path, stem: &str) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(folder)
+            .with_context(|| format!("Không thể tạo folder {:#?}", folder))?;
+
+        let svg_path = folder.join(format!("{}.svg", stem));
+        std::fs::write(&svg_path, self.to_svg())
+            .with_context(|| format!("Không thể ghi file {:#?}", svg_path))?;
+
+        let pdf_path = folder.join(format!("{}.pdf", stem));
+        std::fs::write(&pdf_path, self.to_pdf()?)
+            .with_context(|| format!("Không thể ghi file {:#?}", pdf_path))?;
+
+        Ok(pdf_path)
+    }
+}
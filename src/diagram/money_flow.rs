@@ -0,0 +1,266 @@
+use crate::{
+    diagram::{Diagram, HIGHLIGHT_FILL, NODE_FILL},
+    payload::{
+        code::Currency,
+        section4::{FlowEntryIn, FlowEntryOut, MoneyFlow, TransactionInfo},
+    },
+    pdf::Color,
+    reconcile::{parse_amount, parse_count, parse_date},
+    utils::number::format_amount,
+};
+
+const INFLOW_COLOR: Color = Color(0.1, 0.5, 0.25);
+const OUTFLOW_COLOR: Color = Color(0.75, 0.2, 0.15);
+
+const SOURCE_COLUMN: usize = 0;
+const SUBJECT_COLUMN: usize = 1;
+const DESTINATION_COLUMN: usize = 2;
+
+/// A counterparty or a subject account, as written on the flow rows.
+struct Party<'a> {
+    name: Option<&'a String>,
+    identification: Option<&'a String>,
+    account: Option<&'a String>,
+    bank_name: Option<&'a String>,
+    bank_code: Option<&'a String>,
+}
+
+/// Totals of one inflow or outflow row.
+struct FlowLabel<'a> {
+    total_amount: Option<&'a String>,
+    total_converted: Option<&'a String>,
+    total_transactions: Option<&'a String>,
+    tx_from: Option<&'a String>,
+    tx_to: Option<&'a String>,
+    currency: Option<&'a Currency>,
+}
+
+fn non_empty(value: Option<&String>) -> Option<&String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+impl Party<'_> {
+    fn key(&self) -> String {
+        match (non_empty(self.account), non_empty(self.identification)) {
+            (Some(account), _) => format!("tk:{}", account.trim()),
+            (None, Some(identification)) => format!("dd:{}", identification.trim()),
+            (None, None) => format!(
+                "ten:{}",
+                self.name
+                    .map(|n| n.trim().to_lowercase())
+                    .unwrap_or_default()
+            ),
+        }
+    }
+
+    fn title(&self) -> String {
+        non_empty(self.name)
+            .map(|name| name.trim().to_string())
+            .unwrap_or_else(|| "(Không rõ tên)".to_string())
+    }
+
+    fn details(&self) -> Vec<String> {
+        let bank = non_empty(self.bank_name).or(non_empty(self.bank_code));
+        vec![
+            non_empty(self.identification)
+                .map(|id| format!("Số định danh: {}", id))
+                .unwrap_or_default(),
+            non_empty(self.account)
+                .map(|account| format!("Tài khoản: {}", account))
+                .unwrap_or_default(),
+            bank.map(|bank| format!("Ngân hàng: {}", bank))
+                .unwrap_or_default(),
+        ]
+    }
+}
+
+fn format_date(value: Option<&String>) -> Option<String> {
+    parse_date(value).map(|date| date.format("%d/%m/%Y").to_string())
+}
+
+impl FlowLabel<'_> {
+    fn lines(&self) -> Vec<String> {
+        let currency = self.currency.map(|c| c.to_string()).unwrap_or_default();
+        let amount = parse_amount(self.total_amount)
+            .map(|amount| {
+                format!("{} {}", format_amount(amount), currency)
+                    .trim()
+                    .to_string()
+            })
+            .unwrap_or_default();
+        let converted = match (currency.as_str(), parse_amount(self.total_converted)) {
+            ("VND", _) | (_, None) => String::new(),
+            (_, Some(converted)) => format!("≈ {} VND", format_amount(converted)),
+        };
+        let count = parse_count(self.total_transactions)
+            .map(|count| format!("{} giao dịch", count))
+            .unwrap_or_default();
+        let period = match (format_date(self.tx_from), format_date(self.tx_to)) {
+            (Some(from), Some(to)) if from == to => from,
+            (Some(from), Some(to)) => format!("{} - {}", from, to),
+            (Some(date), None) | (None, Some(date)) => date,
+            (None, None) => String::new(),
+        };
+
+        vec![amount, converted, count, period]
+    }
+}
+
+fn subject_party(flow: &MoneyFlow) -> Party<'_> {
+    Party {
+        name: flow.subject_name.as_ref(),
+        identification: flow.identification.as_ref(),
+        account: flow.account_number.as_ref(),
+        bank_name: flow.bank_name.as_ref(),
+        bank_code: flow.bank_code.as_ref(),
+    }
+}
+
+fn source_party(entry: &FlowEntryIn) -> Party<'_> {
+    Party {
+        name: entry.source_name.as_ref(),
+        identification: entry.source_id.as_ref(),
+        account: entry.source_account.as_ref(),
+        bank_name: entry.source_bank_name.as_ref(),
+        bank_code: entry.source_bank_code.as_ref(),
+    }
+}
+
+fn destination_party(entry: &FlowEntryOut) -> Party<'_> {
+    Party {
+        name: entry.dest_name.as_ref(),
+        identification: entry.dest_id.as_ref(),
+        account: entry.dest_account.as_ref(),
+        bank_name: entry.dest_bank_name.as_ref(),
+        bank_code: entry.dest_bank_code.as_ref(),
+    }
+}
+
+fn subject_totals(flow: &MoneyFlow) -> Vec<String> {
+    let total = |amount: Option<&String>, count: Option<&String>| {
+        let amount = parse_amount(amount)
+            .map(|amount| format!("{} VND", format_amount(amount)))
+            .unwrap_or_else(|| "0 VND".to_string());
+        match parse_count(count) {
+            Some(count) => format!("{} ({} giao dịch)", amount, count),
+            None => amount,
+        }
+    };
+
+    vec![
+        format!(
+            "Tổng vào: {}",
+            total(
+                flow.total_converted_in.as_ref(),
+                flow.total_transactions_in.as_ref()
+            )
+        ),
+        format!(
+            "Tổng ra: {}",
+            total(
+                flow.total_converted_out.as_ref(),
+                flow.total_transactions_out.as_ref()
+            )
+        ),
+    ]
+}
+
+/// Money flow illustration of Section IV: sources on the left, the accounts of the reported
+/// subjects in the middle and destinations on the right. A counterparty that is itself one of the
+/// reported accounts is drawn once, in the middle column.
+pub fn money_flow_diagram(info: &TransactionInfo) -> Option<Diagram> {
+    let flows = info.money_flows.iter().flatten().collect::<Vec<_>>();
+
+    let time_range = info.time_range.clone().unwrap_or_default();
+    let title = match (
+        format_date(time_range.from.as_ref()),
+        format_date(time_range.to.as_ref()),
+    ) {
+        (Some(from), Some(to)) => format!("Minh họa dòng tiền từ ngày {} đến ngày {}", from, to),
+        _ => "Minh họa dòng tiền".to_string(),
+    };
+    let mut diagram = Diagram::new(title);
+
+    for flow in flows.iter() {
+        let subject = subject_party(flow);
+        let mut details = subject.details();
+        details.extend(subject_totals(flow));
+        diagram.add_node(
+            subject.key(),
+            SUBJECT_COLUMN,
+            subject.title(),
+            details,
+            HIGHLIGHT_FILL,
+        );
+    }
+
+    for flow in flows.iter() {
+        let subject_key = subject_party(flow).key();
+        let subject = diagram.find_node(&subject_key).unwrap_or_default();
+
+        for entry in flow.inflows.iter().flatten() {
+            let source = source_party(entry);
+            let label = FlowLabel {
+                total_amount: entry.total_amount.as_ref(),
+                total_converted: entry.total_converted.as_ref(),
+                total_transactions: entry.total_transactions.as_ref(),
+                tx_from: entry.tx_from.as_ref(),
+                tx_to: entry.tx_to.as_ref(),
+                currency: entry.currency.as_ref(),
+            };
+
+            // A transfer between reported accounts is drawn once, from the outflow of the sender
+            let drawn_as_outflow = flows.iter().any(|other| {
+                subject_party(other).key() == source.key()
+                    && other
+                        .outflows
+                        .iter()
+                        .flatten()
+                        .any(|out| destination_party(out).key() == subject_key)
+            });
+            if drawn_as_outflow {
+                continue;
+            }
+            let source_node = match diagram.find_node(&source.key()) {
+                Some(idx) if diagram.nodes[idx].column == SUBJECT_COLUMN => idx,
+                _ => diagram.add_node(
+                    format!("vào:{}", source.key()),
+                    SOURCE_COLUMN,
+                    source.title(),
+                    source.details(),
+                    NODE_FILL,
+                ),
+            };
+            diagram.add_edge(source_node, subject, label.lines(), INFLOW_COLOR);
+        }
+
+        for entry in flow.outflows.iter().flatten() {
+            let destination = destination_party(entry);
+            let label = FlowLabel {
+                total_amount: entry.total_amount.as_ref(),
+                total_converted: entry.total_converted.as_ref(),
+                total_transactions: entry.total_transactions.as_ref(),
+                tx_from: entry.tx_from.as_ref(),
+                tx_to: entry.tx_to.as_ref(),
+                currency: entry.currency.as_ref(),
+            };
+
+            let destination_node = match diagram.find_node(&destination.key()) {
+                Some(idx) if diagram.nodes[idx].column == SUBJECT_COLUMN => idx,
+                _ => diagram.add_node(
+                    format!("ra:{}", destination.key()),
+                    DESTINATION_COLUMN,
+                    destination.title(),
+                    destination.details(),
+                    NODE_FILL,
+                ),
+            };
+            diagram.add_edge(subject, destination_node, label.lines(), OUTFLOW_COLOR);
+        }
+    }
+
+    match diagram.is_empty() {
+        true => None,
+        false => Some(diagram),
+    }
+}
@@ -0,0 +1,474 @@
+use crate::{
+    diagram::{Diagram, HIGHLIGHT_FILL, NODE_FILL},
+    entity_graph::normalize_id,
+    payload::{
+        entities::{Account, Identification, Individual, Organization, PersonRef},
+        section2::Section2,
+        section3::Section3,
+        section4::TransactionInfo,
+    },
+    pdf::Color,
+};
+
+const OWNER_COLOR: Color = Color(0.45, 0.25, 0.6);
+const REPRESENTATIVE_COLOR: Color = Color(0.15, 0.35, 0.7);
+const HOLDER_COLOR: Color = Color(0.3, 0.3, 0.3);
+const AUTHORIZED_COLOR: Color = Color(0.5, 0.5, 0.5);
+const COUNTERPARTY_COLOR: Color = Color(0.85, 0.45, 0.1);
+
+const RELATED_FILL: Color = Color(0.93, 0.93, 0.93);
+
+const OWNER_COLUMN: usize = 0;
+const CUSTOMER_COLUMN: usize = 1;
+const ACCOUNT_COLUMN: usize = 2;
+const RELATED_COLUMN: usize = 3;
+
+fn non_blank(value: Option<&String>) -> Option<&str> {
+    value.map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+fn first_id_number(identifications: &Option<Vec<Identification>>) -> Option<&String> {
+    identifications
+        .iter()
+        .flatten()
+        .find_map(|id| id.id_number.as_ref().filter(|n| !n.trim().is_empty()))
+}
+
+/// Individuals are identified by their ID number, then their CIF, then their name.
+fn person_key(id_number: Option<&String>, id: Option<&String>, name: Option<&String>) -> String {
+    match (non_blank(id_number), non_blank(id)) {
+        (Some(id_number), _) => format!("cn:{}", normalize_id(id_number)),
+        (None, Some(id)) => format!("cn:cif:{}", normalize_id(id)),
+        (None, None) => format!(
+            "cn:ten:{}",
+            non_blank(name).unwrap_or_default().to_lowercase()
+        ),
+    }
+}
+
+fn organization_code(org: &Organization) -> Option<&String> {
+    org.enterprise_code
+        .as_ref()
+        .and_then(|ec| ec.code.as_ref())
+        .filter(|code| !code.trim().is_empty())
+}
+
+fn organization_key(org: &Organization) -> String {
+    match (organization_code(org), non_blank(org.id.as_ref())) {
+        (Some(code), _) => format!("tc:{}", normalize_id(code)),
+        (None, Some(id)) => format!("tc:cif:{}", normalize_id(id)),
+        (None, None) => format!(
+            "tc:ten:{}",
+            non_blank(org.name.as_ref())
+                .unwrap_or_default()
+                .to_lowercase()
+        ),
+    }
+}
+
+fn account_key(account_number: &str) -> String {
+    format!("tk:{}", normalize_id(account_number))
+}
+
+fn name_or_unknown(name: Option<&String>) -> String {
+    non_blank(name)
+        .map(str::to_string)
+        .unwrap_or_else(|| "(Không rõ tên)".to_string())
+}
+
+fn individual_details(person: &Individual) -> Vec<String> {
+    vec![
+        first_id_number(&person.identifications)
+            .map(|id| format!("Số định danh: {}", id))
+            .unwrap_or_default(),
+        non_blank(person.id.as_ref())
+            .map(|cif| format!("CIF: {}", cif))
+            .unwrap_or_default(),
+        non_blank(person.position.as_ref())
+            .map(|position| format!("Chức vụ: {}", position))
+            .unwrap_or_default(),
+    ]
+}
+
+fn organization_details(org: &Organization) -> Vec<String> {
+    vec![
+        organization_code(org)
+            .map(|code| format!("MST: {}", code))
+            .unwrap_or_default(),
+        non_blank(org.id.as_ref())
+            .map(|cif| format!("CIF: {}", cif))
+            .unwrap_or_default(),
+    ]
+}
+
+fn add_individual(diagram: &mut Diagram, person: &Individual, column: usize, fill: Color) -> usize {
+    diagram.add_node(
+        person_key(
+            first_id_number(&person.identifications),
+            person.id.as_ref(),
+            person.full_name.as_ref(),
+        ),
+        column,
+        name_or_unknown(person.full_name.as_ref()),
+        individual_details(person),
+        fill,
+    )
+}
+
+fn add_organization(
+    diagram: &mut Diagram,
+    org: &Organization,
+    column: usize,
+    fill: Color,
+) -> usize {
+    diagram.add_node(
+        organization_key(org),
+        column,
+        name_or_unknown(org.name.as_ref()),
+        organization_details(org),
+        fill,
+    )
+}
+
+fn add_person_ref(diagram: &mut Diagram, person: &PersonRef, column: usize) -> usize {
+    diagram.add_node(
+        person_key(
+            person.id_number.as_ref(),
+            person.id.as_ref(),
+            person.full_name.as_ref(),
+        ),
+        column,
+        name_or_unknown(person.full_name.as_ref()),
+        vec![
+            person
+                .id_number
+                .as_ref()
+                .map(|id| format!("Số định danh: {}", id))
+                .unwrap_or_default(),
+        ],
+        NODE_FILL,
+    )
+}
+
+/// Accounts of a customer with their authorized persons.
+fn add_accounts(diagram: &mut Diagram, owner: usize, accounts: &Option<Vec<Account>>) {
+    for account in accounts.iter().flatten() {
+        let Some(account_number) = non_blank(account.account_number.as_ref()) else {
+            continue;
+        };
+        let bank = account.bank.as_ref().and_then(|bank| {
+            non_blank(bank.bank_name.as_ref()).or(non_blank(bank.bank_code.as_ref()))
+        });
+        let node = diagram.add_node(
+            account_key(account_number),
+            ACCOUNT_COLUMN,
+            format!("Tài khoản {}", account_number),
+            vec![
+                bank.map(|bank| format!("Ngân hàng: {}", bank))
+                    .unwrap_or_default(),
+                account
+                    .currency_type
+                    .as_ref()
+                    .map(|currency| format!("Loại tiền: {}", currency))
+                    .unwrap_or_default(),
+            ],
+            NODE_FILL,
+        );
+        diagram.add_edge(owner, node, vec!["Chủ tài khoản".to_string()], HOLDER_COLOR);
+
+        for person in account.authorized_persons.iter().flatten() {
+            let person_node = add_person_ref(diagram, person, RELATED_COLUMN);
+            diagram.add_edge(
+                person_node,
+                node,
+                vec!["Được ủy quyền".to_string()],
+                AUTHORIZED_COLOR,
+            );
+        }
+    }
+}
+
+fn ownership_label(person: Option<&Individual>) -> Vec<String> {
+    let ownership = person.and_then(|p| p.ownership.as_ref());
+    vec![
+        "Chủ sở hữu hưởng lợi".to_string(),
+        ownership
+            .and_then(|o| o.percentage)
+            .map(|p| format!("Tỷ lệ sở hữu {}%", p))
+            .unwrap_or_default(),
+        ownership
+            .and_then(|o| non_blank(o.through.as_ref()))
+            .map(|through| format!("Thông qua MST {}", through))
+            .unwrap_or_default(),
+    ]
+}
+
+/// Links of the related parties of Section III to the reported accounts they sent money to or
+/// received money from.
+fn add_counterparties(diagram: &mut Diagram, info: &TransactionInfo) {
+    let related_node = |diagram: &Diagram,
+                        account: Option<&String>,
+                        id_number: Option<&String>,
+                        name: Option<&String>| {
+        [
+            non_blank(account).and_then(|a| diagram.find_node(&format!("lq:{}", account_key(a)))),
+            non_blank(id_number).and_then(|n| {
+                diagram
+                    .find_node(&format!("cn:{}", normalize_id(n)))
+                    .or_else(|| diagram.find_node(&format!("tc:{}", normalize_id(n))))
+            }),
+            non_blank(name)
+                .and_then(|n| diagram.find_node(&format!("cn:ten:{}", n.to_lowercase()))),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|idx| diagram.nodes[*idx].column == RELATED_COLUMN)
+    };
+
+    for flow in info.money_flows.iter().flatten() {
+        let Some(account) = non_blank(flow.account_number.as_ref())
+            .and_then(|account| diagram.find_node(&account_key(account)))
+        else {
+            continue;
+        };
+
+        for entry in flow.inflows.iter().flatten() {
+            if let Some(related) = related_node(
+                diagram,
+                entry.source_account.as_ref(),
+                entry.source_id.as_ref(),
+                entry.source_name.as_ref(),
+            ) {
+                diagram.add_edge(
+                    related,
+                    account,
+                    vec!["Đối tác giao dịch".to_string()],
+                    COUNTERPARTY_COLOR,
+                );
+            }
+        }
+        for entry in flow.outflows.iter().flatten() {
+            if let Some(related) = related_node(
+                diagram,
+                entry.dest_account.as_ref(),
+                entry.dest_id.as_ref(),
+                entry.dest_name.as_ref(),
+            ) {
+                diagram.add_edge(
+                    account,
+                    related,
+                    vec!["Đối tác giao dịch".to_string()],
+                    COUNTERPARTY_COLOR,
+                );
+            }
+        }
+    }
+}
+
+/// Relationship illustration of Sections II and III: owners and representatives on the left,
+/// the customers, their accounts, then authorized persons and related parties on the right.
+/// Counterparty links are taken from the Section IV money flows when given.
+pub fn relationship_diagram(
+    section_2: Option<&Section2>,
+    section_3: Option<&Section3>,
+    transaction_info: Option<&TransactionInfo>,
+) -> Option<Diagram> {
+    let mut diagram = Diagram::new("Minh họa mối quan hệ khách hàng");
+
+    let individuals = section_2
+        .iter()
+        .flat_map(|s| s.individuals.iter().flatten())
+        .collect::<Vec<_>>();
+    let organizations = section_2
+        .iter()
+        .flat_map(|s| s.organizations.iter().flatten())
+        .collect::<Vec<_>>();
+    let related_individuals = section_3
+        .iter()
+        .flat_map(|s| s.related_individuals.iter().flatten())
+        .collect::<Vec<_>>();
+    let related_organizations = section_3
+        .iter()
+        .flat_map(|s| s.related_organizations.iter().flatten())
+        .collect::<Vec<_>>();
+    let beneficial_owners = section_2.and_then(|s| s.beneficial_owners.as_ref());
+    let other_owners = beneficial_owners
+        .iter()
+        .flat_map(|owners| owners.other_owners.iter().flatten())
+        .collect::<Vec<_>>();
+
+    // Customers first, so that a customer that is also an owner or a related party is drawn once
+    for person in individuals.iter() {
+        let node = add_individual(&mut diagram, person, CUSTOMER_COLUMN, HIGHLIGHT_FILL);
+        add_accounts(&mut diagram, node, &person.accounts);
+    }
+    for org in organizations.iter() {
+        let node = add_organization(&mut diagram, org, CUSTOMER_COLUMN, HIGHLIGHT_FILL);
+        add_accounts(&mut diagram, node, &org.accounts);
+
+        for rep in org.representatives.iter().flatten() {
+            let rep_node = diagram.add_node(
+                person_key(
+                    first_id_number(&rep.identifications),
+                    rep.id.as_ref(),
+                    rep.full_name.as_ref(),
+                ),
+                OWNER_COLUMN,
+                name_or_unknown(rep.full_name.as_ref()),
+                vec![
+                    first_id_number(&rep.identifications)
+                        .map(|id| format!("Số định danh: {}", id))
+                        .unwrap_or_default(),
+                ],
+                NODE_FILL,
+            );
+            let position = non_blank(rep.position.as_ref())
+                .map(str::to_string)
+                .unwrap_or_default();
+            diagram.add_edge(
+                rep_node,
+                node,
+                vec!["Người đại diện".to_string(), position],
+                REPRESENTATIVE_COLOR,
+            );
+        }
+
+        if let Some(owner) = org.corporate_owner.as_ref() {
+            let parent = organizations
+                .iter()
+                .chain(related_organizations.iter())
+                .find(|other| {
+                    organization_code(other).is_some_and(|code| {
+                        normalize_id(code) == normalize_id(&owner.enterprise_code)
+                    })
+                });
+            let parent_node = match parent {
+                Some(parent) => add_organization(&mut diagram, parent, OWNER_COLUMN, NODE_FILL),
+                None => diagram.add_node(
+                    format!("tc:{}", normalize_id(&owner.enterprise_code)),
+                    OWNER_COLUMN,
+                    format!("Tổ chức MST {}", owner.enterprise_code),
+                    vec![],
+                    NODE_FILL,
+                ),
+            };
+            diagram.add_edge(
+                parent_node,
+                node,
+                vec![
+                    "Chủ sở hữu".to_string(),
+                    owner
+                        .percentage
+                        .map(|p| format!("Tỷ lệ sở hữu {}%", p))
+                        .unwrap_or_default(),
+                ],
+                OWNER_COLOR,
+            );
+        }
+    }
+
+    let links = beneficial_owners
+        .iter()
+        .flat_map(|owners| {
+            let individual_links = owners.individual_links.iter().flatten().map(|link| {
+                (
+                    person_key(
+                        link.id_number.as_ref(),
+                        link.id.as_ref(),
+                        link.name.as_ref(),
+                    ),
+                    link.benefit_group.as_ref(),
+                )
+            });
+            let organization_links = owners.organization_links.iter().flatten().map(|link| {
+                let same_cif = |org: &&&Organization| {
+                    non_blank(org.id.as_ref()).is_some() && org.id == link.id
+                };
+                let same_code = |org: &&&Organization| {
+                    organization_code(org)
+                        .zip(non_blank(link.id_number.as_ref()))
+                        .is_some_and(|(code, id_number)| {
+                            normalize_id(code) == normalize_id(id_number)
+                        })
+                };
+                let key = organizations
+                    .iter()
+                    .find(|org| same_cif(org) || same_code(org))
+                    .map(|org| organization_key(org))
+                    .unwrap_or_default();
+                (key, link.benefit_group.as_ref())
+            });
+            individual_links
+                .chain(organization_links)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    for (customer_key, group) in links {
+        let (Some(customer), Some(group)) = (diagram.find_node(&customer_key), group) else {
+            continue;
+        };
+
+        for person in group.main_group.iter().flatten() {
+            let person_node = add_person_ref(&mut diagram, person, OWNER_COLUMN);
+            diagram.add_edge(
+                person_node,
+                customer,
+                vec!["Người đại diện".to_string()],
+                REPRESENTATIVE_COLOR,
+            );
+        }
+        for person in group.other_group.iter().flatten() {
+            let key = person_key(
+                person.id_number.as_ref(),
+                person.id.as_ref(),
+                person.full_name.as_ref(),
+            );
+            let owner = other_owners.iter().copied().find(|owner| {
+                person_key(
+                    first_id_number(&owner.identifications),
+                    owner.id.as_ref(),
+                    owner.full_name.as_ref(),
+                ) == key
+            });
+            let person_node = match owner {
+                Some(owner) => add_individual(&mut diagram, owner, OWNER_COLUMN, NODE_FILL),
+                None => add_person_ref(&mut diagram, person, OWNER_COLUMN),
+            };
+            diagram.add_edge(person_node, customer, ownership_label(owner), OWNER_COLOR);
+        }
+    }
+
+    // Related parties are keyed by their accounts too, for the counterparty links
+    for person in related_individuals.iter() {
+        let node = add_individual(&mut diagram, person, RELATED_COLUMN, RELATED_FILL);
+        for account in person.accounts.iter().flatten() {
+            if let Some(number) = non_blank(account.account_number.as_ref()) {
+                diagram.nodes[node]
+                    .details
+                    .push(format!("Tài khoản: {}", number));
+                diagram.add_alias(node, format!("lq:{}", account_key(number)));
+            }
+        }
+    }
+    for org in related_organizations.iter() {
+        let node = add_organization(&mut diagram, org, RELATED_COLUMN, RELATED_FILL);
+        for account in org.accounts.iter().flatten() {
+            if let Some(number) = non_blank(account.account_number.as_ref()) {
+                diagram.nodes[node]
+                    .details
+                    .push(format!("Tài khoản: {}", number));
+                diagram.add_alias(node, format!("lq:{}", account_key(number)));
+            }
+        }
+    }
+
+    if let Some(info) = transaction_info {
+        add_counterparties(&mut diagram, info);
+    }
+
+    match diagram.is_empty() {
+        true => None,
+        false => Some(diagram),
+    }
+}
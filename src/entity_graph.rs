@@ -0,0 +1,643 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    payload::{
+        entities::{Account, Identification, Individual, Organization, PersonRef},
+        form::Payload,
+    },
+    rules::{Severity, Violation, report_violations},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Individual,
+    Organization,
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityNode {
+    pub kind: EntityKind,
+    pub sheet: &'static str,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub id_numbers: Vec<String>,
+    /// Customer declared in Section II, as opposed to a related party or a person linked to one
+    pub customer: bool,
+    /// Listed in its own right rather than as a representative or beneficial owner of a customer
+    pub primary: bool,
+}
+
+impl EntityNode {
+    fn canonical_id(&self) -> Option<String> {
+        self.id.clone().or_else(|| self.id_numbers.first().cloned())
+    }
+
+    /// `None` for a node with neither an id nor a name, which cannot be told apart from others
+    fn identity(&self) -> Option<String> {
+        self.id_numbers
+            .iter()
+            .chain(self.id.as_ref())
+            .map(|id| normalize_id(id))
+            .chain(self.name.as_deref().map(normalize_name))
+            .find(|identity| !identity.is_empty())
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "'{}' ({}) tại sheet {}",
+            self.name.clone().unwrap_or_default(),
+            self.canonical_id().unwrap_or_default(),
+            self.sheet
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EntityGraph {
+    pub nodes: Vec<EntityNode>,
+    /// Owners of each account, keyed by account number and bank code
+    pub accounts: BTreeMap<(String, String), Vec<usize>>,
+}
+
+struct Query<'a> {
+    kind: Option<EntityKind>,
+    id: Option<&'a str>,
+    id_number: Option<&'a str>,
+    name: Option<&'a str>,
+    customers_only: bool,
+}
+
+pub(crate) fn normalize_id(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn normalize_name(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn non_blank(value: Option<&String>) -> Option<&str> {
+    value.map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+fn id_numbers_of(identifications: &Option<Vec<Identification>>) -> Vec<String> {
+    identifications
+        .iter()
+        .flatten()
+        .filter_map(|id| non_blank(id.id_number.as_ref()).map(str::to_string))
+        .collect()
+}
+
+fn organization_id_numbers(org: &Organization) -> Vec<String> {
+    let enterprise_code = org.enterprise_code.as_ref().and_then(|ec| ec.code.as_ref());
+    let license_number = org
+        .establishment_license
+        .as_ref()
+        .and_then(|license| license.license_number.as_ref());
+
+    [enterprise_code, license_number]
+        .into_iter()
+        .filter_map(|v| non_blank(v).map(str::to_string))
+        .collect()
+}
+
+fn individual_node(person: &Individual, sheet: &'static str, customer: bool) -> EntityNode {
+    EntityNode {
+        kind: EntityKind::Individual,
+        sheet,
+        id: non_blank(person.id.as_ref()).map(str::to_string),
+        name: person.full_name.clone(),
+        id_numbers: id_numbers_of(&person.identifications),
+        customer,
+        primary: true,
+    }
+}
+
+fn organization_node(org: &Organization, sheet: &'static str, customer: bool) -> EntityNode {
+    EntityNode {
+        kind: EntityKind::Organization,
+        sheet,
+        id: non_blank(org.id.as_ref()).map(str::to_string),
+        name: org.name.clone(),
+        id_numbers: organization_id_numbers(org),
+        customer,
+        primary: true,
+    }
+}
+
+impl EntityGraph {
+    pub fn build(payload: &Payload) -> Self {
+        let mut graph = Self::default();
+
+        if let Some(section_2) = payload.section_2.as_ref() {
+            for person in section_2.individuals.iter().flatten() {
+                let node = graph.add_node(individual_node(person, "Phần II. KHCN", true));
+                graph.add_accounts(node, &person.accounts);
+            }
+
+            for org in section_2.organizations.iter().flatten() {
+                let node = graph.add_node(organization_node(org, "Phần II. KHTC", true));
+                graph.add_accounts(node, &org.accounts);
+
+                for rep in org.representatives.iter().flatten() {
+                    graph.add_node(EntityNode {
+                        kind: EntityKind::Individual,
+                        sheet: "Phần II. Người đại diện",
+                        id: non_blank(rep.id.as_ref()).map(str::to_string),
+                        name: rep.full_name.clone(),
+                        id_numbers: id_numbers_of(&rep.identifications),
+                        customer: false,
+                        primary: false,
+                    });
+                }
+            }
+
+            let other_owners = section_2
+                .beneficial_owners
+                .iter()
+                .flat_map(|owners| owners.other_owners.iter().flatten());
+            for person in other_owners {
+                graph.add_node(EntityNode {
+                    primary: false,
+                    ..individual_node(person, "Phần II. CSHHL khác", false)
+                });
+            }
+        }
+
+        if let Some(section_3) = payload.section_3.as_ref() {
+            for person in section_3.related_individuals.iter().flatten() {
+                let node = graph.add_node(individual_node(person, "Phần III. CN liên quan", false));
+                graph.add_accounts(node, &person.accounts);
+            }
+
+            for org in section_3.related_organizations.iter().flatten() {
+                let node = graph.add_node(organization_node(org, "Phần III. TC liên quan", false));
+                graph.add_accounts(node, &org.accounts);
+            }
+        }
+
+        graph
+    }
+
+    fn add_node(&mut self, node: EntityNode) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn add_accounts(&mut self, node: usize, accounts: &Option<Vec<Account>>) {
+        for account in accounts.iter().flatten() {
+            let Some(account_number) = non_blank(account.account_number.as_ref()) else {
+                continue;
+            };
+            let bank_code = account
+                .bank
+                .as_ref()
+                .and_then(|bank| non_blank(bank.bank_code.as_ref()))
+                .unwrap_or_default();
+
+            self.accounts
+                .entry((normalize_id(account_number), normalize_id(bank_code)))
+                .or_default()
+                .push(node);
+        }
+    }
+
+    fn resolve(&self, query: &Query) -> Option<usize> {
+        let candidates = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| query.kind.is_none_or(|kind| node.kind == kind))
+            .filter(|(_, node)| !query.customers_only || node.customer)
+            .collect::<Vec<_>>();
+
+        // Prefer customers, then entities listed in their own right, when a key matches several
+        let best = |mut matches: Vec<(usize, &EntityNode)>| {
+            matches.sort_by_key(|(_, node)| (!node.customer, !node.primary));
+            matches.first().map(|(idx, _)| *idx)
+        };
+
+        let by_id_number = query.id_number.map(normalize_id).and_then(|key| {
+            best(
+                candidates
+                    .iter()
+                    .filter(|(_, node)| node.id_numbers.iter().any(|n| normalize_id(n) == key))
+                    .copied()
+                    .collect(),
+            )
+        });
+
+        let by_id = || {
+            query.id.map(normalize_id).and_then(|key| {
+                best(
+                    candidates
+                        .iter()
+                        .filter(|(_, node)| {
+                            node.id.as_deref().map(normalize_id) == Some(key.clone())
+                                || node.id_numbers.iter().any(|n| normalize_id(n) == key)
+                        })
+                        .copied()
+                        .collect(),
+                )
+            })
+        };
+
+        // A name alone only identifies an entity when no other entity has the same name
+        let by_name = || {
+            query.name.map(normalize_name).and_then(|key| {
+                let identities = candidates
+                    .iter()
+                    .filter(|(_, node)| {
+                        node.name.as_deref().map(normalize_name) == Some(key.clone())
+                    })
+                    .map(|(_, node)| node.identity())
+                    .collect::<BTreeSet<_>>();
+
+                match identities.len() {
+                    1 => best(
+                        candidates
+                            .iter()
+                            .filter(|(_, node)| {
+                                node.name.as_deref().map(normalize_name) == Some(key.clone())
+                            })
+                            .copied()
+                            .collect(),
+                    ),
+                    _ => None,
+                }
+            })
+        };
+
+        by_id_number.or_else(by_id).or_else(by_name)
+    }
+
+    fn duplicate_entities(&self) -> Vec<Violation> {
+        self.nodes
+            .iter()
+            .filter(|node| node.primary)
+            .filter_map(|node| node.identity().map(|identity| (node, identity)))
+            .fold(
+                BTreeMap::<_, Vec<&EntityNode>>::new(),
+                |mut acc, (node, identity)| {
+                    acc.entry((node.kind == EntityKind::Individual, identity))
+                        .or_default()
+                        .push(node);
+                    acc
+                },
+            )
+            .into_values()
+            .filter(|nodes| nodes.len() > 1)
+            .map(|nodes| Violation {
+                rule_id: "ENT-003",
+                severity: Severity::Warning,
+                message: format!(
+                    "Khách hàng bị khai báo trùng lặp: {}",
+                    nodes
+                        .iter()
+                        .map(|node| node.describe())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })
+            .collect()
+    }
+
+    fn shared_accounts(&self) -> Vec<Violation> {
+        self.accounts
+            .iter()
+            .filter(|(_, owners)| owners.len() > 1)
+            .map(|((account_number, bank_code), owners)| {
+                // Owners without an identity are told apart by their node
+                let identities = owners
+                    .iter()
+                    .map(|idx| self.nodes[*idx].identity().ok_or(*idx))
+                    .collect::<BTreeSet<_>>();
+                let owner_list = owners
+                    .iter()
+                    .map(|idx| self.nodes[*idx].describe())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                match identities.len() {
+                    1 => Violation {
+                        rule_id: "ENT-004",
+                        severity: Severity::Warning,
+                        message: format!(
+                            "Tài khoản {} (ngân hàng {}) bị khai báo trùng lặp: {}",
+                            account_number, bank_code, owner_list
+                        ),
+                    },
+                    _ => Violation {
+                        rule_id: "ENT-005",
+                        severity: Severity::Warning,
+                        message: format!(
+                            "Tài khoản {} (ngân hàng {}) là tài khoản chung của: {}",
+                            account_number, bank_code, owner_list
+                        ),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+fn fill_missing_ids(payload: &mut Payload) {
+    let fill_individual = |person: &mut Individual| {
+        if non_blank(person.id.as_ref()).is_none() {
+            person.id = id_numbers_of(&person.identifications).into_iter().next();
+        }
+    };
+    let fill_organization = |org: &mut Organization| {
+        if non_blank(org.id.as_ref()).is_none() {
+            org.id = organization_id_numbers(org).into_iter().next();
+        }
+    };
+
+    if let Some(section_2) = payload.section_2.as_mut() {
+        section_2
+            .individuals
+            .iter_mut()
+            .flatten()
+            .for_each(fill_individual);
+        section_2
+            .organizations
+            .iter_mut()
+            .flatten()
+            .for_each(fill_organization);
+    }
+
+    if let Some(section_3) = payload.section_3.as_mut() {
+        section_3
+            .related_individuals
+            .iter_mut()
+            .flatten()
+            .for_each(fill_individual);
+        section_3
+            .related_organizations
+            .iter_mut()
+            .flatten()
+            .for_each(fill_organization);
+    }
+}
+
+fn link_person_ref(
+    graph: &EntityGraph,
+    person: &mut PersonRef,
+    context: &str,
+    violations: &mut Vec<Violation>,
+) {
+    let query = Query {
+        kind: EntityKind::Individual.into(),
+        id: non_blank(person.id.as_ref()),
+        id_number: non_blank(person.id_number.as_ref()),
+        name: non_blank(person.full_name.as_ref()),
+        customers_only: false,
+    };
+
+    match graph.resolve(&query) {
+        Some(idx) => {
+            let node = &graph.nodes[idx];
+            person.id = node.canonical_id();
+            if person.full_name.is_none() {
+                person.full_name = node.name.clone();
+            }
+            if person.id_number.is_none() {
+                person.id_number = node.id_numbers.first().cloned();
+            }
+        }
+        None => violations.push(Violation {
+            rule_id: "ENT-002",
+            severity: Severity::Warning,
+            message: format!(
+                "{}: không tìm thấy thông tin của '{}' ({})",
+                context,
+                person.full_name.clone().unwrap_or_default(),
+                person.id_number.clone().unwrap_or_default()
+            ),
+        }),
+    }
+}
+
+/// Resolves every cross-sheet reference in the payload to a concrete customer, related party or
+/// linked person and rewrites the portal `id` fields so that they agree.
+pub fn link_entities(payload: &mut Payload) -> Vec<Violation> {
+    fill_missing_ids(payload);
+
+    let graph = EntityGraph::build(payload);
+    let mut violations = graph.duplicate_entities();
+    violations.extend(graph.shared_accounts());
+
+    let customer_accounts = |account_number: &str| {
+        let key = normalize_id(account_number);
+        graph
+            .accounts
+            .iter()
+            .filter(|((number, _), _)| *number == key)
+            .flat_map(|(_, owners)| owners.iter().copied())
+            .find(|idx| graph.nodes[*idx].customer)
+    };
+
+    if let Some(section_2) = payload.section_2.as_mut() {
+        let accounts = section_2
+            .individuals
+            .iter_mut()
+            .flatten()
+            .flat_map(|person| person.accounts.iter_mut().flatten())
+            .chain(
+                section_2
+                    .organizations
+                    .iter_mut()
+                    .flatten()
+                    .flat_map(|org| org.accounts.iter_mut().flatten()),
+            );
+        for account in accounts {
+            let context = format!(
+                "Người được ủy quyền của tài khoản {}",
+                account.account_number.clone().unwrap_or_default()
+            );
+            for person in account.authorized_persons.iter_mut().flatten() {
+                link_person_ref(&graph, person, &context, &mut violations);
+            }
+        }
+
+        let beneficial_owners = section_2.beneficial_owners.iter_mut();
+        for owners in beneficial_owners {
+            for link in owners.individual_links.iter_mut().flatten() {
+                let query = Query {
+                    kind: EntityKind::Individual.into(),
+                    id: non_blank(link.id.as_ref()),
+                    id_number: non_blank(link.id_number.as_ref()),
+                    name: non_blank(link.name.as_ref()),
+                    customers_only: true,
+                };
+
+                match graph.resolve(&query) {
+                    Some(idx) => link.id = graph.nodes[idx].canonical_id(),
+                    None => violations.push(Violation {
+                        rule_id: "ENT-001",
+                        severity: Severity::Error,
+                        message: format!(
+                            "Liên kết chủ sở hữu hưởng lợi của cá nhân '{}' ({}) không trỏ tới khách hàng nào tại sheet Phần II. KHCN",
+                            link.name.clone().unwrap_or_default(),
+                            link.id_number.clone().unwrap_or_default()
+                        ),
+                    }),
+                }
+
+                let context = format!(
+                    "Chủ sở hữu hưởng lợi của khách hàng '{}'",
+                    link.name.clone().unwrap_or_default()
+                );
+                let group = link.benefit_group.iter_mut().flat_map(|group| {
+                    group
+                        .main_group
+                        .iter_mut()
+                        .flatten()
+                        .chain(group.other_group.iter_mut().flatten())
+                });
+                for person in group {
+                    link_person_ref(&graph, person, &context, &mut violations);
+                }
+            }
+
+            for link in owners.organization_links.iter_mut().flatten() {
+                let query = Query {
+                    kind: EntityKind::Organization.into(),
+                    id: non_blank(link.id.as_ref()),
+                    id_number: non_blank(link.id_number.as_ref()),
+                    name: non_blank(link.name.as_ref()),
+                    customers_only: true,
+                };
+
+                match graph.resolve(&query) {
+                    Some(idx) => link.id = graph.nodes[idx].canonical_id(),
+                    None => violations.push(Violation {
+                        rule_id: "ENT-001",
+                        severity: Severity::Error,
+                        message: format!(
+                            "Liên kết chủ sở hữu hưởng lợi của tổ chức '{}' ({}) không trỏ tới khách hàng nào tại sheet Phần II. KHTC",
+                            link.name.clone().unwrap_or_default(),
+                            link.id_number.clone().unwrap_or_default()
+                        ),
+                    }),
+                }
+
+                let context = format!(
+                    "Chủ sở hữu hưởng lợi của khách hàng '{}'",
+                    link.name.clone().unwrap_or_default()
+                );
+                let group = link.benefit_group.iter_mut().flat_map(|group| {
+                    group
+                        .main_group
+                        .iter_mut()
+                        .flatten()
+                        .chain(group.other_group.iter_mut().flatten())
+                });
+                for person in group {
+                    link_person_ref(&graph, person, &context, &mut violations);
+                }
+            }
+        }
+    }
+
+    let money_flows = payload
+        .section_4
+        .as_mut()
+        .and_then(|s| s.transaction_info.as_mut())
+        .and_then(|t| t.money_flows.as_mut());
+    for flow in money_flows.into_iter().flatten() {
+        let query = Query {
+            kind: None,
+            id: non_blank(flow.id.as_ref()),
+            id_number: non_blank(flow.identification.as_ref()),
+            name: non_blank(flow.subject_name.as_ref()),
+            customers_only: true,
+        };
+        let owner = graph
+            .resolve(&query)
+            .or_else(|| non_blank(flow.account_number.as_ref()).and_then(customer_accounts));
+
+        match owner {
+            Some(idx) => {
+                let node = &graph.nodes[idx];
+                flow.id = node.canonical_id();
+                if non_blank(flow.subject_name.as_ref()).is_none() {
+                    flow.subject_name = node.name.clone();
+                }
+                if non_blank(flow.identification.as_ref()).is_none() {
+                    flow.identification = node.id_numbers.first().cloned();
+                }
+            }
+            None => violations.push(Violation {
+                rule_id: "ENT-006",
+                severity: Severity::Warning,
+                message: format!(
+                    "Dòng tiền của tài khoản {} (CIF {}) không thuộc khách hàng nào tại Phần II",
+                    flow.account_number.clone().unwrap_or_default(),
+                    flow.id.clone().unwrap_or_default()
+                ),
+            }),
+        }
+    }
+
+    violations
+}
+
+/// Links the payload entities, logs warnings and fails on references that cannot be resolved.
+pub fn check_entity_links(payload: &mut Payload) -> anyhow::Result<()> {
+    report_violations(
+        &link_entities(payload),
+        "Thông tin liên kết giữa các sheet không hợp lệ",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, id_number: &str) -> EntityNode {
+        EntityNode {
+            kind: EntityKind::Individual,
+            sheet: "Phần II. KHCN",
+            id: None,
+            name: Some(name.to_string()),
+            id_numbers: [id_number]
+                .into_iter()
+                .filter(|n| !n.is_empty())
+                .map(str::to_string)
+                .collect(),
+            customer: true,
+            primary: true,
+        }
+    }
+
+    #[test]
+    fn blank_entities_are_not_duplicates_of_each_other() {
+        let graph = EntityGraph {
+            nodes: vec![node(" ", ""), node("", "")],
+            accounts: BTreeMap::from([(("123".to_string(), String::new()), vec![0, 1])]),
+        };
+
+        assert!(graph.duplicate_entities().is_empty());
+        let shared = graph.shared_accounts();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].rule_id, "ENT-005");
+    }
+
+    #[test]
+    fn entities_with_the_same_id_number_are_duplicates() {
+        let graph = EntityGraph {
+            nodes: vec![node("Nguyễn Văn A", "001"), node("Nguyen Van A", " 001")],
+            accounts: BTreeMap::new(),
+        };
+
+        assert_eq!(graph.duplicate_entities().len(), 1);
+    }
+}
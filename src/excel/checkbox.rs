@@ -0,0 +1,421 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Context;
+use calamine::{Data, Range};
+use quick_xml::events::Event;
+
+use crate::{
+    template::{ReportForm, value_from_key, value_list_from_key},
+    utils::excel::ExcelCoord,
+};
+
+const MARKERS_KEY: &str = "Dấu tick";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckState {
+    Checked,
+    Unchecked,
+    Unrecognized {
+        value: String,
+        address: String,
+        markers: Vec<String>,
+    },
+}
+
+impl CheckState {
+    pub fn is_checked(&self) -> bool {
+        matches!(self, CheckState::Checked)
+    }
+
+    pub fn warn_if_unrecognized(&self, item: &str) {
+        if let CheckState::Unrecognized {
+            value,
+            address,
+            markers,
+        } = self
+        {
+            log::warn!(
+                "Giá trị '{}' tại ô {} của mục '{}' không phải là dấu tick hợp lệ, mục này được coi là không được chọn. Các dấu tick hợp lệ: {}",
+                value,
+                address,
+                item,
+                markers.join(", ")
+            );
+        }
+    }
+}
+
+/// Whether an item read from the tick column is selected, warning about unrecognized marks.
+pub fn is_selected(state: Option<&CheckState>, item: &str) -> bool {
+    match state {
+        Some(state) => {
+            state.warn_if_unrecognized(item);
+            state.is_checked()
+        }
+        None => false,
+    }
+}
+
+/// Markers accepted as a ticked box, either a single value or a list in the template.
+fn checkbox_markers(report_form: &ReportForm) -> Vec<String> {
+    value_list_from_key(report_form, MARKERS_KEY)
+        .or_else(|_| value_from_key(report_form, MARKERS_KEY).map(|marker| vec![marker]))
+        .unwrap_or_default()
+}
+
+/// Checked state of form controls keyed by the lowercased sheet name and a cell
+type LinkedCheckboxes = HashMap<(String, (u32, u32)), bool>;
+
+/// Form-control checkbox of a sheet.
+struct FormCheckbox {
+    /// Lowercased name of the sheet the control is drawn on
+    sheet: String,
+    /// Top-left cell the control is drawn over
+    anchor: Option<(u32, u32)>,
+    link: Option<(String, (u32, u32))>,
+    checked: bool,
+}
+
+/// Tick marks typed in cells, boolean cells and form-control checkboxes. Loaded once per
+/// workbook.
+pub struct Checkboxes {
+    markers: Vec<String>,
+    linked: LinkedCheckboxes,
+    /// Checked state of form controls keyed by the lowercased sheet name and the row they are
+    /// linked to or drawn on, for controls that are not linked to the tick column
+    rows: HashMap<(String, u32), bool>,
+}
+
+impl Checkboxes {
+    pub fn load(file_path: &Path, report_form: &ReportForm) -> Self {
+        let controls = read_form_checkboxes(file_path).unwrap_or_else(|err| {
+            log::warn!(
+                "Không thể đọc các ô checkbox trong file {:#?}, chỉ sử dụng giá trị trong ô: {:?}",
+                file_path,
+                err
+            );
+            vec![]
+        });
+
+        let mut linked = LinkedCheckboxes::new();
+        let mut rows = HashMap::<(String, u32), bool>::new();
+        for control in controls {
+            // The linked cell gives the row when it is on the same sheet, the drawing otherwise
+            let row = match control.link.as_ref() {
+                Some((sheet, (row, _))) if *sheet == control.sheet => Some(*row),
+                _ => control.anchor.map(|(row, _)| row),
+            };
+            if let Some(row) = row {
+                *rows.entry((control.sheet.clone(), row)).or_default() |= control.checked;
+            }
+            if let Some(link) = control.link {
+                linked.insert(link, control.checked);
+            }
+        }
+
+        Checkboxes {
+            markers: checkbox_markers(report_form),
+            linked,
+            rows,
+        }
+    }
+
+    /// State of the cell at the absolute `coord` (0-based row, column) of `sheet`.
+    pub fn state(&self, sheet: &str, coord: (u32, u32), value: Option<&Data>) -> CheckState {
+        if let Some(checked) = self.linked.get(&(sheet.to_lowercase(), coord)) {
+            return match checked {
+                true => CheckState::Checked,
+                false => CheckState::Unchecked,
+            };
+        }
+
+        let text = match value {
+            None | Some(Data::Empty) => return CheckState::Unchecked,
+            Some(Data::Bool(true)) => return CheckState::Checked,
+            Some(Data::Bool(false)) => return CheckState::Unchecked,
+            Some(Data::String(s)) => s.trim().to_string(),
+            Some(other) => other.to_string().trim().to_string(),
+        };
+
+        if text.is_empty() || text.eq_ignore_ascii_case("false") {
+            return CheckState::Unchecked;
+        }
+        if self
+            .markers
+            .iter()
+            .any(|marker| marker.trim().to_lowercase() == text.to_lowercase())
+        {
+            return CheckState::Checked;
+        }
+
+        CheckState::Unrecognized {
+            value: text,
+            markers: self.markers.clone(),
+            address: format!(
+                "'{}'!{}",
+                sheet,
+                ExcelCoord::from(coord).to_a1_with_base(ExcelCoord::default())
+            ),
+        }
+    }
+
+    /// State of the tick column (column B) on row `row_idx` of `range`. A checked form control
+    /// on the row ticks it whichever cell the control is linked to.
+    pub fn row_state(&self, sheet: &str, range: &Range<Data>, row_idx: usize) -> CheckState {
+        let start = range.start().unwrap_or_default();
+        let coord = (start.0 + row_idx as u32, start.1 + 1);
+
+        let key = (sheet.to_lowercase(), coord.0);
+        if !self.linked.contains_key(&(key.0.clone(), coord))
+            && self.rows.get(&key).copied().unwrap_or_default()
+        {
+            return CheckState::Checked;
+        }
+        self.state(sheet, coord, range.get((row_idx, 1)))
+    }
+}
+
+fn resolve_part(base_dir: &Path, target: &str) -> String {
+    let path = match target.strip_prefix('/') {
+        Some(absolute) => PathBuf::from(absolute),
+        None => base_dir.join(target),
+    };
+
+    let mut parts: Vec<String> = vec![];
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            _ => {}
+        }
+    }
+    parts.join("/")
+}
+
+pub(super) fn read_part<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> anyhow::Result<Option<String>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    Ok(Some(content))
+}
+
+/// Attributes of every element named `element` (namespace prefix ignored).
+pub(super) fn element_attributes(
+    xml: &str,
+    element: &[u8],
+) -> anyhow::Result<Vec<HashMap<String, String>>> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut elements = vec![];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) | Event::Empty(tag) if tag.local_name().as_ref() == element => {
+                let attributes = tag
+                    .attributes()
+                    .map(|attr| -> anyhow::Result<(String, String)> {
+                        let attr = attr?;
+                        let key =
+                            String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+                        Ok((
+                            key,
+                            attr.decode_and_unescape_value(reader.decoder())?
+                                .to_string(),
+                        ))
+                    })
+                    .collect::<anyhow::Result<HashMap<_, _>>>()?;
+                elements.push(attributes);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(elements)
+}
+
+/// Relationship id to part name, from the `.rels` file of `part`.
+pub(super) fn relationships<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    part: &str,
+) -> anyhow::Result<Vec<(String, String, String)>> {
+    let part_path = Path::new(part);
+    let base_dir = part_path.parent().unwrap_or(Path::new(""));
+    let file_name = part_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let rels_name = resolve_part(base_dir, &format!("_rels/{}.rels", file_name));
+
+    let Some(rels) = read_part(archive, &rels_name)? else {
+        return Ok(vec![]);
+    };
+
+    Ok(element_attributes(&rels, b"Relationship")?
+        .into_iter()
+        .map(|attrs| {
+            let target = attrs.get("Target").cloned().unwrap_or_default();
+            (
+                attrs.get("Id").cloned().unwrap_or_default(),
+                attrs.get("Type").cloned().unwrap_or_default(),
+                resolve_part(base_dir, &target),
+            )
+        })
+        .collect())
+}
+
+/// Parses a link such as `$B$12`, `Sheet1!$B$12` or `'Phần IV'!B12`.
+fn parse_cell_link(link: &str, default_sheet: &str) -> Option<(String, (u32, u32))> {
+    let link = link.trim().trim_start_matches('=');
+    let (sheet, cell) = match link.rsplit_once('!') {
+        Some((sheet, cell)) => (sheet.trim_matches('\'').replace("''", "'"), cell),
+        None => (default_sheet.to_string(), link),
+    };
+    let coord = ExcelCoord::from_a1_style(&cell.replace('$', ""))?;
+    Some((sheet.to_lowercase(), (coord.row, coord.col)))
+}
+
+/// Top-left cell of each form control of a sheet, keyed by the relationship id of its
+/// properties part.
+fn control_anchors(sheet_xml: &str) -> anyhow::Result<HashMap<String, (u32, u32)>> {
+    let mut reader = quick_xml::Reader::from_str(sheet_xml);
+    let mut anchors = HashMap::new();
+
+    let mut control_id: Option<String> = None;
+    let mut in_from = false;
+    let mut field: Option<Vec<u8>> = None;
+    let (mut row, mut col) = (None, None);
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) if tag.local_name().as_ref() == b"control" => {
+                control_id = tag
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.local_name().as_ref() == b"id")
+                    .map(|attr| String::from_utf8_lossy(&attr.value).to_string());
+                (row, col) = (None, None);
+            }
+            Event::Start(tag) if tag.local_name().as_ref() == b"from" => in_from = true,
+            Event::End(tag) if tag.local_name().as_ref() == b"from" => in_from = false,
+            Event::Start(tag) if in_from => field = Some(tag.local_name().as_ref().to_vec()),
+            Event::Text(text) if in_from => {
+                let value = text.decode()?.trim().parse::<u32>().ok();
+                match field.as_deref() {
+                    Some(b"row") => row = value,
+                    Some(b"col") => col = value,
+                    _ => {}
+                }
+            }
+            Event::End(tag) if tag.local_name().as_ref() == b"control" => {
+                if let (Some(id), Some(row), Some(col)) = (control_id.take(), row, col) {
+                    anchors.insert(id, (row, col));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(anchors)
+}
+
+fn read_form_checkboxes(file_path: &Path) -> anyhow::Result<Vec<FormCheckbox>> {
+    let file = std::fs::File::open(file_path)
+        .with_context(|| format!("Không thể mở file {:#?}", file_path))?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    let workbook_xml = read_part(&mut archive, "xl/workbook.xml")?.unwrap_or_default();
+    let sheets = element_attributes(&workbook_xml, b"sheet")?;
+    let workbook_rels = relationships(&mut archive, "xl/workbook.xml")?;
+
+    let mut checkboxes = vec![];
+    for sheet in sheets {
+        let sheet_name = sheet.get("name").cloned().unwrap_or_default();
+        let Some((_, _, sheet_part)) = workbook_rels
+            .iter()
+            .find(|(id, _, _)| Some(id) == sheet.get("id"))
+        else {
+            continue;
+        };
+        let controls = relationships(&mut archive, sheet_part)?
+            .into_iter()
+            .filter(|(_, rel_type, _)| rel_type.ends_with("/ctrlProp"))
+            .collect::<Vec<_>>();
+        if controls.is_empty() {
+            continue;
+        }
+        let sheet_xml = read_part(&mut archive, sheet_part)?.unwrap_or_default();
+        let anchors = control_anchors(&sheet_xml)?;
+
+        for (rel_id, _, target) in controls {
+            let Some(ctrl_prop) = read_part(&mut archive, &target)? else {
+                continue;
+            };
+
+            for control in element_attributes(&ctrl_prop, b"formControlPr")? {
+                let is_checkbox = control
+                    .get("objectType")
+                    .is_some_and(|t| t.eq_ignore_ascii_case("CheckBox"));
+                if !is_checkbox {
+                    continue;
+                }
+
+                checkboxes.push(FormCheckbox {
+                    sheet: sheet_name.to_lowercase(),
+                    anchor: anchors.get(&rel_id).copied(),
+                    link: control
+                        .get("fmlaLink")
+                        .and_then(|link| parse_cell_link(link, &sheet_name)),
+                    checked: control
+                        .get("checked")
+                        .is_some_and(|c| c.eq_ignore_ascii_case("Checked")),
+                });
+            }
+        }
+    }
+
+    Ok(checkboxes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controls_are_anchored_at_their_top_left_cell() {
+        let sheet_xml = r#"<worksheet xmlns:r="r" xmlns:xdr="xdr"><controls>
+            <control shapeId="1025" r:id="rId3" name="Check Box 1"><controlPr><anchor>
+                <from><xdr:col>3</xdr:col><xdr:colOff>10</xdr:colOff><xdr:row>11</xdr:row><xdr:rowOff>5</xdr:rowOff></from>
+                <to><xdr:col>4</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>12</xdr:row><xdr:rowOff>0</xdr:rowOff></to>
+            </anchor></controlPr></control>
+        </controls></worksheet>"#;
+
+        let anchors = control_anchors(sheet_xml).unwrap();
+        assert_eq!(anchors.get("rId3"), Some(&(11, 3)));
+    }
+
+    #[test]
+    fn cell_links_name_their_sheet() {
+        assert_eq!(
+            parse_cell_link("'Phần IV'!$D$12", "STR"),
+            Some(("phần iv".to_string(), (11, 3)))
+        );
+        assert_eq!(
+            parse_cell_link("$B$3", "STR"),
+            Some(("str".to_string(), (2, 1)))
+        );
+    }
+}
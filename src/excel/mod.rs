@@ -1,3 +1,5 @@
+mod checkbox;
+mod number_format;
 mod section1;
 mod section2;
 mod section3;
@@ -14,6 +16,10 @@ use anyhow::Context;
 use calamine::{DataType, Reader};
 
 use crate::{
+    attachments::generated::ReportData,
+    codes::{change_type::ChangeTypeCode, utils::start_warning_scope},
+    deadline::{FilingDeadline, PendingReport},
+    excel::{checkbox::Checkboxes, number_format::PercentCells},
     payload::{
         self,
         form::{Form, Payload},
@@ -25,28 +31,39 @@ use crate::{
         section5::Section5,
         section6::Section6,
     },
-    template::{cell_value_from_key, table_config_from_key},
-    utils::excel::col_name_to_index,
+    template::{ReportForm, Table, cell_value_from_key, select_report_form, table_config_from_key},
+    utils::{
+        datetime::{ConvertDateFormat, ensure_not_after},
+        excel::{CellAddress, col_name_to_index},
+    },
 };
 
 impl Form {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         file_path: &std::path::Path,
+        form_override: Option<&'static ReportForm>,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        let payload = Payload::from_excel(workbook, file_path)?;
-        let detection_date = payload.section_4.detection_date.clone().unwrap_or_default();
-        let others_info = [("ngay_phat_hien".to_string(), detection_date.clone())]
+        start_warning_scope(file_path);
+        let report_form = select_report_form(workbook, form_override)?;
+        let payload = Payload::from_excel(workbook, file_path, report_form)?;
+        let others_info = payload
+            .section_4
+            .as_ref()
+            .map(|section_4| {
+                let detection_date = section_4.detection_date.clone().unwrap_or_default();
+                ("ngay_phat_hien".to_string(), detection_date)
+            })
             .into_iter()
             .collect();
 
         Ok(Form {
             id: None,
-            internal_number: internal_number(workbook)?,
-            report_type: "M1".to_string(),
+            internal_number: internal_number(workbook, report_form)?,
+            report_type: report_form.code.clone(),
             creation_status: payload::form::CreationStatus::InProgress,
             payload: payload.into(),
             others: others_info,
@@ -58,18 +75,78 @@ impl Payload {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         file_path: &std::path::Path,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
+        let checkboxes = Checkboxes::load(file_path, report_form);
+        let percent_cells = PercentCells::load(file_path);
+
+        let general_info = GeneralInfo::from_excel(workbook, file_path, report_form)?;
+        let section_1 = match report_form.has_section(1) {
+            true => Some(Section1::from_excel(workbook, file_path, report_form)?),
+            false => None,
+        };
+        let section_2 = match report_form.has_section(2) {
+            true => Some(Section2::from_excel(
+                workbook,
+                file_path,
+                &percent_cells,
+                report_form,
+            )?),
+            false => None,
+        };
+        let section_3 = match report_form.has_section(3) {
+            true => Some(Section3::from_excel(workbook, file_path, report_form)?),
+            false => None,
+        };
+        let section_4 = match report_form.has_section(4) {
+            true => Some(Section4::from_excel(
+                workbook,
+                file_path,
+                &checkboxes,
+                &percent_cells,
+                report_form,
+            )?),
+            false => None,
+        };
+        let section_5 = match report_form.has_section(5) {
+            true => Some(Section5::from_excel(
+                workbook,
+                file_path,
+                &checkboxes,
+                report_form,
+            )?),
+            false => None,
+        };
+        let section_6 = match report_form.has_section(6) {
+            true => {
+                let data = ReportData {
+                    section_2: section_2.as_ref(),
+                    section_3: section_3.as_ref(),
+                    transaction_info: section_4
+                        .as_ref()
+                        .and_then(|section_4| section_4.transaction_info.as_ref()),
+                };
+                Some(Section6::from_excel(
+                    workbook,
+                    file_path,
+                    data,
+                    report_form,
+                )?)
+            }
+            false => None,
+        };
+
         Ok(Payload {
-            general_info: GeneralInfo::from_excel(workbook, file_path)?,
-            section_1: Section1::from_excel(workbook, file_path)?,
-            section_2: Section2::from_excel(workbook, file_path)?,
-            section_3: Section3::from_excel(workbook, file_path)?,
-            section_4: Section4::from_excel(workbook, file_path)?,
-            section_5: Section5::from_excel(workbook, file_path)?,
-            section_6: Section6::from_excel(workbook, file_path)?,
+            general_info,
+            section_1,
+            section_2,
+            section_3,
+            section_4,
+            section_5,
+            section_6,
         })
     }
 }
@@ -78,39 +155,165 @@ impl GeneralInfo {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         _file_path: &std::path::Path,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(GeneralInfo {
-            report_date: report_date(workbook)?,
+            report_date: report_date(workbook, report_form)?,
             report_number: None,
-            amendment: Amendment {
-                change_type: 0,
-                report_number: String::new(),
-                report_date: String::new(),
-            },
+            amendment: Amendment::from_excel(workbook, report_form)?,
             reporting_entity_name: cell_value_from_key(
+                report_form,
                 "Phần I.1: Thông tin đối tượng báo cáo - Tên",
                 workbook,
             )?
             .into(),
             reporting_entity_code: cell_value_from_key(
+                report_form,
                 "Phần I.1: Thông tin đối tượng báo cáo - Mã",
                 workbook,
             )?
             .into(),
-            report_form: Some("M1".to_string()),
+            report_form: Some(report_form.code.clone()),
         })
     }
 }
 
-fn report_date<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<String>>
+impl Amendment {
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
+    where
+        RS: Seek + Read,
+    {
+        Self::_from_excel(workbook, report_form)
+            .context("Lỗi xử lý thông tin sửa đổi, bổ sung báo cáo")
+    }
+
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
+    where
+        RS: Seek + Read,
+    {
+        let change_type =
+            cell_value_from_key(report_form, "Sửa đổi bổ sung - Loại thay đổi", workbook)?
+                .to_change_type_code()?;
+        let report_number =
+            cell_value_from_key(report_form, "Sửa đổi bổ sung - Số báo cáo gốc", workbook)?
+                .trim()
+                .to_string();
+        let original_date =
+            cell_value_from_key(report_form, "Sửa đổi bổ sung - Ngày báo cáo gốc", workbook)?;
+
+        if change_type == 0 {
+            if !report_number.is_empty() || !original_date.trim().is_empty() {
+                log::warn!(
+                    "Đã nhập số/ngày báo cáo gốc nhưng chưa chọn loại thay đổi. Báo cáo được gửi như báo cáo mới"
+                );
+            }
+            return Ok(Amendment::default());
+        }
+
+        if report_number.is_empty() || original_date.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "Báo cáo sửa đổi, bổ sung phải có số báo cáo gốc và ngày báo cáo gốc"
+            ));
+        }
+
+        let original_date = original_date
+            .convert_date_vn_to_iso()
+            .context("Ngày báo cáo gốc không hợp lệ")?
+            .unwrap_or_default();
+        ensure_not_after(
+            &Some(original_date.clone()),
+            &report_date(workbook, report_form)?,
+        )
+        .context("Ngày báo cáo gốc không được sau ngày báo cáo")?;
+
+        Ok(Amendment {
+            change_type,
+            report_number,
+            report_date: original_date,
+        })
+    }
+}
+
+impl PendingReport {
+    /// Reads only what is needed to compute the filing deadline, so that unfinished workbooks can
+    /// still be listed.
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        file_path: &std::path::Path,
+        today: chrono::NaiveDate,
+        form_override: Option<&'static ReportForm>,
+    ) -> anyhow::Result<Self>
+    where
+        RS: Seek + Read,
+    {
+        start_warning_scope(file_path);
+        let report_form = select_report_form(workbook, form_override)?;
+        let change_type =
+            cell_value_from_key(report_form, "Sửa đổi bổ sung - Loại thay đổi", workbook)?
+                .to_change_type_code()?;
+
+        let detection_date = match report_form.has_section(4) && change_type == 0 {
+            true => cell_value_from_key(
+                report_form,
+                "Phần IV: Ngày phát hiện giao dịch đáng ngờ",
+                workbook,
+            )?,
+            false => String::new(),
+        };
+        // Same conversion as Section IV, so the deadline matches the one checked on submit
+        let deadline = match detection_date.trim() {
+            "" => None,
+            date => date
+                .convert_date_vn_to_iso()
+                .context("Lỗi dữ liệu Phần IV - Mục 6. Ngày phát hiện giao dịch đáng ngờ")?
+                .map(|date| FilingDeadline::from_iso_date(&date, today))
+                .transpose()?,
+        };
+
+        Ok(PendingReport {
+            file_name: file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            internal_number: internal_number(workbook, report_form)?,
+            change_type,
+            deadline,
+        })
+    }
+}
+
+/// Folder next to the workbook, named after it, that holds the report attachments.
+pub fn attachment_folder(file_path: &std::path::Path) -> std::path::PathBuf {
+    let file_name = file_path
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut folder = std::path::PathBuf::new();
+    folder.push("input");
+    folder.push(file_name);
+    folder
+}
+
+fn report_date<RS>(
+    workbook: &mut calamine::Xlsx<RS>,
+    report_form: &ReportForm,
+) -> anyhow::Result<Option<String>>
 where
     RS: Seek + Read,
 {
     const CELL_KEY: &str = "Ngày báo cáo";
-    let cell_value = cell_value_from_key(CELL_KEY, workbook)?;
+    let cell_value = cell_value_from_key(report_form, CELL_KEY, workbook)?;
 
     let date_value = regex::Regex::new(r"(?ms)(\d{2}).+(\d{2}).+(\d{4})")?
         .captures(&cell_value)
@@ -118,23 +321,27 @@ where
     Ok(date_value)
 }
 
-fn internal_number<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<String>
+fn internal_number<RS>(
+    workbook: &mut calamine::Xlsx<RS>,
+    report_form: &ReportForm,
+) -> anyhow::Result<String>
 where
     RS: Seek + Read,
 {
     const CELL_KEY: &str = "Mã báo cáo nội bộ";
-    let cell_value = cell_value_from_key(CELL_KEY, workbook)?;
+    let cell_value = cell_value_from_key(report_form, CELL_KEY, workbook)?;
     Ok(cell_value)
 }
 
 pub fn read_table_from_sheet<RS>(
     workbook: &mut calamine::Xlsx<RS>,
     sheet_key: &str,
+    report_form: &ReportForm,
 ) -> anyhow::Result<(Vec<Vec<String>>, HashMap<String, String>, (u32, u32))>
 where
     RS: Seek + Read,
 {
-    let table_config = table_config_from_key(sheet_key)?;
+    let table_config = table_config_from_key(report_form, sheet_key)?;
     let range = workbook.worksheet_range(&table_config.sheet)?;
 
     // Check table columns matched with config
@@ -217,6 +424,18 @@ where
     ))
 }
 
+/// Address of a cell in the `n_row`-th data row of a table, counted from the row below the header.
+pub fn get_cell_address(col_name: &str, table: &Table, n_row: usize) -> CellAddress {
+    CellAddress {
+        sheet: table.sheet.clone(),
+        cell: format!(
+            "{}{}",
+            table.columns.get(col_name).cloned().unwrap_or_default(),
+            table.header_row as usize + 1 + n_row
+        ),
+    }
+}
+
 pub fn get_cell_value(
     col_name: &str,
     col_map: &HashMap<String, String>,
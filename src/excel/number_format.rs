@@ -0,0 +1,188 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::Context;
+use quick_xml::events::Event;
+
+use crate::{
+    excel::checkbox::{element_attributes, read_part, relationships},
+    utils::excel::CellAddress,
+};
+
+// Built-in number formats `0%` and `0.00%`
+const BUILTIN_PERCENT_FORMATS: [u32; 2] = [9, 10];
+
+/// Cells formatted as a percentage, whose value Excel stores as a fraction: 25% is stored as
+/// 0.25.
+#[derive(Debug, Default)]
+pub struct PercentCells {
+    cells: HashSet<(String, String)>,
+}
+
+impl PercentCells {
+    pub fn load(file_path: &Path) -> Self {
+        read_percent_cells(file_path).unwrap_or_else(|err| {
+            log::warn!(
+                "Không thể đọc định dạng ô trong file {:#?}, các tỷ lệ được đọc theo giá trị nhập: {:?}",
+                file_path,
+                err
+            );
+            Default::default()
+        })
+    }
+
+    pub fn contains(&self, address: &CellAddress) -> bool {
+        self.cells
+            .contains(&(address.sheet.to_lowercase(), address.cell.to_uppercase()))
+    }
+}
+
+/// Whether a custom format code shows a percentage, `%` inside quotes or escaped is literal text.
+fn is_percent_format(code: &str) -> bool {
+    let mut chars = code.chars();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' => {
+                chars.next();
+            }
+            '%' if !quoted => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Indices of the cell styles of `styles.xml` using a percent number format.
+fn percent_styles(styles_xml: &str) -> anyhow::Result<HashSet<u32>> {
+    let custom_formats = element_attributes(styles_xml, b"numFmt")?
+        .into_iter()
+        .filter_map(|attrs| {
+            let id = attrs.get("numFmtId")?.parse::<u32>().ok()?;
+            Some((id, attrs.get("formatCode").cloned().unwrap_or_default()))
+        })
+        .collect::<HashMap<_, _>>();
+    let is_percent = |id: u32| match custom_formats.get(&id) {
+        Some(code) => is_percent_format(code),
+        None => BUILTIN_PERCENT_FORMATS.contains(&id),
+    };
+
+    // Only the `xf` of `cellXfs` are referenced by cells, `cellStyleXfs` uses the same element
+    let mut reader = quick_xml::Reader::from_str(styles_xml);
+    let mut in_cell_xfs = false;
+    let mut index = 0;
+    let mut styles = HashSet::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) if tag.local_name().as_ref() == b"cellXfs" => in_cell_xfs = true,
+            Event::End(tag) if tag.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Event::Start(tag) | Event::Empty(tag)
+                if in_cell_xfs && tag.local_name().as_ref() == b"xf" =>
+            {
+                let format_id = tag
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.local_name().as_ref() == b"numFmtId")
+                    .and_then(|attr| String::from_utf8_lossy(&attr.value).parse::<u32>().ok())
+                    .unwrap_or_default();
+                if is_percent(format_id) {
+                    styles.insert(index);
+                }
+                index += 1;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(styles)
+}
+
+/// References of the cells of a sheet whose style is one of `styles`.
+fn styled_cells(sheet_xml: &str, styles: &HashSet<u32>) -> anyhow::Result<Vec<String>> {
+    Ok(element_attributes(sheet_xml, b"c")?
+        .into_iter()
+        .filter(|attrs| {
+            attrs
+                .get("s")
+                .and_then(|s| s.parse::<u32>().ok())
+                .is_some_and(|s| styles.contains(&s))
+        })
+        .filter_map(|attrs| attrs.get("r").map(|r| r.to_uppercase()))
+        .collect())
+}
+
+fn read_percent_cells(file_path: &Path) -> anyhow::Result<PercentCells> {
+    let file = std::fs::File::open(file_path)
+        .with_context(|| format!("Không thể mở file {:#?}", file_path))?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    let styles_xml = read_part(&mut archive, "xl/styles.xml")?.unwrap_or_default();
+    let styles = percent_styles(&styles_xml)?;
+    if styles.is_empty() {
+        return Ok(Default::default());
+    }
+
+    let workbook_xml = read_part(&mut archive, "xl/workbook.xml")?.unwrap_or_default();
+    let workbook_rels = relationships(&mut archive, "xl/workbook.xml")?;
+
+    let mut cells = HashSet::new();
+    for sheet in element_attributes(&workbook_xml, b"sheet")? {
+        let sheet_name = sheet.get("name").cloned().unwrap_or_default();
+        let Some((_, _, sheet_part)) = workbook_rels
+            .iter()
+            .find(|(id, _, _)| Some(id) == sheet.get("id"))
+        else {
+            continue;
+        };
+        let sheet_xml = read_part(&mut archive, sheet_part)?.unwrap_or_default();
+        cells.extend(
+            styled_cells(&sheet_xml, &styles)?
+                .into_iter()
+                .map(|cell| (sheet_name.to_lowercase(), cell)),
+        );
+    }
+
+    Ok(PercentCells { cells })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_styles_come_from_the_number_format() {
+        let styles_xml = r#"<styleSheet>
+            <numFmts count="2">
+                <numFmt numFmtId="164" formatCode="0.0%"/>
+                <numFmt numFmtId="165" formatCode="&quot;%&quot;0"/>
+            </numFmts>
+            <cellStyleXfs count="1"><xf numFmtId="9"/></cellStyleXfs>
+            <cellXfs count="5">
+                <xf numFmtId="0"/>
+                <xf numFmtId="9"/>
+                <xf numFmtId="164"/>
+                <xf numFmtId="165"/>
+                <xf numFmtId="4"><alignment horizontal="left"/></xf>
+            </cellXfs>
+        </styleSheet>"#;
+
+        assert_eq!(percent_styles(styles_xml).unwrap(), HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn styled_cells_are_listed_by_reference() {
+        let sheet_xml = r#"<worksheet><sheetData>
+            <row r="7"><c r="E7" s="1"><v>0.25</v></c><c r="F7" s="0"><v>25</v></c></row>
+            <row r="8"><c r="E8" s="2"/></row>
+        </sheetData></worksheet>"#;
+
+        assert_eq!(
+            styled_cells(sheet_xml, &HashSet::from([1, 2])).unwrap(),
+            vec!["E7", "E8"]
+        );
+    }
+}
@@ -3,93 +3,114 @@ use std::io::{Read, Seek};
 use anyhow::Context;
 
 use crate::{
-    codes::country::CountryCode,
+    codes::{admin_unit::AdminUnitCode, contact::ContactInfo, country::CountryCode},
     payload::section1::{
         Address, ReportPreparer, ReportingEntity, ResponsiblePerson, Section1, TransactionLocation,
     },
-    template::cell_value_from_key,
+    template::{ReportForm, cell_address_from_key, cell_value_from_key},
 };
 
 impl Section1 {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         _file_path: &std::path::Path,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu Phần I - Thông tin chung"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(Self {
-            reporting_entity: ReportingEntity::from_excel(workbook)?,
-            responsible_person: ResponsiblePerson::from_excel(workbook)?,
-            report_preparer: ReportPreparer::from_excel(workbook)?,
+            reporting_entity: ReportingEntity::from_excel(workbook, report_form)?,
+            responsible_person: ResponsiblePerson::from_excel(workbook, report_form)?,
+            report_preparer: ReportPreparer::from_excel(workbook, report_form)?,
         })
     }
 }
 
 impl ReportingEntity {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(Self {
             name: Some(cell_value_from_key(
+ report_form,
                 "Phần I.1: Thông tin đối tượng báo cáo - Tên",
                 workbook,
             )?),
             code: Some(cell_value_from_key(
+ report_form,
                 "Phần I.1: Thông tin đối tượng báo cáo - Mã",
                 workbook,
             )?),
             address: Some(Address {
                 street_address: cell_value_from_key(
+ report_form,
                     "Phần I.1: Thông tin đối tượng báo cáo - Địa chỉ",
                     workbook,
                 )?,
                 district: cell_value_from_key(
+ report_form,
                     "Phần I.1: Thông tin đối tượng báo cáo - Phường/Xã",
                     workbook,
                 )?,
                 city_province: cell_value_from_key(
+ report_form,
                     "Phần I.1: Thông tin đối tượng báo cáo - Tỉnh/Thành phố",
                     workbook,
                 )?,
                 country: cell_value_from_key(
+ report_form,
                     "Phần I.1: Thông tin đối tượng báo cáo - Quốc gia",
                     workbook,
                 )?
                 .to_country_code()
                 .with_context(|| format!("Lỗi dữ liệu Thông tin đối tượng báo cáo - Quốc gia"))?,
                 phone: cell_value_from_key(
+ report_form,
                     "Phần I.1: Thông tin đối tượng báo cáo - Điện thoại",
                     workbook,
-                )?,
-            }),
+                )?
+                .to_phone_number(&cell_address_from_key(report_form, "Phần I.1: Thông tin đối tượng báo cáo - Điện thoại")?),
+            }
+            .normalize_admin_units()?),
             transaction_location: TransactionLocation {
                 transaction_point_name: cell_value_from_key(
+ report_form,
                     "Phần I.1: Tên điểm phát sinh giao dịch hoặc đơn vị quản lý tài khoản",
                     workbook,
                 )?,
                 street_address: cell_value_from_key(
+ report_form,
                     "Phần I.1: Địa chỉ điểm phát sinh giao dịch hoặc địa chỉ đơn vị quản lý tài khoản",
                     workbook,
                 )?,
                 district: cell_value_from_key(
+ report_form,
                     "Phần I.1: Địa chỉ điểm phát sinh giao dịch - Phường/Xã",
                     workbook,
                 )?,
                 city_province: cell_value_from_key(
+ report_form,
                     "Phần I.1: Địa chỉ điểm phát sinh giao dịch - Tỉnh/Thành phố",
                     workbook,
                 )?,
                 country: cell_value_from_key(
+ report_form,
                     "Phần I.1: Địa chỉ điểm phát sinh giao dịch - Quốc gia",
                     workbook,
                 )?
@@ -98,34 +119,47 @@ impl ReportingEntity {
                     format!("Lỗi dữ liệu Địa chỉ điểm phát sinh giao dịch - Quốc gia")
                 })?,
                 phone: cell_value_from_key(
+ report_form,
                     "Phần I.1: Địa chỉ điểm phát sinh giao dịch - Điện thoại",
                     workbook,
-                )?,
-            },
-            email: cell_value_from_key("Phần I.1: Địa chỉ email của đơn vị", workbook)?,
+                )?
+                .to_phone_number(&cell_address_from_key(report_form, "Phần I.1: Địa chỉ điểm phát sinh giao dịch - Điện thoại")?),
+            }
+            .normalize_admin_units()?,
+            email: cell_value_from_key(report_form, "Phần I.1: Địa chỉ email của đơn vị", workbook)?
+                .to_email(&cell_address_from_key(report_form, "Phần I.1: Địa chỉ email của đơn vị")?),
         })
     }
 }
 
 impl ResponsiblePerson {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(Self {
             full_name: cell_value_from_key(
+ report_form,
                 "Phần I.2: Thông tin về người chịu trách nhiệm về phòng, chống rửa tiền - Họ và tên",
                 workbook,
             )?,
             work_phone: cell_value_from_key(
+ report_form,
                 "Phần I.2: Thông tin về người chịu trách nhiệm về phòng, chống rửa tiền - Điện thoại nơi làm việc",
                 workbook,
-            )?,
+            )?
+            .to_phone_number(&cell_address_from_key(report_form, "Phần I.2: Thông tin về người chịu trách nhiệm về phòng, chống rửa tiền - Điện thoại nơi làm việc")?),
             mobile_phone: cell_value_from_key(
+ report_form,
                 "Phần I.2: Thông tin về người chịu trách nhiệm về phòng, chống rửa tiền - Điện thoại di động",
                 workbook,
-            )?,
+            )?
+            .to_phone_number(&cell_address_from_key(report_form, "Phần I.2: Thông tin về người chịu trách nhiệm về phòng, chống rửa tiền - Điện thoại di động")?),
             position: cell_value_from_key(
+ report_form,
                 "Phần I.2: Thông tin về người chịu trách nhiệm về phòng, chống rửa tiền - Chức vụ",
                 workbook,
             )?,
@@ -134,24 +168,39 @@ impl ResponsiblePerson {
 }
 
 impl ReportPreparer {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(Self {
             full_name: cell_value_from_key(
+                report_form,
                 "Phần I.2: Thông tin về người lập báo cáo - Họ và tên",
                 workbook,
             )?,
             work_phone: cell_value_from_key(
+                report_form,
                 "Phần I.2: Thông tin về người lập báo cáo - Điện thoại nơi làm việc",
                 workbook,
-            )?,
+            )?
+            .to_phone_number(&cell_address_from_key(
+                report_form,
+                "Phần I.2: Thông tin về người lập báo cáo - Điện thoại nơi làm việc",
+            )?),
             mobile_phone: cell_value_from_key(
+                report_form,
                 "Phần I.2: Thông tin về người lập báo cáo - Điện thoại di động",
                 workbook,
-            )?,
+            )?
+            .to_phone_number(&cell_address_from_key(
+                report_form,
+                "Phần I.2: Thông tin về người lập báo cáo - Điện thoại di động",
+            )?),
             department: cell_value_from_key(
+                report_form,
                 "Phần I.2: Thông tin về người lập báo cáo - Bộ phận công tác",
                 workbook,
             )?,
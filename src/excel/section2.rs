@@ -7,68 +7,99 @@ use anyhow::Context;
 
 use crate::{
     codes::{
-        account_status::AccountStatusCode, account_type::AccountTypeCode, age_range::AgeRangeCode,
+        account_status::AccountStatusCode, account_type::AccountTypeCode,
+        admin_unit::AdminUnitCode, age_range::AgeRangeCode, bank::BankCode, contact::ContactInfo,
         corporate_type::CorporateTypeCode, country::CountryCode, currency::CurrencyCode,
-        gender::GenderCode, occupation::OccupationCode, personal_id::PersonalIdCode,
+        enterprise_code::EnterpriseCodeCheck, gender::GenderCode, occupation::OccupationCode,
+        personal_id::PersonalIdCode,
+    },
+    excel::{
+        get_cell_address, get_cell_value, number_format::PercentCells, read_table_from_sheet,
+        report_date,
     },
-    excel::{get_cell_value, read_table_from_sheet},
     payload::{
         entities::{
-            Account, AddrSimple, Bank, BeneficialOwners, BenefitGroup, CodeDesc, EnterpriseCode,
-            Identification, Individual, IndividualLink, License, Occupation, Organization,
-            OrganizationLink, PersonRef, Representative,
+            Account, AddrSimple, Bank, BeneficialOwners, BenefitGroup, CodeDesc, CorporateOwner,
+            EnterpriseCode, Identification, Individual, IndividualLink, License, Occupation,
+            Organization, OrganizationLink, Ownership, PersonRef, Representative,
         },
         section2::Section2,
     },
-    template::cell_value_from_key,
-    utils::datetime::ConvertDateFormat,
+    template::{ReportForm, cell_value_from_key, table_config_from_key},
+    utils::{datetime::ConvertDateFormat, number::ConvertPercentage},
 };
 
 impl Section2 {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         _file_path: &std::path::Path,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, percent_cells, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu Phần II - Thông tin khách hàng"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(Self {
-            individuals: Individual::from_excel(workbook)?,
-            organizations: Organization::from_excel(workbook)?,
-            beneficial_owners: BeneficialOwners::from_excel(workbook)?,
-            additional_info: Some(cell_value_from_key("Phần II: Thông tin bổ sung", workbook)?),
+            individuals: Individual::from_excel(workbook, report_form)?,
+            organizations: Organization::from_excel(workbook, percent_cells, report_form)?,
+            beneficial_owners: BeneficialOwners::from_excel(workbook, percent_cells, report_form)?,
+            additional_info: Some(cell_value_from_key(
+                report_form,
+                "Phần II: Thông tin bổ sung",
+                workbook,
+            )?),
+            declared_accounts: Account::from_excel(workbook, report_form)?
+                .into_values()
+                .flatten()
+                .filter_map(|account| account.account_number)
+                .collect(),
         })
     }
 }
 
 impl Individual {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<Vec<Self>>>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel(workbook).with_context(|| format!("Lỗi xử lý tại sheet `Phần II. KHCN`"))
+        Self::_from_excel(workbook, report_form).context("Lỗi xử lý tại sheet `Phần II. KHCN`")
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<Vec<Self>>>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        let accounts = Account::from_excel(workbook)?;
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, "Phần II. KHCN")?;
+        let accounts = Account::from_excel(workbook, report_form)?;
+        let (rows, col_map, base_coord) =
+            read_table_from_sheet(workbook, "Phần II. KHCN", report_form)?;
+        let table_config = table_config_from_key(report_form, "Phần II. KHCN")?;
 
         let persons = rows
             .into_iter()
-            .map(|curr_row| -> anyhow::Result<Individual> {
+            .enumerate()
+            .map(|(n_row, curr_row)| -> anyhow::Result<Individual> {
                 let cell_value_func =
                     |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
+                let cell_address_func =
+                    |col_name: &str| get_cell_address(col_name, &table_config, n_row);
 
                 let cif_value = cell_value_func("CIF")?.unwrap_or_default();
                 let id_number = cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?;
@@ -100,7 +131,7 @@ impl Individual {
                     }
                     .into(),
                     position: None,
-                    permanent_address: Some(AddrSimple {
+                    permanent_address: AddrSimple {
                         street_address: cell_value_func("Địa chỉ đăng ký thường trú (Số nhà)")?,
                         city_province: cell_value_func("Địa chỉ đăng ký thường trú (Tỉnh/TP)")?,
                         district: cell_value_func("Địa chỉ đăng ký thường trú (Phường/Xã)")?,
@@ -108,8 +139,10 @@ impl Individual {
                             .to_country_code()?
                             .into(),
                         phone: None,
-                    }),
-                    current_address: Some(AddrSimple {
+                    }
+                    .normalize_admin_units()?
+                    .into(),
+                    current_address: AddrSimple {
                         street_address: cell_value_func("Nơi ở hiện tại (Số nhà)")?,
                         city_province: cell_value_func("Nơi ở hiện tại (Tỉnh/TP)")?,
                         district: cell_value_func("Nơi ở hiện tại (Phường/Xã)")?,
@@ -117,7 +150,9 @@ impl Individual {
                             .to_country_code()?
                             .into(),
                         phone: None,
-                    }),
+                    }
+                    .normalize_admin_units()?
+                    .into(),
                     identifications: Some(vec![Identification {
                         id_type: cell_value_func("Loại định danh")?
                             .to_personal_id_code()?
@@ -130,10 +165,12 @@ impl Individual {
                             .convert_date_vn_to_iso()?,
                         place_of_issue: cell_value_func("Nơi cấp")?,
                     }]),
-                    phone_number: cell_value_func("Số điện thoại")?,
+                    phone_number: cell_value_func("Số điện thoại")?
+                        .to_phone_number(&cell_address_func("Số điện thoại")),
                     education_level: None,
-                    email: cell_value_func("Email")?,
+                    email: cell_value_func("Email")?.to_email(&cell_address_func("Email")),
                     accounts: accounts.get(&cif_value).cloned(),
+                    ownership: None,
                 };
 
                 Ok(individual)
@@ -164,36 +201,69 @@ impl Individual {
 }
 
 impl Organization {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<Vec<Self>>>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, percent_cells, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu tại sheet `Phần II. KHTC`"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<Vec<Self>>>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        let accounts = Account::from_excel(workbook)?;
-        let rep_persons = Representative::from_excel(workbook)?;
+        let accounts = Account::from_excel(workbook, report_form)?;
+        let rep_persons = Representative::from_excel(workbook, report_form)?;
+        let report_date = report_date(workbook, report_form)?;
 
         let sheet_key = "Phần II. KHTC";
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
+        let table_config = table_config_from_key(report_form, sheet_key)?;
 
         let orgs = rows
             .into_iter()
-            .map(|curr_row| -> anyhow::Result<Organization> {
+            .enumerate()
+            .map(|(n_row, curr_row)| -> anyhow::Result<Organization> {
                 let cell_value_func =
                     |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
+                let cell_address_func =
+                    |col_name: &str| get_cell_address(col_name, &table_config, n_row);
 
                 let cif_value = cell_value_func("CIF")?.unwrap_or_default();
-                let enterprise_code = cell_value_func("MS doanh nghiệp/MS thuế")?;
+                let enterprise_code = EnterpriseCode {
+                    code: cell_value_func("MS doanh nghiệp/MS thuế")?,
+                    issue_date: cell_value_func("Ngày cấp MST (dd/mm/yyyy)")?
+                        .convert_date_vn_to_iso()?,
+                    issue_place: cell_value_func("Quốc gia cấp MST")?
+                        .to_country_code()?
+                        .into(),
+                }
+                .validate_enterprise_code(&report_date)?;
+
+                let corporate_owner = match cell_value_func("Tổ chức sở hữu (MST)")? {
+                    Some(owner_code) => CorporateOwner {
+                        enterprise_code: owner_code,
+                        percentage: cell_value_func("Tỷ lệ sở hữu của tổ chức sở hữu (%)")?
+                            .to_percentage(percent_cells.contains(&cell_address_func(
+                                "Tỷ lệ sở hữu của tổ chức sở hữu (%)",
+                            )))?,
+                    }
+                    .into(),
+                    None => None,
+                };
 
                 let org = Organization {
                     id: if cif_value.is_empty() {
-                        enterprise_code.clone()
+                        enterprise_code.code.clone()
                     } else {
                         cif_value.clone().into()
                     },
@@ -220,8 +290,10 @@ impl Organization {
                             .unwrap_or_default()
                             .to_country_code()?
                             .into(),
-                        phone: cell_value_func("Số điện thoại")?,
+                        phone: cell_value_func("Số điện thoại")?
+                            .to_phone_number(&cell_address_func("Số điện thoại")),
                     }
+                    .normalize_admin_units()?
                     .into(),
                     establishment_license: License {
                         license_number: cell_value_func("Giấy phép thành lập số")?,
@@ -229,21 +301,16 @@ impl Organization {
                             .convert_date_vn_to_iso()?,
                         issue_place: cell_value_func("Nơi cấp giấy phép")?,
                     }
+                    .validate_enterprise_code(&report_date)?
                     .into(),
-                    enterprise_code: EnterpriseCode {
-                        code: enterprise_code.clone(),
-                        issue_date: cell_value_func("Ngày cấp MST (dd/mm/yyyy)")?
-                            .convert_date_vn_to_iso()?,
-                        issue_place: cell_value_func("Quốc gia cấp MST")?
-                            .to_country_code()?
-                            .into(),
-                    }
-                    .into(),
+                    enterprise_code: enterprise_code.into(),
                     business_sector: cell_value_func("Ngành nghề kinh doanh chính")?,
-                    phone_number: cell_value_func("Số điện thoại")?,
+                    phone_number: cell_value_func("Số điện thoại")?
+                        .to_phone_number(&cell_address_func("Số điện thoại")),
                     website: cell_value_func("Địa chỉ trang thông tin điện tử của doanh nghiệp")?,
                     accounts: accounts.get(&cif_value).cloned().into(),
                     representatives: rep_persons.get(&cif_value).cloned().into(),
+                    corporate_owner,
                 };
                 Ok(org)
             })
@@ -274,22 +341,24 @@ impl Organization {
 impl Account {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<HashMap<String, Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu tại sheet `Phần II. Tài khoản`"))
     }
 
     fn _from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<HashMap<String, Vec<Self>>>
     where
         RS: Read + Seek,
     {
         let sheet_key = "Phần II. Tài khoản";
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
 
         let accounts = rows
             .into_iter()
@@ -301,11 +370,15 @@ impl Account {
 
                 let account = Account {
                     account_number: cell_value_func("Số tài khoản")?,
-                    bank: Some(Bank {
-                        bank_name: cell_value_func("Tên Ngân hàng")?,
-                        bank_code: cell_value_func("Mã Ngân hàng")?
-                            .map(|v| v.split("-").next().unwrap_or_default().trim().to_string()),
-                    }),
+                    bank: Some(
+                        Bank {
+                            bank_name: cell_value_func("Tên Ngân hàng")?,
+                            bank_code: cell_value_func("Mã Ngân hàng")?.map(|v| {
+                                v.split("-").next().unwrap_or_default().trim().to_string()
+                            }),
+                        }
+                        .complete_bank_info()?,
+                    ),
                     currency_type: cell_value_func("Loại tiền")?.to_currency_code()?.into(),
                     account_type: cell_value_func("Loại TK")?.to_account_type_code()?.into(),
                     open_date: cell_value_func("Ngày mở")?.convert_date_vn_to_iso()?,
@@ -344,81 +417,92 @@ impl Account {
 impl Representative {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<HashMap<String, Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu tại sheet `Phần II. Người đại diện`"))
     }
 
     fn _from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<HashMap<String, Vec<Self>>>
     where
         RS: Read + Seek,
     {
         let sheet_key = "Phần II. Người đại diện";
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
+        let table_config = table_config_from_key(report_form, sheet_key)?;
 
         let representatives = rows
             .into_iter()
-            .map(|curr_row| -> anyhow::Result<(String, Representative)> {
-                let cell_value_func =
-                    |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
-
-                let cif_value = cell_value_func("CIF")?.unwrap_or_default();
-
-                let rep = Representative {
-                    id: cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?,
-                    full_name: cell_value_func("Họ và tên")?,
-                    date_of_birth: cell_value_func("Ngày sinh")?.convert_date_vn_to_iso()?,
-                    occupation: Occupation {
-                        occupation_code: cell_value_func("Nghề nghiệp")?
-                            .to_occupation_code()?
-                            .into(),
-                        description: cell_value_func("Nghề nghiệp")?,
-                        content: cell_value_func("Nếu Nghề nghiệp Khác")?,
-                    }
-                    .into(),
-                    position: cell_value_func("Chức vụ/vị trí việc làm")?,
-                    permanent_address: AddrSimple {
-                        street_address: cell_value_func("Địa chỉ đăng ký thường trú (Số nhà)")?,
-                        city_province: cell_value_func("Địa chỉ đăng ký thường trú (Tỉnh/TP)")?,
-                        district: cell_value_func("Địa chỉ đăng ký thường trú (Phường/Xã)")?,
-                        country: cell_value_func("Địa chỉ đăng ký thường trú (Quốc gia)")?
-                            .to_country_code()?
-                            .into(),
-                        phone: None,
-                    }
-                    .into(),
-                    current_address: AddrSimple {
-                        street_address: cell_value_func("Nơi ở hiện tại (Số nhà)")?,
-                        city_province: cell_value_func("Nơi ở hiện tại (Tỉnh/TP)")?,
-                        district: cell_value_func("Nơi ở hiện tại (Phường/Xã)")?,
-                        country: cell_value_func("Nơi ở hiện tại (Quốc gia)")?
-                            .to_country_code()?
-                            .into(),
-                        phone: None,
-                    }
-                    .into(),
-                    phone_number: cell_value_func("Điện thoại liên lạc")?,
-                    nationality: cell_value_func("Quốc tịch")?.to_country_code()?.into(),
-                    identifications: Some(vec![Identification {
-                        id_type: cell_value_func("Loại định danh")?
-                            .to_personal_id_code()?
-                            .into(),
-                        id_number: cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?,
-                        issue_date: cell_value_func("Ngày cấp (dd/mm/yyyy)")?
-                            .convert_date_vn_to_iso()?,
-                        issuing_authority: cell_value_func("Cơ quan cấp")?,
-                        expiry_date: None,
-                        place_of_issue: cell_value_func("Nơi cấp")?,
-                    }]),
-                };
+            .enumerate()
+            .map(
+                |(n_row, curr_row)| -> anyhow::Result<(String, Representative)> {
+                    let cell_value_func =
+                        |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
+                    let cell_address_func =
+                        |col_name: &str| get_cell_address(col_name, &table_config, n_row);
+
+                    let cif_value = cell_value_func("CIF")?.unwrap_or_default();
+
+                    let rep = Representative {
+                        id: cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?,
+                        full_name: cell_value_func("Họ và tên")?,
+                        date_of_birth: cell_value_func("Ngày sinh")?.convert_date_vn_to_iso()?,
+                        occupation: Occupation {
+                            occupation_code: cell_value_func("Nghề nghiệp")?
+                                .to_occupation_code()?
+                                .into(),
+                            description: cell_value_func("Nghề nghiệp")?,
+                            content: cell_value_func("Nếu Nghề nghiệp Khác")?,
+                        }
+                        .into(),
+                        position: cell_value_func("Chức vụ/vị trí việc làm")?,
+                        permanent_address: AddrSimple {
+                            street_address: cell_value_func("Địa chỉ đăng ký thường trú (Số nhà)")?,
+                            city_province: cell_value_func("Địa chỉ đăng ký thường trú (Tỉnh/TP)")?,
+                            district: cell_value_func("Địa chỉ đăng ký thường trú (Phường/Xã)")?,
+                            country: cell_value_func("Địa chỉ đăng ký thường trú (Quốc gia)")?
+                                .to_country_code()?
+                                .into(),
+                            phone: None,
+                        }
+                        .normalize_admin_units()?
+                        .into(),
+                        current_address: AddrSimple {
+                            street_address: cell_value_func("Nơi ở hiện tại (Số nhà)")?,
+                            city_province: cell_value_func("Nơi ở hiện tại (Tỉnh/TP)")?,
+                            district: cell_value_func("Nơi ở hiện tại (Phường/Xã)")?,
+                            country: cell_value_func("Nơi ở hiện tại (Quốc gia)")?
+                                .to_country_code()?
+                                .into(),
+                            phone: None,
+                        }
+                        .normalize_admin_units()?
+                        .into(),
+                        phone_number: cell_value_func("Điện thoại liên lạc")?
+                            .to_phone_number(&cell_address_func("Điện thoại liên lạc")),
+                        nationality: cell_value_func("Quốc tịch")?.to_country_code()?.into(),
+                        identifications: Some(vec![Identification {
+                            id_type: cell_value_func("Loại định danh")?
+                                .to_personal_id_code()?
+                                .into(),
+                            id_number: cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?,
+                            issue_date: cell_value_func("Ngày cấp (dd/mm/yyyy)")?
+                                .convert_date_vn_to_iso()?,
+                            issuing_authority: cell_value_func("Cơ quan cấp")?,
+                            expiry_date: None,
+                            place_of_issue: cell_value_func("Nơi cấp")?,
+                        }]),
+                    };
 
-                Ok((cif_value, rep))
-            })
+                    Ok((cif_value, rep))
+                },
+            )
             .enumerate()
             .fold(
                 anyhow::Result::<HashMap<String, Vec<Representative>>>::Ok(Default::default()),
@@ -443,22 +527,30 @@ impl Representative {
 }
 
 impl BeneficialOwners {
-    fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<Self>>
+    fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Option<Self>>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, percent_cells, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu chủ sở hữu hưởng lợi tại Phần II"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Option<Self>>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Option<Self>>
     where
         RS: Seek + Read,
     {
-        let other_owners = other_owners_from_excel(workbook)?;
+        let other_owners = other_owners_from_excel(workbook, percent_cells, report_form)?;
         let other_owners_list = other_owners.values().cloned().flatten().collect::<Vec<_>>();
 
-        let individuals = Individual::from_excel(workbook)?.unwrap_or_default();
+        let individuals = Individual::from_excel(workbook, report_form)?.unwrap_or_default();
         let individual_links = individuals
             .into_iter()
             .map(|person| {
@@ -503,8 +595,9 @@ impl BeneficialOwners {
             })
             .collect::<Vec<_>>();
 
-        let orgs = Organization::from_excel(workbook)?.unwrap_or_default();
-        let representatives = Representative::from_excel(workbook)?;
+        let orgs =
+            Organization::from_excel(workbook, percent_cells, report_form)?.unwrap_or_default();
+        let representatives = Representative::from_excel(workbook, report_form)?;
 
         let organization_links = orgs
             .into_iter()
@@ -573,85 +666,108 @@ impl BeneficialOwners {
 
 fn other_owners_from_excel<RS>(
     workbook: &mut calamine::Xlsx<RS>,
+    percent_cells: &PercentCells,
+    report_form: &ReportForm,
 ) -> anyhow::Result<HashMap<String, Vec<Individual>>>
 where
     RS: Seek + Read,
 {
-    _other_owners_from_excel(workbook)
+    _other_owners_from_excel(workbook, percent_cells, report_form)
         .with_context(|| format!("Lỗi khi xử lý dữ liệu tại sheet `Phần II. CSHHL khác`"))
 }
 
 fn _other_owners_from_excel<RS>(
     workbook: &mut calamine::Xlsx<RS>,
+    percent_cells: &PercentCells,
+    report_form: &ReportForm,
 ) -> anyhow::Result<HashMap<String, Vec<Individual>>>
 where
     RS: Seek + Read,
 {
     let sheet_key = "Phần II. CSHHL khác";
-    let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+    let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
+    let table_config = table_config_from_key(report_form, sheet_key)?;
 
     let beneficiaries = rows
         .into_iter()
-        .map(|curr_row| -> anyhow::Result<(String, Individual)> {
-            let cell_value_func =
-                |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
-
-            let cif_value = cell_value_func("CIF")?.unwrap_or_default();
-
-            let rep = Individual {
-                existing_customer: None,
-                id: cif_value.clone().into(),
-                full_name: cell_value_func("Họ và tên")?,
-                date_of_birth: cell_value_func("Ngày sinh")?.convert_date_vn_to_iso()?,
-                age_range: None,
-                gender: cell_value_func("Giới tính")?.to_gender_code()?.into(),
-                nationality: cell_value_func("Quốc tịch")?.to_country_code()?.into(),
-                occupation: Occupation {
-                    occupation_code: cell_value_func("Nghề nghiệp")?.to_occupation_code()?.into(),
-                    description: cell_value_func("Nghề nghiệp")?,
-                    content: cell_value_func("Nếu Nghề nghiệp Khác")?,
-                }
-                .into(),
-                position: cell_value_func("Chức vụ/vị trí việc làm")?,
-                permanent_address: AddrSimple {
-                    street_address: cell_value_func("Địa chỉ đăng ký thường trú (Số nhà)")?,
-                    city_province: cell_value_func("Địa chỉ đăng ký thường trú (Tỉnh/TP)")?,
-                    district: cell_value_func("Địa chỉ đăng ký thường trú (Phường/Xã)")?,
-                    country: cell_value_func("Địa chỉ đăng ký thường trú (Quốc gia)")?
-                        .to_country_code()?
-                        .into(),
-                    phone: None,
-                }
-                .into(),
-                current_address: AddrSimple {
-                    street_address: cell_value_func("Nơi ở hiện tại (Số nhà)")?,
-                    city_province: cell_value_func("Nơi ở hiện tại (Tỉnh/TP)")?,
-                    district: cell_value_func("Nơi ở hiện tại (Phường/Xã)")?,
-                    country: cell_value_func("Nơi ở hiện tại (Quốc gia)")?
-                        .to_country_code()?
-                        .into(),
-                    phone: None,
-                }
-                .into(),
-                phone_number: cell_value_func("Điện thoại liên lạc")?,
-                identifications: Some(vec![Identification {
-                    id_type: cell_value_func("Loại định danh")?
-                        .to_personal_id_code()?
-                        .into(),
-                    id_number: cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?,
-                    issue_date: cell_value_func("Ngày cấp (dd/mm/yyyy)")?
-                        .convert_date_vn_to_iso()?,
-                    issuing_authority: cell_value_func("Cơ quan cấp")?,
-                    expiry_date: None,
-                    place_of_issue: cell_value_func("Nơi cấp")?,
-                }]),
-                education_level: None,
-                email: None,
-                accounts: None,
-            };
-
-            Ok((cif_value, rep))
-        })
+        .enumerate()
+        .map(
+            |(n_row, curr_row)| -> anyhow::Result<(String, Individual)> {
+                let cell_value_func =
+                    |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
+                let cell_address_func =
+                    |col_name: &str| get_cell_address(col_name, &table_config, n_row);
+
+                let cif_value = cell_value_func("CIF")?.unwrap_or_default();
+
+                let rep = Individual {
+                    existing_customer: None,
+                    id: cif_value.clone().into(),
+                    full_name: cell_value_func("Họ và tên")?,
+                    date_of_birth: cell_value_func("Ngày sinh")?.convert_date_vn_to_iso()?,
+                    age_range: None,
+                    gender: cell_value_func("Giới tính")?.to_gender_code()?.into(),
+                    nationality: cell_value_func("Quốc tịch")?.to_country_code()?.into(),
+                    occupation: Occupation {
+                        occupation_code: cell_value_func("Nghề nghiệp")?
+                            .to_occupation_code()?
+                            .into(),
+                        description: cell_value_func("Nghề nghiệp")?,
+                        content: cell_value_func("Nếu Nghề nghiệp Khác")?,
+                    }
+                    .into(),
+                    position: cell_value_func("Chức vụ/vị trí việc làm")?,
+                    permanent_address: AddrSimple {
+                        street_address: cell_value_func("Địa chỉ đăng ký thường trú (Số nhà)")?,
+                        city_province: cell_value_func("Địa chỉ đăng ký thường trú (Tỉnh/TP)")?,
+                        district: cell_value_func("Địa chỉ đăng ký thường trú (Phường/Xã)")?,
+                        country: cell_value_func("Địa chỉ đăng ký thường trú (Quốc gia)")?
+                            .to_country_code()?
+                            .into(),
+                        phone: None,
+                    }
+                    .normalize_admin_units()?
+                    .into(),
+                    current_address: AddrSimple {
+                        street_address: cell_value_func("Nơi ở hiện tại (Số nhà)")?,
+                        city_province: cell_value_func("Nơi ở hiện tại (Tỉnh/TP)")?,
+                        district: cell_value_func("Nơi ở hiện tại (Phường/Xã)")?,
+                        country: cell_value_func("Nơi ở hiện tại (Quốc gia)")?
+                            .to_country_code()?
+                            .into(),
+                        phone: None,
+                    }
+                    .normalize_admin_units()?
+                    .into(),
+                    phone_number: cell_value_func("Điện thoại liên lạc")?
+                        .to_phone_number(&cell_address_func("Điện thoại liên lạc")),
+                    identifications: Some(vec![Identification {
+                        id_type: cell_value_func("Loại định danh")?
+                            .to_personal_id_code()?
+                            .into(),
+                        id_number: cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?,
+                        issue_date: cell_value_func("Ngày cấp (dd/mm/yyyy)")?
+                            .convert_date_vn_to_iso()?,
+                        issuing_authority: cell_value_func("Cơ quan cấp")?,
+                        expiry_date: None,
+                        place_of_issue: cell_value_func("Nơi cấp")?,
+                    }]),
+                    education_level: None,
+                    email: None,
+                    accounts: None,
+                    ownership: Ownership {
+                        through: cell_value_func("Sở hữu thông qua tổ chức (MST)")?,
+                        percentage: cell_value_func("Tỷ lệ sở hữu (%)")?.to_percentage(
+                            percent_cells.contains(&cell_address_func("Tỷ lệ sở hữu (%)")),
+                        )?,
+                        control_basis: cell_value_func("Cơ sở kiểm soát")?,
+                    }
+                    .into(),
+                };
+
+                Ok((cif_value, rep))
+            },
+        )
         .enumerate()
         .fold(
             anyhow::Result::<HashMap<String, Vec<Individual>>>::Ok(Default::default()),
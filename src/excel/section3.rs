@@ -7,11 +7,12 @@ use anyhow::Context;
 
 use crate::{
     codes::{
-        account_status::AccountStatusCode, account_type::AccountTypeCode, age_range::AgeRangeCode,
-        country::CountryCode, currency::CurrencyCode, gender::GenderCode,
-        occupation::OccupationCode, personal_id::PersonalIdCode,
+        account_status::AccountStatusCode, account_type::AccountTypeCode,
+        admin_unit::AdminUnitCode, age_range::AgeRangeCode, bank::BankCode, contact::ContactInfo,
+        country::CountryCode, currency::CurrencyCode, enterprise_code::EnterpriseCodeCheck,
+        gender::GenderCode, occupation::OccupationCode, personal_id::PersonalIdCode,
     },
-    excel::{get_cell_value, read_table_from_sheet},
+    excel::{get_cell_address, get_cell_value, read_table_from_sheet, report_date},
     payload::{
         entities::{
             Account, AddrSimple, Bank, CodeDesc, EnterpriseCode, Identification, Individual,
@@ -19,7 +20,7 @@ use crate::{
         },
         section3::Section3,
     },
-    template::cell_value_from_key,
+    template::{ReportForm, cell_value_from_key, table_config_from_key},
     utils::datetime::ConvertDateFormat,
 };
 
@@ -27,22 +28,31 @@ impl Section3 {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         _file_path: &std::path::Path,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu Phần II - Thông tin khách hàng"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         Ok(Self {
-            related_individuals: Individual::from_excel_related_party(workbook)?,
-            related_organizations: Organization::from_excel_related_party(workbook)?,
-            additional_info: cell_value_from_key("Phần II: Thông tin bổ sung", workbook)?.into(),
+            related_individuals: Individual::from_excel_related_party(workbook, report_form)?,
+            related_organizations: Organization::from_excel_related_party(workbook, report_form)?,
+            additional_info: cell_value_from_key(
+                report_form,
+                "Phần II: Thông tin bổ sung",
+                workbook,
+            )?
+            .into(),
         })
     }
 }
@@ -50,29 +60,35 @@ impl Section3 {
 impl Individual {
     pub fn from_excel_related_party<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel_related_party(workbook)
+        Self::_from_excel_related_party(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý tại sheet `Phần II. KHCN`"))
     }
 
     fn _from_excel_related_party<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
         let sheet_key = "Phần III. CN liên quan";
-        let accounts = Account::from_excel_related_party(workbook)?;
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+        let accounts = Account::from_excel_related_party(workbook, report_form)?;
+        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
+        let table_config = table_config_from_key(report_form, sheet_key)?;
 
         let persons = rows
             .into_iter()
-            .map(|curr_row| -> anyhow::Result<Individual> {
+            .enumerate()
+            .map(|(n_row, curr_row)| -> anyhow::Result<Individual> {
                 let cell_value_func =
                     |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
+                let cell_address_func =
+                    |col_name: &str| get_cell_address(col_name, &table_config, n_row);
 
                 let id_number =
                     cell_value_func("CMND/CCCD/Hộ chiếu/Định danh cá nhân")?.unwrap_or_default();
@@ -96,7 +112,7 @@ impl Individual {
                     }
                     .into(),
                     position: None,
-                    permanent_address: Some(AddrSimple {
+                    permanent_address: AddrSimple {
                         street_address: cell_value_func("Địa chỉ đăng ký thường trú (Số nhà)")?,
                         city_province: cell_value_func("Địa chỉ đăng ký thường trú (Tỉnh/TP)")?,
                         district: cell_value_func("Địa chỉ đăng ký thường trú (Phường/Xã)")?,
@@ -104,8 +120,10 @@ impl Individual {
                             .to_country_code()?
                             .into(),
                         phone: None,
-                    }),
-                    current_address: Some(AddrSimple {
+                    }
+                    .normalize_admin_units()?
+                    .into(),
+                    current_address: AddrSimple {
                         street_address: cell_value_func("Nơi ở hiện tại (Số nhà)")?,
                         city_province: cell_value_func("Nơi ở hiện tại (Tỉnh/TP)")?,
                         district: cell_value_func("Nơi ở hiện tại (Phường/Xã)")?,
@@ -113,7 +131,9 @@ impl Individual {
                             .to_country_code()?
                             .into(),
                         phone: None,
-                    }),
+                    }
+                    .normalize_admin_units()?
+                    .into(),
                     identifications: Some(vec![Identification {
                         id_type: cell_value_func("Loại định danh")?
                             .to_personal_id_code()?
@@ -126,10 +146,12 @@ impl Individual {
                             .convert_date_vn_to_iso()?,
                         place_of_issue: cell_value_func("Nơi cấp")?,
                     }]),
-                    phone_number: cell_value_func("Số điện thoại")?,
+                    phone_number: cell_value_func("Số điện thoại")?
+                        .to_phone_number(&cell_address_func("Số điện thoại")),
                     education_level: None,
                     email: None,
                     accounts: accounts.get(&id_number).cloned(),
+                    ownership: None,
                 };
 
                 Ok(individual)
@@ -162,30 +184,37 @@ impl Individual {
 impl Organization {
     pub fn from_excel_related_party<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel_related_party(workbook)
+        Self::_from_excel_related_party(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu tại sheet `Phần II. KHTC`"))
     }
 
     fn _from_excel_related_party<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Option<Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        let accounts = Account::from_excel_related_party(workbook)?;
+        let accounts = Account::from_excel_related_party(workbook, report_form)?;
+        let report_date = report_date(workbook, report_form)?;
 
         let sheet_key = "Phần III. TC liên quan";
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
+        let table_config = table_config_from_key(report_form, sheet_key)?;
 
         let orgs = rows
             .into_iter()
-            .map(|curr_row| -> anyhow::Result<Organization> {
+            .enumerate()
+            .map(|(n_row, curr_row)| -> anyhow::Result<Organization> {
                 let cell_value_func =
                     |col_name: &str| get_cell_value(col_name, &col_map, base_coord, &curr_row);
+                let cell_address_func =
+                    |col_name: &str| get_cell_address(col_name, &table_config, n_row);
 
                 let enterprise_code =
                     cell_value_func("MS doanh nghiệp/MS thuế")?.unwrap_or_default();
@@ -209,8 +238,10 @@ impl Organization {
                             .unwrap_or_default()
                             .to_country_code()?
                             .into(),
-                        phone: cell_value_func("Số điện thoại")?,
+                        phone: cell_value_func("Số điện thoại")?
+                            .to_phone_number(&cell_address_func("Số điện thoại")),
                     }
+                    .normalize_admin_units()?
                     .into(),
                     establishment_license: License {
                         license_number: cell_value_func("Giấy phép thành lập số")?,
@@ -218,6 +249,7 @@ impl Organization {
                             .convert_date_vn_to_iso()?,
                         issue_place: cell_value_func("Nơi cấp giấy phép")?,
                     }
+                    .validate_enterprise_code(&report_date)?
                     .into(),
                     enterprise_code: EnterpriseCode {
                         code: cell_value_func("MS doanh nghiệp/MS thuế")?,
@@ -227,12 +259,15 @@ impl Organization {
                             .to_country_code()?
                             .into(),
                     }
+                    .validate_enterprise_code(&report_date)?
                     .into(),
                     business_sector: cell_value_func("Ngành nghề kinh doanh chính")?,
-                    phone_number: cell_value_func("Số điện thoại")?,
+                    phone_number: cell_value_func("Số điện thoại")?
+                        .to_phone_number(&cell_address_func("Số điện thoại")),
                     website: cell_value_func("Địa chỉ trang thông tin điện tử của doanh nghiệp")?,
                     accounts: accounts.get(&enterprise_code).cloned().into(),
                     representatives: None,
+                    corporate_owner: None,
                 };
                 Ok(org)
             })
@@ -263,22 +298,24 @@ impl Organization {
 impl Account {
     pub fn from_excel_related_party<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<HashMap<String, Vec<Self>>>
     where
         RS: Read + Seek,
     {
-        Self::_from_excel_related_party(workbook)
+        Self::_from_excel_related_party(workbook, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu tại sheet `Phần II. Tài khoản`"))
     }
 
     fn _from_excel_related_party<RS>(
         workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
     ) -> anyhow::Result<HashMap<String, Vec<Self>>>
     where
         RS: Read + Seek,
     {
         let sheet_key = "Phần III. Tài khoản liên quan";
-        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key)?;
+        let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
 
         let accounts = rows
             .into_iter()
@@ -290,11 +327,15 @@ impl Account {
 
                 let account = Account {
                     account_number: cell_value_func("Số tài khoản")?,
-                    bank: Some(Bank {
-                        bank_name: cell_value_func("Tên Ngân hàng")?,
-                        bank_code: cell_value_func("Mã Ngân hàng")?
-                            .map(|v| v.split("-").next().unwrap_or_default().trim().to_string()),
-                    }),
+                    bank: Some(
+                        Bank {
+                            bank_name: cell_value_func("Tên Ngân hàng")?,
+                            bank_code: cell_value_func("Mã Ngân hàng")?.map(|v| {
+                                v.split("-").next().unwrap_or_default().trim().to_string()
+                            }),
+                        }
+                        .complete_bank_info()?,
+                    ),
                     currency_type: cell_value_func("Loại tiền")?.to_currency_code()?.into(),
                     account_type: cell_value_func("Loại TK")?.to_account_type_code()?.into(),
                     open_date: cell_value_func("Ngày mở")?.convert_date_vn_to_iso()?,
@@ -7,8 +7,15 @@ use anyhow::{Context, Ok};
 use calamine::{DataType, Reader};
 
 use crate::{
-    codes::currency::CurrencyCode,
+    codes::{bank::BankCode, currency::CurrencyCode},
+    excel::{
+        attachment_folder,
+        checkbox::{CheckState, Checkboxes, is_selected},
+        number_format::PercentCells,
+    },
+    narrative::{find_narrative_file, read_narrative_file},
     payload::{
+        code::{ClauseType, CrimeType, Currency, IndicatorType, LegalBasisType, TransactionStatus},
         entities::{Account, Individual, Organization},
         section4::{
             AmountEntry, Analysis, Clause, ConclusionEntry, FlowEntryIn, FlowEntryOut, LegalBasis,
@@ -16,80 +23,112 @@ use crate::{
         },
     },
     template::{
-        cell_value_from_key, legal_basis_mapping_from_key, mapping_from_key, table_config_from_key,
-        value_list_from_key,
+        ReportForm, cell_address_from_key, cell_value_from_key, legal_basis_mapping_from_key,
+        mapping_from_key, table_config_from_key, value_from_key, value_list_from_key,
     },
     utils::{
         datetime::ConvertDateFormat,
-        excel::{col_name_to_index, read_cell_value},
+        excel::{
+            ExcelCoord, cells_below_range, col_name_to_index, defined_name_range, read_cell_value,
+            read_range_values,
+        },
+        number,
     },
 };
 
 impl Section4 {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
-        _file_path: &std::path::Path,
+        file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, file_path, checkboxes, percent_cells, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu Phần IV - Thông tin về giao dịch đáng ngờ"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        let detection_date =
-            cell_value_from_key("Phần IV: Ngày phát hiện giao dịch đáng ngờ", workbook)?
-                .convert_date_vn_to_iso()
-                .with_context(|| {
-                    format!("Lỗi dữ liệu Phần IV - Mục 6. Ngày phát hiện giao dịch đáng ngờ")
-                })?;
+        let detection_date = cell_value_from_key(
+            report_form,
+            "Phần IV: Ngày phát hiện giao dịch đáng ngờ",
+            workbook,
+        )?
+        .convert_date_vn_to_iso()
+        .context("Lỗi dữ liệu Phần IV - Mục 6. Ngày phát hiện giao dịch đáng ngờ")?;
 
         Ok(Section4 {
-            report_type: ReportType::from_excel(workbook)?.into(),
-            transaction_info: TransactionInfo::from_excel(workbook)?.into(),
-            analysis: Analysis::from_excel(workbook)?.into(),
-            conclusions: ConclusionEntry::from_excel(workbook)?.into(),
+            report_type: ReportType::from_excel(workbook, file_path, checkboxes, report_form)?
+                .into(),
+            transaction_info: TransactionInfo::from_excel(
+                workbook,
+                file_path,
+                checkboxes,
+                percent_cells,
+                report_form,
+            )?
+            .into(),
+            analysis: Analysis::from_excel(workbook, file_path, report_form)?.into(),
+            conclusions: ConclusionEntry::from_excel(workbook, file_path, checkboxes, report_form)?
+                .into(),
             detection_date: detection_date,
         })
     }
 }
 
 impl ReportType {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        _file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook).with_context(|| {
+        Self::_from_excel(workbook, _file_path, checkboxes, report_form).with_context(|| {
             format!("Lỗi xử lý dữ liệu Phần IV - Mục 1 - Loại báo cáo giao dịch đáng ngờ")
         })
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        _file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        let sheet_name = cell_value_from_key("Phần IV: Thông tin về giao dịch đáng ngờ", workbook)?;
-        let checked_box = cell_value_from_key("Dấu tick", workbook)?;
+        let sheet_name = cell_value_from_key(
+            report_form,
+            "Phần IV: Thông tin về giao dịch đáng ngờ",
+            workbook,
+        )?;
         let range = workbook.worksheet_range(&sheet_name)?;
 
         let selection = range
             .rows()
-            .into_iter()
-            .map(|row| {
+            .enumerate()
+            .map(|(row_idx, row)| {
                 let key = row
                     .get(0)
                     .map(|c| c.get_string().unwrap_or_default().trim().to_string())
                     .unwrap_or(Default::default());
 
-                let value = row
-                    .get(1)
-                    .map(|c| c.get_string().unwrap_or_default().trim().to_string())
-                    .map(|c| c == checked_box)
-                    .unwrap_or_default();
+                let value = checkboxes.row_state(&sheet_name, &range, row_idx);
 
                 let other_content = row
                     .get(2)
@@ -105,28 +144,30 @@ impl ReportType {
             .collect::<HashMap<_, _>>();
 
         let report_key = "Phần IV: Loại báo cáo giao dịch đáng ngờ";
-        let reports = mapping_from_key(report_key)?
+        let reports = mapping_from_key(report_form, report_key)?
             .into_iter()
-            .filter(|(k, _)| selection.get(k).map(|res| res.0).unwrap_or(false))
-            .map(|(k, v)| Clause {
-                code: k.into(),
-                description: v.into(),
+            .filter(|(k, v)| is_selected(selection.get(k).map(|res| &res.0), v))
+            .map(|(k, v)| {
+                Ok(Clause {
+                    code: ClauseType::for_form(report_form, k)?.into(),
+                    description: v.into(),
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         let indicator_key = "Phần IV: Dấu hiệu đáng ngờ";
-        let indicators = mapping_from_key(indicator_key)?
+        let indicators = mapping_from_key(report_form, indicator_key)?
             .into_iter()
-            .filter(|(k, _)| selection.get(k).map(|res| res.0).unwrap_or(false))
+            .filter(|(k, v)| is_selected(selection.get(k).map(|res| &res.0), v))
             .map(|(k, v)| {
                 let desc_key = format!("{}_desc", k);
-                SuspiciousIndicator {
-                    code: k.into(),
+                Ok(SuspiciousIndicator {
+                    code: IndicatorType::for_form(report_form, k)?.into(),
                     description: v.into(),
                     other_content: selection.get(&desc_key).map(|value| value.1.clone()),
-                }
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         Ok(Self {
             clauses: reports.into(),
@@ -136,62 +177,112 @@ impl ReportType {
 }
 
 impl Analysis {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        file_path: &std::path::Path,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook).with_context(|| {
+        Self::_from_excel(workbook, file_path, report_form).with_context(|| {
             format!("Lỗi xử lý dữ liệu Phần IV - Mục 2 - Thông tin về giao dịch đáng ngờ")
         })
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        file_path: &std::path::Path,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
         let sheet_key = "Phần IV: Thông tin về giao dịch đáng ngờ";
-        let sheet_name = cell_value_from_key(sheet_key, workbook)?;
+        let sheet_name = cell_value_from_key(report_form, sheet_key, workbook)?;
 
         let detail_key = "Phần IV: Mô tả, phân tích chi tiết";
-        let detail_analysis = value_list_from_key(detail_key)?
-            .into_iter()
-            .map(|cell_name| read_cell_value(workbook, &sheet_name, &cell_name).unwrap_or_default())
-            .filter(|v| !v.is_empty())
-            .fold(String::new(), |mut acc, s| {
-                if !acc.is_empty() {
-                    acc.push_str("\n");
+        let named_range_key = "Phần IV: Mô tả, phân tích chi tiết - Tên vùng";
+
+        // A named range in the workbook takes precedence over the cells listed in the template
+        let named_range =
+            defined_name_range(workbook, &value_from_key(report_form, named_range_key)?);
+        let detail_cells = match named_range {
+            Some((sheet, range_ref)) => vec![(sheet, range_ref)],
+            None => {
+                let detail_cells = value_list_from_key(report_form, detail_key)?
+                    .into_iter()
+                    .map(|range_ref| (sheet_name.clone(), range_ref))
+                    .collect::<Vec<_>>();
+                if let Some((sheet, range_ref)) = detail_cells.last() {
+                    let ignored_cells = cells_below_range(workbook, sheet, range_ref)?;
+                    if !ignored_cells.is_empty() {
+                        log::warn!(
+                            "Phần mô tả, phân tích chi tiết chỉ được đọc trong vùng {} của sheet `{}`, các ô {} ngay bên dưới có nội dung nhưng không được đọc. Đặt tên vùng '{}' cho toàn bộ phần mô tả hoặc dùng file mô tả trong thư mục tài liệu đính kèm nếu đây là phần tiếp theo của mô tả",
+                            range_ref,
+                            sheet,
+                            ignored_cells.join(", "),
+                            value_from_key(report_form, named_range_key)?
+                        );
+                    }
                 }
-                acc.push_str(&s);
-                acc
-            });
+                detail_cells
+            }
+        };
+
+        let detail_analysis = detail_cells
+            .iter()
+            .map(|(sheet, range_ref)| read_range_values(workbook, sheet, range_ref))
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .concat()
+            .join("\n");
+
+        let detail_analysis = match find_narrative_file(&attachment_folder(file_path)) {
+            Some(narrative_file) => {
+                if !detail_analysis.is_empty() {
+                    log::warn!(
+                        "Phần mô tả, phân tích chi tiết trong file Excel được thay thế bởi nội dung file {:#?}",
+                        narrative_file
+                    );
+                }
+                read_narrative_file(&narrative_file)?
+            }
+            None => detail_analysis,
+        };
 
         Ok(Analysis {
             detail: detail_analysis.into(),
-            legal_bases: LegalBasis::from_excel(workbook)?.into(),
+            legal_bases: LegalBasis::from_excel(workbook, report_form)?.into(),
         })
     }
 }
 
 impl LegalBasis {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Vec<Self>>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Vec<Self>>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook).with_context(|| {
+        Self::_from_excel(workbook, report_form).with_context(|| {
             format!("Lỗi xử lý dữ liệu Phần IV - Mục 4 - Cơ sở hợp lý để nghi ngờ")
         })
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Vec<Self>>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Vec<Self>>
     where
         RS: Seek + Read,
     {
         let sheet_key = "Phần IV: Thông tin về giao dịch đáng ngờ";
-        let sheet_name = cell_value_from_key(sheet_key, workbook)?;
+        let sheet_name = cell_value_from_key(report_form, sheet_key, workbook)?;
 
         let legal_basis_key = "Phần IV: Cơ sở hợp lý để nghi ngờ";
 
-        let legal_basis = legal_basis_mapping_from_key(legal_basis_key)?
+        let legal_basis = legal_basis_mapping_from_key(report_form, legal_basis_key)?
             .into_iter()
             .map(|(field, basis)| {
                 let notice_number = basis
@@ -206,43 +297,43 @@ impl LegalBasis {
                     .map(|s| if s.is_empty() { None } else { Some(s) })
                     .flatten();
 
-                LegalBasis {
-                    report_type: field.into(),
+                Ok(LegalBasis {
+                    report_type: LegalBasisType::for_form(report_form, field)?.into(),
                     notice_number: notice_number,
                     basis: basis_text,
-                }
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         Ok(legal_basis)
     }
 }
 
 impl ConclusionEntry {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Vec<Self>>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        _file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Vec<Self>>
     where
         RS: Seek + Read,
     {
         let sheet_key = "Phần IV: Thông tin về giao dịch đáng ngờ";
-        let sheet_name = cell_value_from_key(sheet_key, workbook)?;
+        let sheet_name = cell_value_from_key(report_form, sheet_key, workbook)?;
 
-        let checked_box = cell_value_from_key("Dấu tick", workbook)?;
         let range = workbook.worksheet_range(&sheet_name)?;
 
         let selection = range
             .rows()
-            .into_iter()
-            .map(|row| {
+            .enumerate()
+            .map(|(row_idx, row)| {
                 let crime_code = row
                     .get(0)
                     .map(|c| c.get_string().unwrap_or_default().trim().to_string())
                     .unwrap_or(Default::default());
 
-                let is_selected = row
-                    .get(1)
-                    .map(|c| c.get_string().unwrap_or_default().trim().to_string())
-                    .map(|c| c == checked_box)
-                    .unwrap_or_default();
+                let is_selected = checkboxes.row_state(&sheet_name, &range, row_idx);
 
                 let other_content = row
                     .get(2)
@@ -252,66 +343,90 @@ impl ConclusionEntry {
                 (crime_code, (is_selected, other_content))
             })
             .filter(|(crime_code, (is_selected, other_content))| {
-                !crime_code.is_empty() && (*is_selected || !other_content.is_empty())
+                !crime_code.is_empty()
+                    && (*is_selected != CheckState::Unchecked || !other_content.is_empty())
             })
             .collect::<HashMap<_, _>>();
 
         let conclusion_key =
             "Phần IV: Nhận định về loại tội phạm có thể liên quan đến giao dịch đáng ngờ";
 
-        let conclusions = mapping_from_key(conclusion_key)?
+        let conclusions = mapping_from_key(report_form, conclusion_key)?
             .into_iter()
-            .filter(|(k, _)| selection.get(k).map(|(v, _)| v).copied().unwrap_or(false))
+            .filter(|(k, v)| is_selected(selection.get(k).map(|(state, _)| state), v))
             .map(|(crime_code, crime_desc)| {
                 let crime_desc_key = format!("{}_desc", crime_code);
                 let other_content = selection.get(&crime_desc_key).map(|(_, v)| v).cloned();
-                ConclusionEntry {
-                    crime_code: crime_code.into(),
+                Ok(ConclusionEntry {
+                    crime_code: CrimeType::for_form(report_form, crime_code)?.into(),
                     description: crime_desc.into(),
                     other_content: other_content,
-                }
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         Ok(conclusions)
     }
 }
 
 impl TransactionInfo {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        _file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook).with_context(|| {
+        Self::_from_excel(workbook, _file_path, checkboxes, percent_cells, report_form).with_context(|| {
             format!("Lỗi xử lý dữ liệu Phần IV - Mục 2.2 - Thông tin về giao dịch đáng ngờ đã được thực hiện")
         })
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Self>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        _file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        let checked_box = cell_value_from_key("Dấu tick", workbook)?;
-        let status_value =
-            cell_value_from_key("Phần IV: Trạng thái của giao dịch đáng ngờ", workbook)?;
-        let status = match status_value == checked_box {
-            true => "1".to_string().into(),
+        let status_key = "Phần IV: Trạng thái của giao dịch đáng ngờ";
+        let status_addr = cell_address_from_key(report_form, status_key)?;
+        let status_range = workbook.worksheet_range(&status_addr.sheet)?;
+        let status_coord = ExcelCoord::from_a1_style(&status_addr.cell)
+            .with_context(|| format!("Địa chỉ ô '{}' không hợp lệ", status_addr.cell))?;
+        let status_state = checkboxes.state(
+            &status_addr.sheet,
+            (status_coord.row, status_coord.col),
+            status_range.get_value((status_coord.row, status_coord.col)),
+        );
+        status_state.warn_if_unrecognized(status_key);
+
+        let status = match status_state.is_checked() {
+            true => TransactionStatus::new("1")?.into(),
             false => None,
         };
 
         let from_date = cell_value_from_key(
+            report_form,
             "Phần IV: Thông tin về giao dịch đáng ngờ - Từ ngày",
             workbook,
         )?
         .convert_date_vn_to_iso()?;
 
         let to_date = cell_value_from_key(
+            report_form,
             "Phần IV: Thông tin về giao dịch đáng ngờ - Đến ngày",
             workbook,
         )?
         .convert_date_vn_to_iso()?;
 
-        let moneyflow_details = MoneyFlow::from_excel(workbook)?;
+        let moneyflow_details = MoneyFlow::from_excel(workbook, percent_cells, report_form)?;
 
         let amount_by_currency = moneyflow_details
             .iter()
@@ -339,14 +454,11 @@ impl TransactionInfo {
             })
             .flatten()
             .fold(
-                HashMap::<String, f64>::new(),
+                HashMap::<Currency, f64>::new(),
                 |mut acc, (currency_opt, amount_opt)| {
                     let currency = currency_opt.cloned().unwrap_or_default();
                     let original_amount = amount_opt
-                        .cloned()
-                        .unwrap_or_default()
-                        .parse::<f64>()
-                        .ok()
+                        .and_then(|amount| number::parse_amount(amount))
                         .unwrap_or(0.0);
 
                     *acc.entry(currency).or_insert(0.0) += original_amount;
@@ -366,15 +478,13 @@ impl TransactionInfo {
                 let total_in = flow
                     .total_converted_in
                     .as_ref()
-                    .map(|v| v.parse::<f64>().ok())
-                    .flatten()
+                    .and_then(|v| number::parse_amount(v))
                     .unwrap_or_default();
 
                 let total_out = flow
                     .total_converted_out
                     .as_ref()
-                    .map(|v| v.parse::<f64>().ok())
-                    .flatten()
+                    .and_then(|v| number::parse_amount(v))
                     .unwrap_or_default();
 
                 total_in + total_out
@@ -420,11 +530,12 @@ fn get_cell_value(
 fn read_table_from_sheet<RS>(
     workbook: &mut calamine::Xlsx<RS>,
     sheet_key: &str,
+    report_form: &ReportForm,
 ) -> anyhow::Result<(Vec<Vec<String>>, HashMap<String, String>, (u32, u32))>
 where
     RS: Seek + Read,
 {
-    let table_config = table_config_from_key(sheet_key)?;
+    let table_config = table_config_from_key(report_form, sheet_key)?;
     let range = workbook.worksheet_range(&table_config.sheet)?;
     let header_row_idx = table_config.header_row - range.start().unwrap_or_default().0 - 1;
     let end_row_idx = {
@@ -466,15 +577,23 @@ where
 }
 
 impl MoneyFlow {
-    pub fn from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Vec<Self>>
+    pub fn from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Vec<Self>>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook)
+        Self::_from_excel(workbook, percent_cells, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu Phần IV - Thông tin về giao dịch đáng ngờ"))
     }
 
-    fn _from_excel<RS>(workbook: &mut calamine::Xlsx<RS>) -> anyhow::Result<Vec<Self>>
+    fn _from_excel<RS>(
+        workbook: &mut calamine::Xlsx<RS>,
+        percent_cells: &PercentCells,
+        report_form: &ReportForm,
+    ) -> anyhow::Result<Vec<Self>>
     where
         RS: Seek + Read,
     {
@@ -482,7 +601,7 @@ impl MoneyFlow {
         let outflow_sheet_key = "Phần IV. Ghi Nợ";
 
         let bank_accounts = {
-            Account::from_excel(workbook)?
+            Account::from_excel(workbook, report_form)?
                 .into_iter()
                 .map(|(cif, accounts)| {
                     accounts.into_iter().map(move |account| {
@@ -496,8 +615,9 @@ impl MoneyFlow {
         };
 
         let customer_infos = {
-            let persons = Individual::from_excel(workbook)?.unwrap_or_default();
-            let orgs = Organization::from_excel(workbook)?.unwrap_or_default();
+            let persons = Individual::from_excel(workbook, report_form)?.unwrap_or_default();
+            let orgs =
+                Organization::from_excel(workbook, percent_cells, report_form)?.unwrap_or_default();
 
             let person_ids = persons.iter().map(|p| {
                 let cif = p.id.clone();
@@ -548,7 +668,7 @@ impl MoneyFlow {
         };
 
         let (inflow_rows, inflow_columns, inflow_base_coord) =
-            read_table_from_sheet(workbook, inflow_sheet_key)?;
+            read_table_from_sheet(workbook, inflow_sheet_key, report_form)?;
 
         let mut inflow_entries = inflow_rows
             .into_iter()
@@ -574,7 +694,8 @@ impl MoneyFlow {
                         tx_to: cell_value_func("Giao dịch đến ngày").convert_date_vn_to_iso()?,
                         currency: cell_value_func("Loại tiền").to_currency_code()?.into(),
                         content: cell_value_func("Tóm tắt nội dung giao dịch"),
-                    };
+                    }
+                    .complete_bank_info()?;
 
                     Ok((cif, account_number, entry))
                 },
@@ -604,7 +725,7 @@ impl MoneyFlow {
             .with_context(|| format!("Lỗi xử lý dữ liệu sheet {}", inflow_sheet_key))?;
 
         let (outflow_rows, outflow_columns, outflow_base_coord) =
-            read_table_from_sheet(workbook, outflow_sheet_key)?;
+            read_table_from_sheet(workbook, outflow_sheet_key, report_form)?;
 
         let mut outflow_entries = outflow_rows
             .into_iter()
@@ -630,7 +751,8 @@ impl MoneyFlow {
                         tx_to: cell_value_func("Giao dịch đến ngày").convert_date_vn_to_iso()?,
                         currency: cell_value_func("Loại tiền").to_currency_code()?.into(),
                         content: cell_value_func("Tóm tắt nội dung giao dịch"),
-                    };
+                    }
+                    .complete_bank_info()?;
 
                     Ok((cif, account_number, entry))
                 },
@@ -710,7 +832,7 @@ impl MoneyFlow {
                         acc + entry
                             .total_converted
                             .as_ref()
-                            .and_then(|s| s.replace(",", "").parse::<f64>().ok())
+                            .and_then(|s| number::parse_amount(s))
                             .unwrap_or(0.0)
                     })
                     .to_string()
@@ -721,7 +843,7 @@ impl MoneyFlow {
                         acc + entry
                             .total_converted
                             .as_ref()
-                            .and_then(|s| s.replace(",", "").parse::<f64>().ok())
+                            .and_then(|s| number::parse_amount(s))
                             .unwrap_or(0.0)
                     })
                     .to_string()
@@ -732,7 +854,7 @@ impl MoneyFlow {
                         acc + entry
                             .total_transactions
                             .as_ref()
-                            .and_then(|s| s.replace(",", "").parse::<i64>().ok())
+                            .and_then(|s| number::parse_count(s))
                             .unwrap_or(0_i64)
                     })
                     .to_string()
@@ -743,7 +865,7 @@ impl MoneyFlow {
                         acc + entry
                             .total_transactions
                             .as_ref()
-                            .and_then(|s| s.replace(",", "").parse::<i64>().ok())
+                            .and_then(|s| number::parse_count(s))
                             .unwrap_or(0_i64)
                     })
                     .to_string()
@@ -751,7 +873,8 @@ impl MoneyFlow {
                 inflows: inflows.into(),
                 outflows: outflows.into(),
             })
-            .collect::<Vec<_>>();
+            .map(|flow| flow.complete_bank_info())
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         Ok(results)
     }
@@ -6,40 +6,42 @@ use std::{
 use calamine::{DataType, Reader};
 
 use crate::{
-    payload::section5::{Document, ProcessedTask, Section5},
-    template::cell_value_from_key,
+    codes::processed_task::PROCESSED_TASKS,
+    excel::checkbox::{CheckState, Checkboxes, is_selected},
+    payload::{
+        code::{DocumentDirection, ProcessedTaskType},
+        section5::{Document, ProcessedTask, Section5},
+    },
+    template::{ReportForm, cell_value_from_key},
 };
 
 impl Section5 {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         _file_path: &std::path::Path,
+        checkboxes: &Checkboxes,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        let sheet_name = cell_value_from_key("Phần V: Công việc xử lý", workbook)?;
-        let checked_box = cell_value_from_key("Dấu tick", workbook)?;
+        let sheet_name = cell_value_from_key(report_form, "Phần V: Công việc xử lý", workbook)?;
         let range = workbook.worksheet_range(&sheet_name)?;
 
         let selection = range
             .rows()
-            .into_iter()
-            .map(|row| {
+            .enumerate()
+            .map(|(row_idx, row)| {
                 let key = row
                     .get(0)
                     .map(|c| c.get_string().unwrap_or_default().trim().to_string())
                     .unwrap_or(Default::default());
 
-                let value = row
-                    .get(1)
-                    .map(|c| c.get_string().unwrap_or_default().trim().to_string())
-                    .map(|c| c == checked_box)
-                    .unwrap_or_default();
+                let value = checkboxes.row_state(&sheet_name, &range, row_idx);
 
                 (key, value)
             })
-            .filter(|(k, v)| !k.is_empty() && *v)
+            .filter(|(k, v)| !k.is_empty() && *v != CheckState::Unchecked)
             .collect::<HashMap<_, _>>();
 
         let get_desc_fn = |key: &str| -> Option<String> {
@@ -99,11 +101,9 @@ impl Section5 {
                 .filter(|(k, _)| k == &doc_key)
                 .map(|(_, (doc_number, doc_date, unit))| Document {
                     doc_type: match doc_type {
-                        DocType::In => "0",
-                        DocType::Out => "1",
-                    }
-                    .to_string()
-                    .into(),
+                        DocType::In => DocumentDirection::new("0").ok(),
+                        DocType::Out => DocumentDirection::new("1").ok(),
+                    },
                     doc_number: doc_number,
                     doc_date: doc_date,
                     unit: unit,
@@ -111,27 +111,9 @@ impl Section5 {
                 .next()
         };
 
-        let processed_tasks = vec![
-            ("1", "Từ chối thực hiện giao dịch"),
-            ("2", "Tạm khóa tài khoản"),
-            ("3", "Chấm dứt thiết lập giao dịch với khách hàng"),
-            ("4", "Giám sát sau giao dịch"),
-            ("5", "Đưa vào hệ thống cảnh báo của đối tượng báo cáo"),
-            (
-                "6",
-                "Ngân hàng đã có công văn gửi Cơ quan nhà nước có thẩm quyền",
-            ),
-            (
-                "7",
-                "Ngân hàng nhận được công văn của Cơ quan nhà nước có thẩm quyền yêu cầu cung cấp thông tin, tài liệu",
-            ),
-            ("8", "Tạm ngừng cung cấp dịch vụ ngân hàng điện tử"),
-            ("0", "Công việc khác"),
-        ];
-
-        let processed_tasks = processed_tasks
+        let processed_tasks = PROCESSED_TASKS
             .into_iter()
-            .filter(|(code, _)| selection.get(*code).cloned().unwrap_or_default())
+            .filter(|(code, description)| is_selected(selection.get(*code), description))
             .map(|(code, description)| {
                 let in_doc = get_doc_fn(code, DocType::In);
                 let out_doc = get_doc_fn(code, DocType::Out);
@@ -143,14 +125,14 @@ impl Section5 {
                     (Some(in_doc), Some(out_doc)) => Some(vec![in_doc, out_doc]),
                 };
 
-                ProcessedTask {
-                    code: code.to_string().into(),
+                Ok(ProcessedTask {
+                    code: ProcessedTaskType::new(code)?.into(),
                     description: description.to_string().into(),
                     documents: documents,
                     other_content: get_desc_fn(code),
-                }
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         Ok(Section5 {
             processed_tasks: processed_tasks.into(),
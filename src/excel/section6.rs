@@ -1,46 +1,60 @@
 use std::{
+    collections::HashMap,
     io::{Read, Seek},
-    path::PathBuf,
 };
 
 use anyhow::Context;
+use calamine::Reader;
 
 use crate::{
+    attachments::{
+        convert::{PlannedAttachment, conversion_enabled, convert_attachments},
+        generated::{
+            ReportData, generated_folder, plan_generated_attachments, save_generated_attachments,
+        },
+        integrity::check_duplicate_attachments,
+        manifest::{
+            ManifestEntry, check_manifest, find_manifest_file, is_manifest_file,
+            manifest_from_rows, read_manifest_file,
+        },
+        pages::count_pages,
+        policy::{check_attachment_files, check_required_types},
+        scan::{configured_scanner, scan_attachment_files, scan_enabled},
+        statement::is_transactions_file,
+        upload::hash_file,
+    },
     codes::document_type::DocumentType,
+    excel::{attachment_folder, get_cell_value, read_table_from_sheet},
+    narrative::is_narrative_file,
     payload::section6::{Attachment, Section6},
-    template::value_list_from_key,
+    rules::report_violations,
+    template::{ReportForm, table_config_from_key},
 };
 
 impl Section6 {
     pub fn from_excel<RS>(
         workbook: &mut calamine::Xlsx<RS>,
         file_path: &std::path::Path,
+        data: ReportData,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        Self::_from_excel(workbook, file_path)
+        Self::_from_excel(workbook, file_path, data, report_form)
             .with_context(|| format!("Lỗi xử lý dữ liệu Phần VI - Tài liệu đính kèm"))
     }
 
     fn _from_excel<RS>(
-        _workbook: &mut calamine::Xlsx<RS>,
+        workbook: &mut calamine::Xlsx<RS>,
         file_path: &std::path::Path,
+        data: ReportData,
+        report_form: &ReportForm,
     ) -> anyhow::Result<Self>
     where
         RS: Seek + Read,
     {
-        let file_name = file_path
-            .file_stem()
-            .map(|name| name.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        let attachment_folder = {
-            let mut folder = PathBuf::new();
-            folder.push("input");
-            folder.push(file_name);
-            folder
-        };
+        let attachment_folder = attachment_folder(file_path);
         if !attachment_folder.exists() || !attachment_folder.is_dir() {
             return Err(anyhow::anyhow!(
                 "Không tìm thấy folder chứa các file đính kèm {:#?}. Bổ sung thêm folder đính kèm và đặt tên folder trùng với tên file.",
@@ -48,124 +62,275 @@ impl Section6 {
             ));
         }
 
-        let file_list = std::fs::read_dir(&attachment_folder)
-            .with_context(|| format!("Không thể mở folder {:#?}", &attachment_folder))?;
+        let (manifest, manifest_source) = match read_manifest_sheet(workbook, report_form)? {
+            Some(entries) => (Some(entries), "sheet 'Tài liệu đính kèm'".to_string()),
+            None => match find_manifest_file(&attachment_folder) {
+                Some(manifest_file) => (
+                    Some(read_manifest_file(&manifest_file)?),
+                    format!("file {:#?}", manifest_file),
+                ),
+                None => (None, String::new()),
+            },
+        };
 
-        let mut attachments = vec![];
+        let mut files = std::fs::read_dir(&attachment_folder)
+            .with_context(|| format!("Không thể mở folder {:#?}", &attachment_folder))?
+            .map(|file| {
+                file.map(|file| file.path())
+                    .with_context(|| format!("Không thể mở folder {:#?}", &attachment_folder))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        files.retain(|path| {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            path.is_file()
+                && !file_name.starts_with("~$")
+                && !is_narrative_file(path)
+                && !is_manifest_file(path)
+                && !is_transactions_file(path)
+        });
+        files.sort();
 
-        for file in file_list {
-            let file =
-                file.with_context(|| format!("Không thể mở folder {:#?}", &attachment_folder))?;
+        let file_names = files
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        if let Some(manifest) = manifest.as_ref() {
+            check_manifest(manifest, &file_names, &manifest_source)?;
+        }
 
-            let file_name = file
-                .path()
+        let mut planned = vec![];
+
+        for (path, file_full_name) in files.iter().zip(file_names) {
+            let entry = manifest
+                .iter()
+                .flatten()
+                .find(|entry| entry.file_name.eq_ignore_ascii_case(&file_full_name));
+
+            let file_name = path
                 .file_stem()
                 .unwrap_or_default()
                 .to_string_lossy()
                 .to_string();
 
-            if file_name.starts_with("~$") {
-                continue;
-            }
+            let manifest_type = entry
+                .map(|entry| entry.attachment_type())
+                .transpose()?
+                .flatten();
 
-            let file_prefix = file_name
-                .split("_")
-                .next()
-                .unwrap_or_default()
-                .to_string()
-                .to_uppercase()
-                .validate_document_type()
-                .with_context(|| {
-                    format!(
-                        "File đính kèm {:#?} có tiền tố không hợp lệ",
-                        file.path().as_path()
-                    )
-                })?;
-
-            let file_desc = file_name
-                .strip_prefix(format!("{}_", file_prefix).as_str())
-                .unwrap_or_default()
-                .to_string();
+            // Files not described by the manifest fall back to the `TYPE_description` convention
+            let file_prefix = match manifest_type {
+                Some(attachment_type) => attachment_type,
+                None => file_name
+                    .split("_")
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+                    .to_uppercase()
+                    .validate_document_type()
+                    .with_context(|| {
+                        format!(
+                            "File đính kèm {:#?} có tiền tố không hợp lệ",
+                            path.as_path()
+                        )
+                    })?,
+            };
 
-            let file_ext = file
-                .path()
-                .extension()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            let file_mime = mime_guess::from_path(file.path())
-                .first_or_octet_stream()
-                .to_string()
-                .into();
-
-            let file_content = std::fs::read(file.path())
-                .with_context(|| format!("Không thể đọc file {}", file.path().to_string_lossy()))?;
-
-            let page_count = match file_ext.to_lowercase().as_str() {
-                "pdf" => {
-                    let doc =
-                        lopdf::Document::load_mem(file_content.as_slice()).with_context(|| {
-                            format!("Không thể đọc được file PDF {:#?}", file.path())
-                        })?;
-                    doc.get_pages().iter().count() as i32
-                }
-                _ => 1,
+            let file_desc = match entry.and_then(|entry| entry.description.clone()) {
+                Some(description) => description,
+                None => file_name
+                    .strip_prefix(format!("{}_", file_prefix).as_str())
+                    .unwrap_or_default()
+                    .to_string(),
             };
 
-            let file_size = file_content.len() as i64;
+            planned.push(PlannedAttachment {
+                path: path.clone(),
+                file_name: file_full_name,
+                attachment_type: file_prefix,
+                description: file_desc,
+                page_count: entry.and_then(|entry| entry.page_count),
+                order: entry.and_then(|entry| entry.order),
+            });
+        }
+
+        let generated = plan_generated_attachments(&planned, data, file_path)?;
+
+        if conversion_enabled()? {
+            planned = convert_attachments(planned, file_path)?;
+        }
+        let attachments = prepare_attachments(planned, &attachment_folder)?;
+
+        let attachment_types = attachments
+            .iter()
+            .filter_map(|attachment| attachment.attachment_type.as_ref())
+            .map(|attachment_type| attachment_type.as_str())
+            .chain(generated.iter().map(|generated| generated.code))
+            .collect::<Vec<_>>();
+        report_violations(
+            &check_required_types(&attachment_types, report_form)?,
+            &format!(
+                "Thiếu tài liệu đính kèm bắt buộc trong folder {:#?}",
+                attachment_folder.as_path()
+            ),
+        )?;
+
+        Ok(Section6 {
+            attachments,
+            generated,
+        })
+    }
+
+    /// Writes the attachments generated from the report data to the local archive and adds them
+    /// to the list. Only called when the report is submitted, reading or validating a workbook
+    /// leaves no file behind.
+    pub fn save_generated_attachments(
+        &mut self,
+        file_path: &std::path::Path,
+    ) -> anyhow::Result<()> {
+        if self.generated.is_empty() {
+            return Ok(());
+        }
+
+        let planned = save_generated_attachments(&self.generated, file_path)?;
+        let attachments = prepare_attachments(planned, &generated_folder(file_path))?;
+        self.attachments.extend(attachments);
+        self.generated.clear();
+
+        report_violations(
+            &check_duplicate_attachments(&self.attachments),
+            &format!("Các file đính kèm của file {:#?} bị trùng lặp", file_path),
+        )
+    }
+}
+
+/// Checks the files against the portal rules and the malware scanner and reads their size, hash
+/// and page count, in the order given by the manifest.
+fn prepare_attachments(
+    planned: Vec<PlannedAttachment>,
+    folder: &std::path::Path,
+) -> anyhow::Result<Vec<Attachment>> {
+    report_violations(
+        &check_attachment_files(
+            &planned
+                .iter()
+                .map(|file| file.path.clone())
+                .collect::<Vec<_>>(),
+        )?,
+        &format!(
+            "Các file đính kèm trong folder {:#?} không đạt yêu cầu của website NHNN",
+            folder
+        ),
+    )?;
+    if scan_enabled()? {
+        let scanner = configured_scanner()?;
+        report_violations(
+            &scan_attachment_files(
+                scanner.as_ref(),
+                &planned
+                    .iter()
+                    .map(|file| file.path.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            &format!(
+                "Các file đính kèm trong folder {:#?} không qua được kiểm tra mã độc, báo cáo không được gửi",
+                folder
+            ),
+        )?;
+    }
+
+    let mut attachments = vec![];
+
+    for file in planned.into_iter() {
+        let path = &file.path;
+        let file_ext = path
+            .extension()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let file_mime = mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string()
+            .into();
 
-            attachments.push(Attachment {
+        let (file_size, file_hash) = hash_file(path)?;
+
+        let page_count = match file.page_count {
+            Some(page_count) => page_count,
+            None => count_pages(path)?,
+        };
+
+        attachments.push((
+            file.order,
+            Attachment {
                 str_id: None,
                 status: "ACTIVE".to_string().into(),
-                attachment_type: file_prefix.into(),
+                attachment_type: file.attachment_type.into(),
                 page_count: page_count.into(),
-                description: file_desc.into(),
-                file_name: file
-                    .path()
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string()
-                    .into(),
+                description: file.description.into(),
+                file_name: file.file_name.into(),
                 file_type: file_ext.into(),
-                file_size: file_size.into(),
+                file_size: (file_size as i64).into(),
                 file: Default::default(),
-                file_mime: file_mime,
-                file_content: file_content.into(),
-            });
-        }
+                file_mime,
+                file_path: file.path.clone().into(),
+                file_hash: file_hash.into(),
+            },
+        ));
+    }
 
-        let required_doc_types =
-            value_list_from_key("Phần VI. Tài liệu đính kèm - Tài liệu bắt buộc")?;
+    // Files listed with an order come first, the others keep the folder order
+    attachments.sort_by_key(|(order, _)| (order.is_none(), *order));
+    let attachments = attachments
+        .into_iter()
+        .map(|(_, attachment)| attachment)
+        .collect::<Vec<_>>();
 
-        for doc_type in required_doc_types.into_iter() {
-            let doc_type_code = doc_type
-                .to_document_type()
-                .with_context(|| format!("Loại tài liệu bắt buộc không phù hợp {}", doc_type))?;
+    report_violations(
+        &check_duplicate_attachments(&attachments),
+        &format!("Các file đính kèm trong folder {:#?} bị trùng lặp", folder),
+    )?;
+
+    Ok(attachments)
+}
 
-            let doc_count = attachments
+/// Attachment list from the "Tài liệu đính kèm" sheet, when the workbook has one.
+fn read_manifest_sheet<RS>(
+    workbook: &mut calamine::Xlsx<RS>,
+    report_form: &ReportForm,
+) -> anyhow::Result<Option<Vec<ManifestEntry>>>
+where
+    RS: Seek + Read,
+{
+    let sheet_key = "Phần VI. Tài liệu đính kèm - Danh sách";
+    let table_config = table_config_from_key(report_form, sheet_key)?;
+    if !workbook.sheet_names().contains(&table_config.sheet) {
+        return Ok(None);
+    }
+
+    let (rows, col_map, base_coord) = read_table_from_sheet(workbook, sheet_key, report_form)?;
+    let columns = col_map.keys().cloned().collect::<Vec<_>>();
+    let rows = rows
+        .iter()
+        .map(|curr_row| {
+            columns
                 .iter()
-                .filter(|attachement| {
-                    let attachment_type = attachement
-                        .attachment_type
-                        .as_ref()
-                        .map(|s| s.as_str())
-                        .unwrap_or_default();
-                    attachment_type == doc_type_code.as_str()
+                .map(|column| {
+                    let value = get_cell_value(column, &col_map, base_coord, curr_row)?;
+                    Ok((column.clone(), value.unwrap_or_default()))
                 })
-                .count();
-
-            if doc_count == 0 {
-                return Err(anyhow::anyhow!(
-                    "Không tìm thấy loại tài liệu bắt buộc \"{}\" trong folder {:#?}. Đặt tên các file '{}' chứa tiền tố '{}_'",
-                    doc_type,
-                    attachment_folder.as_path(),
-                    doc_type,
-                    doc_type_code
-                ));
-            }
-        }
+                .collect::<anyhow::Result<HashMap<_, _>>>()
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
-        Ok(Section6 { attachments })
+    let entries = manifest_from_rows(&columns, rows)
+        .with_context(|| format!("Lỗi dữ liệu tại sheet '{}'", table_config.sheet))?;
+    match entries.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(entries)),
     }
 }
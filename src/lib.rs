@@ -1,9 +1,19 @@
+pub mod archive;
+pub mod attachments;
 pub mod auth;
 pub mod build;
 mod codes;
+pub mod deadline;
+pub mod diagram;
+pub mod entity_graph;
 pub mod excel;
 pub mod launch;
+pub mod narrative;
+pub mod ownership;
 pub mod payload;
+pub mod pdf;
+pub mod reconcile;
 pub mod response;
+pub mod rules;
 pub mod template;
 pub mod utils;
@@ -0,0 +1,172 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use quick_xml::events::Event;
+
+use crate::template::{ReportForm, value_from_key};
+
+/// Sidecar file in the attachment folder holding the "Mô tả, phân tích chi tiết" narrative.
+pub const NARRATIVE_FILE_STEM: &str = "Mo_ta_phan_tich";
+const NARRATIVE_EXTENSIONS: [&str; 3] = ["txt", "md", "docx"];
+
+const LENGTH_LIMIT_KEY: &str = "Phần IV: Mô tả, phân tích chi tiết - Số ký tự tối đa";
+
+pub struct ChecklistItem {
+    pub name: &'static str,
+    pub keywords: &'static [&'static str],
+}
+
+pub const CHECKLIST: [ChecklistItem; 5] = [
+    ChecklistItem {
+        name: "đối tượng thực hiện giao dịch (ai)",
+        keywords: &[
+            "khách hàng",
+            "ông ",
+            "bà ",
+            "cá nhân",
+            "tổ chức",
+            "công ty",
+            "chủ tài khoản",
+            "doanh nghiệp",
+        ],
+    },
+    ChecklistItem {
+        name: "nội dung giao dịch (cái gì)",
+        keywords: &[
+            "giao dịch",
+            "chuyển tiền",
+            "chuyển khoản",
+            "nộp tiền",
+            "rút tiền",
+            "số tiền",
+            "thanh toán",
+        ],
+    },
+    ChecklistItem {
+        name: "thời gian giao dịch (khi nào)",
+        keywords: &["ngày", "tháng", "năm", "thời gian", "giai đoạn"],
+    },
+    ChecklistItem {
+        name: "địa điểm, kênh giao dịch (ở đâu)",
+        keywords: &[
+            "chi nhánh",
+            "phòng giao dịch",
+            "địa chỉ",
+            "quầy",
+            "atm",
+            "trực tuyến",
+            "internet banking",
+            "mobile banking",
+            "kênh",
+        ],
+    },
+    ChecklistItem {
+        name: "lý do nghi ngờ (tại sao)",
+        keywords: &[
+            "nghi ngờ",
+            "đáng ngờ",
+            "bất thường",
+            "không phù hợp",
+            "dấu hiệu",
+            "không rõ",
+            "không tương xứng",
+        ],
+    },
+];
+
+pub fn is_narrative_file(path: &Path) -> bool {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    stem.eq_ignore_ascii_case(NARRATIVE_FILE_STEM)
+        && NARRATIVE_EXTENSIONS.contains(&extension.as_str())
+}
+
+pub fn find_narrative_file(folder: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.is_file() && is_narrative_file(path))
+}
+
+pub fn read_narrative_file(path: &Path) -> anyhow::Result<String> {
+    let extension = path
+        .extension()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let text = match extension.as_str() {
+        "docx" => read_docx_text(path),
+        _ => std::fs::read_to_string(path).map_err(anyhow::Error::from),
+    }
+    .with_context(|| format!("Không thể đọc file mô tả, phân tích chi tiết {:#?}", path))?;
+
+    Ok(text.trim().to_string())
+}
+
+fn read_docx_text(path: &Path) -> anyhow::Result<String> {
+    let file = std::fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    let mut document = String::new();
+    archive
+        .by_name("word/document.xml")?
+        .read_to_string(&mut document)?;
+
+    let mut reader = quick_xml::Reader::from_str(&document);
+    let mut paragraphs = vec![];
+    let mut paragraph = String::new();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) if tag.name().as_ref() == b"w:t" => in_text = true,
+            Event::End(tag) if tag.name().as_ref() == b"w:t" => in_text = false,
+            Event::Text(text) if in_text => paragraph.push_str(&text.decode()?),
+            Event::GeneralRef(entity) if in_text => {
+                if let Some(ch) = entity.resolve_char_ref()? {
+                    paragraph.push(ch);
+                } else {
+                    let name = entity.decode()?;
+                    let resolved = quick_xml::escape::resolve_xml_entity(&name).unwrap_or_default();
+                    paragraph.push_str(resolved);
+                }
+            }
+            Event::Empty(tag) if tag.name().as_ref() == b"w:tab" => paragraph.push('\t'),
+            Event::Empty(tag) if tag.name().as_ref() == b"w:br" => paragraph.push('\n'),
+            Event::End(tag) if tag.name().as_ref() == b"w:p" => {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(paragraphs.join("\n"))
+}
+
+pub fn narrative_length_limit(report_form: &ReportForm) -> Option<usize> {
+    value_from_key(report_form, LENGTH_LIMIT_KEY)
+        .ok()
+        .and_then(|limit| limit.trim().parse::<usize>().ok())
+}
+
+/// Checklist items for which none of the keywords appear in the narrative.
+pub fn missing_checklist_items(narrative: &str) -> Vec<&'static str> {
+    let narrative = narrative.to_lowercase();
+    CHECKLIST
+        .iter()
+        .filter(|item| !item.keywords.iter().any(|k| narrative.contains(k)))
+        .map(|item| item.name)
+        .collect()
+}
@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{
+    payload::{
+        entities::{Individual, Organization},
+        form::Payload,
+    },
+    rules::{Severity, Violation, report_violations},
+};
+
+// Percentages are typed by hand, allow for rounding when summing them
+const PERCENTAGE_TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone)]
+pub struct EffectiveOwnership {
+    pub organization: String,
+    pub owner: String,
+    pub id_number: String,
+    /// Organizations the stake is held through, from the direct parent of the customer upwards
+    pub chain: Vec<String>,
+    pub percentage: Option<f64>,
+    pub control_basis: Option<String>,
+}
+
+struct Ancestor<'a> {
+    enterprise_code: String,
+    name: String,
+    organization: Option<&'a Organization>,
+    multiplier: Option<f64>,
+}
+
+fn enterprise_code_of(org: &Organization) -> String {
+    org.enterprise_code
+        .as_ref()
+        .and_then(|ec| ec.code.clone())
+        .unwrap_or_default()
+        .trim()
+        .to_uppercase()
+}
+
+fn organization_name(org: &Organization) -> String {
+    org.name.clone().unwrap_or_default()
+}
+
+fn id_number_of(person: &Individual) -> String {
+    person
+        .identifications
+        .as_ref()
+        .and_then(|ids| ids.first())
+        .and_then(|id| id.id_number.clone())
+        .unwrap_or_default()
+}
+
+fn same_code(a: &str, b: &str) -> bool {
+    !a.is_empty() && a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// Walks the corporate owners of `customer` upwards, the customer itself comes first.
+fn ancestors_of<'a>(
+    customer: &'a Organization,
+    customers: &[&'a Organization],
+    related: &[&'a Organization],
+    violations: &mut Vec<Violation>,
+) -> Vec<Ancestor<'a>> {
+    let mut ancestors = vec![Ancestor {
+        enterprise_code: enterprise_code_of(customer),
+        name: organization_name(customer),
+        organization: Some(customer),
+        multiplier: Some(1.0),
+    }];
+    let mut seen = HashSet::from([enterprise_code_of(customer)]);
+
+    while let Some(owner) = ancestors
+        .last()
+        .and_then(|a| a.organization)
+        .and_then(|org| org.corporate_owner.as_ref())
+    {
+        let code = owner.enterprise_code.trim().to_uppercase();
+        if !seen.insert(code.clone()) {
+            violations.push(Violation {
+                rule_id: "OWN-005",
+                severity: Severity::Error,
+                message: format!(
+                    "Chuỗi sở hữu của tổ chức '{}' bị lặp vòng tại tổ chức có MST {}",
+                    organization_name(customer),
+                    code
+                ),
+            });
+            break;
+        }
+
+        let organization = customers
+            .iter()
+            .find(|org| same_code(&enterprise_code_of(org), &code))
+            .copied();
+        let name = organization
+            .or_else(|| {
+                related
+                    .iter()
+                    .find(|org| same_code(&enterprise_code_of(org), &code))
+                    .copied()
+            })
+            .map(organization_name)
+            .unwrap_or_else(|| code.clone());
+        let multiplier = ancestors
+            .last()
+            .and_then(|a| a.multiplier)
+            .zip(owner.percentage)
+            .map(|(multiplier, percentage)| multiplier * percentage / 100.0);
+
+        ancestors.push(Ancestor {
+            enterprise_code: code,
+            name,
+            organization,
+            multiplier,
+        });
+    }
+
+    ancestors
+}
+
+fn analyze(payload: &Payload) -> (Vec<EffectiveOwnership>, Vec<Violation>) {
+    let mut table = vec![];
+    let mut violations = vec![];
+
+    let Some(section_2) = payload.section_2.as_ref() else {
+        return (table, violations);
+    };
+
+    let customers = section_2.organizations.iter().flatten().collect::<Vec<_>>();
+    let related = payload
+        .section_3
+        .iter()
+        .flat_map(|s| s.related_organizations.iter().flatten())
+        .collect::<Vec<_>>();
+    let owners = section_2
+        .beneficial_owners
+        .iter()
+        .flat_map(|b| b.other_owners.iter().flatten())
+        .collect::<Vec<_>>();
+
+    let owners_of = |org: &Organization| {
+        let cif = org.id.clone().unwrap_or_default();
+        owners
+            .iter()
+            .filter(move |person| person.id.as_deref().is_some_and(|id| same_code(id, &cif)))
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    for customer in customers.iter() {
+        let n_rows = table.len();
+        let ancestors = ancestors_of(customer, &customers, &related, &mut violations);
+
+        for (level, ancestor) in ancestors.iter().enumerate() {
+            let Some(organization) = ancestor.organization else {
+                continue;
+            };
+
+            for person in owners_of(organization) {
+                let ownership = person.ownership.clone().unwrap_or_default();
+                let target = match ownership.through.as_deref() {
+                    None => Some(level),
+                    Some(through) => ancestors
+                        .iter()
+                        .enumerate()
+                        .skip(level)
+                        .find(|(_, a)| same_code(&a.enterprise_code, through))
+                        .map(|(idx, _)| idx),
+                };
+
+                let Some(target) = target else {
+                    // Reported once, for the customer the row is declared under
+                    if level == 0 {
+                        violations.push(Violation {
+                            rule_id: "OWN-004",
+                            severity: Severity::Error,
+                            message: format!(
+                                "Chủ sở hữu hưởng lợi '{}' của tổ chức '{}' sở hữu thông qua tổ chức có MST {} nhưng tổ chức này không thuộc chuỗi sở hữu của khách hàng tại sheet Phần II. KHTC",
+                                person.full_name.clone().unwrap_or_default(),
+                                organization_name(customer),
+                                ownership.through.clone().unwrap_or_default()
+                            ),
+                        });
+                    }
+                    continue;
+                };
+
+                table.push(EffectiveOwnership {
+                    organization: organization_name(customer),
+                    owner: person.full_name.clone().unwrap_or_default(),
+                    id_number: id_number_of(person),
+                    chain: ancestors[1..=target]
+                        .iter()
+                        .map(|a| a.name.clone())
+                        .collect(),
+                    percentage: ancestors[target]
+                        .multiplier
+                        .zip(ownership.percentage)
+                        .map(|(multiplier, percentage)| multiplier * percentage),
+                    control_basis: ownership.control_basis.clone(),
+                });
+            }
+        }
+
+        if table.len() == n_rows {
+            violations.push(Violation {
+                rule_id: "OWN-001",
+                severity: Severity::Error,
+                message: format!(
+                    "Tổ chức '{}' chưa có chủ sở hữu hưởng lợi nào tại sheet Phần II. CSHHL khác",
+                    organization_name(customer)
+                ),
+            });
+        }
+    }
+
+    // Stakes held directly in each organization, the same person listed under several customers
+    // is only counted once
+    let mut stakes = BTreeMap::<String, (String, f64)>::new();
+    let mut counted = HashSet::new();
+    for person in owners.iter() {
+        let ownership = person.ownership.clone().unwrap_or_default();
+        let Some(percentage) = ownership.percentage else {
+            continue;
+        };
+        let customer = customers.iter().find(|org| {
+            person
+                .id
+                .as_deref()
+                .is_some_and(|id| org.id.as_deref().is_some_and(|cif| same_code(id, cif)))
+        });
+        let target = match (ownership.through.as_deref(), customer) {
+            (Some(through), _) => through.trim().to_uppercase(),
+            (None, Some(customer)) => enterprise_code_of(customer),
+            (None, None) => continue,
+        };
+
+        // Owners without an id number are told apart by name, and always counted without either
+        let owner_key = Some(id_number_of(person))
+            .filter(|id_number| !id_number.trim().is_empty())
+            .map(|id_number| id_number.trim().to_uppercase())
+            .or_else(|| {
+                person
+                    .full_name
+                    .as_deref()
+                    .map(|name| name.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.to_lowercase())
+            });
+        let first_seen = match owner_key {
+            Some(owner_key) => counted.insert((owner_key, target.clone())),
+            None => true,
+        };
+        if first_seen {
+            let name = customers
+                .iter()
+                .chain(related.iter())
+                .find(|org| same_code(&enterprise_code_of(org), &target))
+                .map(|org| organization_name(org))
+                .unwrap_or_else(|| target.clone());
+            stakes.entry(target).or_insert((name, 0.0)).1 += percentage;
+        }
+    }
+    for org in customers.iter() {
+        if let Some(owner) = org.corporate_owner.as_ref()
+            && let Some(percentage) = owner.percentage
+        {
+            stakes
+                .entry(enterprise_code_of(org))
+                .or_insert((organization_name(org), 0.0))
+                .1 += percentage;
+        }
+    }
+
+    violations.extend(
+        stakes
+            .into_iter()
+            .filter(|(_, (_, total))| *total > 100.0 + PERCENTAGE_TOLERANCE)
+            .map(|(code, (name, total))| Violation {
+                rule_id: "OWN-002",
+                severity: Severity::Error,
+                message: format!(
+                    "Tổng tỷ lệ sở hữu của tổ chức '{}' (MST {}) là {:.2}%, vượt quá 100%",
+                    name, code, total
+                ),
+            }),
+    );
+
+    (table, violations)
+}
+
+pub fn effective_ownership(payload: &Payload) -> Vec<EffectiveOwnership> {
+    analyze(payload).0
+}
+
+pub fn validate_ownership(payload: &Payload) -> Vec<Violation> {
+    analyze(payload).1
+}
+
+pub fn check_ownership(payload: &Payload) -> anyhow::Result<()> {
+    report_violations(
+        &validate_ownership(payload),
+        "Cơ cấu chủ sở hữu hưởng lợi không hợp lệ",
+    )
+}
+
+/// Logs the effective ownership table, kept out of stdout which holds the JSON of the report.
+pub fn log_ownership_table(payload: &Payload) {
+    let table = effective_ownership(payload);
+    if table.is_empty() {
+        return;
+    }
+
+    log::info!("Tỷ lệ sở hữu thực tế của chủ sở hữu hưởng lợi:");
+    for row in table {
+        let percentage = row
+            .percentage
+            .map(|p| format!("{:.2}%", p))
+            .unwrap_or_else(|| "không rõ".to_string());
+        let chain = match row.chain.is_empty() {
+            true => "trực tiếp".to_string(),
+            false => format!("thông qua {}", row.chain.join(" -> ")),
+        };
+
+        log::info!(
+            "  {} | {} ({}) | {} | {} | {}",
+            row.organization,
+            row.owner,
+            row.id_number,
+            percentage,
+            chain,
+            row.control_basis.unwrap_or_default()
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::{fmt, hash::Hash, marker::PhantomData, ops::Deref};
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    codes::{
+        account_status::ACCOUNT_STATUS_CODES, account_type::ACCOUNT_TYPE_CODES,
+        age_range::AGE_RANGE_CODES, corporate_type::CORPORATE_TYPES, country::COUNTRY_CODES,
+        currency::CURRENCY_CODES, document_type::DOCUMENT_TYPES, gender::GENDER_CODES,
+        occupation::OCCUPATION_CODES, personal_id::PERSONAL_ID_CODES,
+        processed_task::PROCESSED_TASKS, utils::search_for_name,
+    },
+    template::{
+        REPORT_FORMS, ReportForm, default_report_form, legal_basis_mapping_from_key,
+        mapping_from_key,
+    },
+};
+
+pub trait CodeList {
+    const NAME: &'static str;
+
+    fn description(report_form: &ReportForm, code: &str) -> anyhow::Result<Option<String>>;
+
+    fn codes(report_form: &ReportForm) -> anyhow::Result<Vec<String>>;
+}
+
+/// Code list that is the same for every report form.
+pub trait FixedCodeList: CodeList {}
+
+/// Portal code validated against the code list `L`. An empty code is allowed and means the field
+/// was left blank in the report.
+pub struct Code<L: CodeList> {
+    value: String,
+    list: PhantomData<L>,
+}
+
+impl<L: FixedCodeList> Code<L> {
+    pub fn new(code: impl Into<String>) -> anyhow::Result<Self> {
+        Self::for_form(default_report_form(), code)
+    }
+}
+
+impl<L: CodeList> Code<L> {
+    /// Code validated against the list of `report_form`.
+    pub fn for_form(report_form: &ReportForm, code: impl Into<String>) -> anyhow::Result<Self> {
+        let value = code.into().trim().to_string();
+        if !value.is_empty() && L::description(report_form, &value)?.is_none() {
+            return Err(anyhow::anyhow!("{} không hợp lệ: {}", L::NAME, value));
+        }
+
+        Ok(Self {
+            value,
+            list: PhantomData,
+        })
+    }
+
+    pub fn blank() -> Self {
+        Self {
+            value: String::new(),
+            list: PhantomData,
+        }
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn description(&self, report_form: &ReportForm) -> Option<String> {
+        L::description(report_form, &self.value).ok().flatten()
+    }
+}
+
+impl<L: CodeList> Deref for Code<L> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl<L: CodeList> Clone for Code<L> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            list: PhantomData,
+        }
+    }
+}
+
+impl<L: CodeList> fmt::Debug for Code<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+impl<L: CodeList> fmt::Display for Code<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl<L: CodeList> Default for Code<L> {
+    fn default() -> Self {
+        Self::blank()
+    }
+}
+
+impl<L: CodeList> PartialEq for Code<L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<L: CodeList> Eq for Code<L> {}
+
+impl<L: CodeList> Hash for Code<L> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<L: CodeList> JsonSchema for Code<L> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        L::NAME.into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        // Lists that differ per report form are replaced by `form_schema`
+        let codes = std::iter::once(String::new())
+            .chain(L::codes(default_report_form()).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        json_schema!({
+            "type": "string",
+            "enum": codes,
+            "description": L::NAME,
+        })
+    }
+}
+
+impl<L: CodeList> Serialize for Code<L> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.value)
+    }
+}
+
+impl<'de, L: CodeList> Deserialize<'de> for Code<L> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        // The form is only known once the whole report is read, a code of any form is accepted
+        // here and the schema of the report form checks the exact list
+        REPORT_FORMS
+            .iter()
+            .find_map(|report_form| Self::for_form(report_form, value.as_str()).ok())
+            .map_or_else(|| Self::for_form(default_report_form(), value), Ok)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+macro_rules! table_code_list {
+    ($list:ident, $alias:ident, $table:expr, $name:literal) => {
+        #[derive(Debug)]
+        pub enum $list {}
+
+        impl CodeList for $list {
+            const NAME: &'static str = $name;
+
+            fn description(_: &ReportForm, code: &str) -> anyhow::Result<Option<String>> {
+                Ok(search_for_name(&$table, code))
+            }
+
+            fn codes(_: &ReportForm) -> anyhow::Result<Vec<String>> {
+                Ok($table.iter().map(|(code, _)| code.to_string()).collect())
+            }
+        }
+
+        impl FixedCodeList for $list {}
+
+        pub type $alias = Code<$list>;
+    };
+}
+
+macro_rules! template_code_list {
+    ($list:ident, $alias:ident, $key:literal, $name:literal) => {
+        #[derive(Debug)]
+        pub enum $list {}
+
+        impl CodeList for $list {
+            const NAME: &'static str = $name;
+
+            fn description(report_form: &ReportForm, code: &str) -> anyhow::Result<Option<String>> {
+                Ok(mapping_from_key(report_form, $key)?.remove(code))
+            }
+
+            fn codes(report_form: &ReportForm) -> anyhow::Result<Vec<String>> {
+                let mut codes = mapping_from_key(report_form, $key)?
+                    .into_keys()
+                    .collect::<Vec<_>>();
+                codes.sort();
+                Ok(codes)
+            }
+        }
+
+        pub type $alias = Code<$list>;
+    };
+}
+
+table_code_list!(GenderList, Gender, GENDER_CODES, "Giới tính");
+table_code_list!(CountryList, Country, COUNTRY_CODES, "Mã quốc gia");
+table_code_list!(CurrencyList, Currency, CURRENCY_CODES, "Loại tiền");
+table_code_list!(
+    AccountTypeList,
+    AccountType,
+    ACCOUNT_TYPE_CODES,
+    "Loại tài khoản"
+);
+table_code_list!(
+    AccountStatusList,
+    AccountStatus,
+    ACCOUNT_STATUS_CODES,
+    "Trạng thái tài khoản"
+);
+table_code_list!(
+    CorporateTypeList,
+    CorporateType,
+    CORPORATE_TYPES,
+    "Loại hình tổ chức"
+);
+table_code_list!(
+    OccupationList,
+    OccupationType,
+    OCCUPATION_CODES,
+    "Nghề nghiệp"
+);
+table_code_list!(
+    PersonalIdList,
+    PersonalIdType,
+    PERSONAL_ID_CODES,
+    "Loại định danh"
+);
+table_code_list!(AgeRangeList, AgeRange, AGE_RANGE_CODES, "Độ tuổi");
+table_code_list!(
+    ProcessedTaskList,
+    ProcessedTaskType,
+    PROCESSED_TASKS,
+    "Mã công việc đã xử lý"
+);
+table_code_list!(
+    AttachmentTypeList,
+    AttachmentType,
+    DOCUMENT_TYPES,
+    "Loại tài liệu"
+);
+table_code_list!(
+    DocumentDirectionList,
+    DocumentDirection,
+    [("0", "Công văn nhận được"), ("1", "Công văn gửi đi")],
+    "Loại công văn"
+);
+table_code_list!(
+    TransactionStatusList,
+    TransactionStatus,
+    [("1", "Đã thực hiện")],
+    "Hiện trạng giao dịch"
+);
+
+template_code_list!(
+    ClauseList,
+    ClauseType,
+    "Phần IV: Loại báo cáo giao dịch đáng ngờ",
+    "Mã điều khoản báo cáo"
+);
+template_code_list!(
+    IndicatorList,
+    IndicatorType,
+    "Phần IV: Dấu hiệu đáng ngờ",
+    "Mã dấu hiệu đáng ngờ"
+);
+template_code_list!(
+    CrimeList,
+    CrimeType,
+    "Phần IV: Nhận định về loại tội phạm có thể liên quan đến giao dịch đáng ngờ",
+    "Mã tội phạm"
+);
+
+#[derive(Debug)]
+pub enum LegalBasisList {}
+
+impl CodeList for LegalBasisList {
+    const NAME: &'static str = "Loại cơ sở nghi ngờ";
+
+    fn description(report_form: &ReportForm, code: &str) -> anyhow::Result<Option<String>> {
+        Ok(
+            legal_basis_mapping_from_key(report_form, "Phần IV: Cơ sở hợp lý để nghi ngờ")?
+                .contains_key(code)
+                .then(|| code.to_string()),
+        )
+    }
+
+    fn codes(report_form: &ReportForm) -> anyhow::Result<Vec<String>> {
+        let mut codes =
+            legal_basis_mapping_from_key(report_form, "Phần IV: Cơ sở hợp lý để nghi ngờ")?
+                .into_keys()
+                .collect::<Vec<_>>();
+        codes.sort();
+        Ok(codes)
+    }
+}
+
+/// Code lists read from the template of the report form, by list name. Fails when one of them
+/// is missing from the form.
+pub fn template_code_lists(
+    report_form: &ReportForm,
+) -> anyhow::Result<Vec<(&'static str, Vec<String>)>> {
+    Ok(vec![
+        (ClauseList::NAME, ClauseList::codes(report_form)?),
+        (IndicatorList::NAME, IndicatorList::codes(report_form)?),
+        (CrimeList::NAME, CrimeList::codes(report_form)?),
+        (LegalBasisList::NAME, LegalBasisList::codes(report_form)?),
+    ])
+}
+
+pub type LegalBasisType = Code<LegalBasisList>;
@@ -1,6 +1,13 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+use crate::payload::code::{
+    AccountStatus, AccountType, AgeRange, CorporateType, Country, Currency, Gender, OccupationType,
+    PersonalIdType,
+};
+use crate::payload::schema::iso_date;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Individual {
     pub id: Option<String>,
     #[serde(rename = "khach_hang_hien_huu")]
@@ -8,13 +15,14 @@ pub struct Individual {
     #[serde(rename = "ho_ten")]
     pub full_name: Option<String>,
     #[serde(rename = "ngay_sinh")]
+    #[schemars(schema_with = "iso_date")]
     pub date_of_birth: Option<String>,
     #[serde(rename = "do_tuoi")]
-    pub age_range: Option<String>,
+    pub age_range: Option<AgeRange>,
     #[serde(rename = "gioi_tinh")]
-    pub gender: Option<String>,
+    pub gender: Option<Gender>,
     #[serde(rename = "quoc_tich")]
-    pub nationality: Option<String>,
+    pub nationality: Option<Country>,
     #[serde(rename = "nghe_nghiep")]
     pub occupation: Option<Occupation>,
     #[serde(rename = "chuc_vu")]
@@ -32,9 +40,12 @@ pub struct Individual {
     pub email: Option<String>,
     #[serde(rename = "tai_khoan")]
     pub accounts: Option<Vec<Account>>,
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub ownership: Option<Ownership>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Organization {
     pub id: Option<String>,
     #[serde(rename = "khach_hang_hien_huu")]
@@ -62,9 +73,28 @@ pub struct Organization {
     pub accounts: Option<Vec<Account>>,
     #[serde(rename = "nguoi_dai_dien")]
     pub representatives: Option<Vec<Representative>>,
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub corporate_owner: Option<CorporateOwner>,
+}
+
+/// Stake of a beneficial owner, held in the customer itself or through one of its parent
+/// organizations. Only used for validation, the portal payload has no field for it.
+#[derive(Debug, Clone, Default)]
+pub struct Ownership {
+    pub through: Option<String>,
+    pub percentage: Option<f64>,
+    pub control_basis: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// Organization holding a stake in a corporate customer, identified by its enterprise code.
+#[derive(Debug, Clone, Default)]
+pub struct CorporateOwner {
+    pub enterprise_code: String,
+    pub percentage: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct BeneficialOwners {
     #[serde(rename = "chu_so_huu_khac")]
     pub other_owners: Option<Vec<Individual>>,
@@ -74,7 +104,7 @@ pub struct BeneficialOwners {
     pub organization_links: Option<Vec<OrganizationLink>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct IndividualLink {
     #[serde(rename = "ten")]
     pub name: Option<String>,
@@ -87,7 +117,7 @@ pub struct IndividualLink {
     pub benefit_group: Option<BenefitGroup>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct OrganizationLink {
     pub id: Option<String>,
     #[serde(rename = "ten")]
@@ -98,7 +128,7 @@ pub struct OrganizationLink {
     pub benefit_group: Option<BenefitGroup>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct BenefitGroup {
     #[serde(rename = "nhom_thong_tin")]
     pub main_group: Option<Vec<PersonRef>>,
@@ -106,7 +136,7 @@ pub struct BenefitGroup {
     pub other_group: Option<Vec<PersonRef>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct PersonRef {
     #[serde(rename = "ho_ten")]
     pub full_name: Option<String>,
@@ -115,17 +145,17 @@ pub struct PersonRef {
     pub id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Occupation {
     #[serde(rename = "ma_nghe_nghiep")]
-    pub occupation_code: Option<String>,
+    pub occupation_code: Option<OccupationType>,
     #[serde(rename = "mo_ta")]
     pub description: Option<String>,
     #[serde(rename = "noi_dung")]
     pub content: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct AddrSimple {
     #[serde(rename = "so_nha")]
     pub street_address: Option<String>,
@@ -134,20 +164,22 @@ pub struct AddrSimple {
     #[serde(rename = "tinh_thanh")]
     pub city_province: Option<String>,
     #[serde(rename = "quoc_gia")]
-    pub country: Option<String>,
+    pub country: Option<Country>,
     #[serde(rename = "dien_thoai")]
     pub phone: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Identification {
     #[serde(rename = "loai_dinh_danh")]
-    pub id_type: Option<String>,
+    pub id_type: Option<PersonalIdType>,
     #[serde(rename = "so_dinh_danh")]
     pub id_number: Option<String>,
     #[serde(rename = "ngay_cap")]
+    #[schemars(schema_with = "iso_date")]
     pub issue_date: Option<String>,
     #[serde(rename = "ngay_het_han")]
+    #[schemars(schema_with = "iso_date")]
     pub expiry_date: Option<String>,
     #[serde(rename = "co_quan_cap")]
     pub issuing_authority: Option<String>,
@@ -155,25 +187,26 @@ pub struct Identification {
     pub place_of_issue: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Account {
     #[serde(rename = "so_tai_khoan")]
     pub account_number: Option<String>,
     #[serde(rename = "ngan_hang")]
     pub bank: Option<Bank>,
     #[serde(rename = "loai_tien")]
-    pub currency_type: Option<String>,
+    pub currency_type: Option<Currency>,
     #[serde(rename = "loai_tai_khoan")]
-    pub account_type: Option<String>,
+    pub account_type: Option<AccountType>,
     #[serde(rename = "ngay_mo")]
+    #[schemars(schema_with = "iso_date")]
     pub open_date: Option<String>,
     #[serde(rename = "trang_thai")]
-    pub status: Option<String>,
+    pub status: Option<AccountStatus>,
     #[serde(rename = "nguoi_duoc_uy_quyen")]
     pub authorized_persons: Option<Vec<PersonRef>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Bank {
     #[serde(rename = "ma_ngan_hang")]
     pub bank_code: Option<String>,
@@ -181,40 +214,43 @@ pub struct Bank {
     pub bank_name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct CodeDesc {
     #[serde(rename = "ma_loai_hinh")]
-    pub type_code: Option<String>,
+    pub type_code: Option<CorporateType>,
     #[serde(rename = "mo_ta")]
     pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct License {
     #[serde(rename = "so_giay_phep")]
     pub license_number: Option<String>,
     #[serde(rename = "ngay_cap")]
+    #[schemars(schema_with = "iso_date")]
     pub issue_date: Option<String>,
     #[serde(rename = "noi_cap")]
     pub issue_place: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct EnterpriseCode {
     #[serde(rename = "ma_so")]
     pub code: Option<String>,
     #[serde(rename = "ngay_cap")]
+    #[schemars(schema_with = "iso_date")]
     pub issue_date: Option<String>,
     #[serde(rename = "noi_cap")]
-    pub issue_place: Option<String>,
+    pub issue_place: Option<Country>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Representative {
     pub id: Option<String>,
     #[serde(rename = "ho_ten")]
     pub full_name: Option<String>,
     #[serde(rename = "ngay_sinh")]
+    #[schemars(schema_with = "iso_date")]
     pub date_of_birth: Option<String>,
     #[serde(rename = "nghe_nghiep")]
     pub occupation: Option<Occupation>,
@@ -227,7 +263,7 @@ pub struct Representative {
     #[serde(rename = "so_dien_thoai")]
     pub phone_number: Option<String>,
     #[serde(rename = "quoc_tich")]
-    pub nationality: Option<String>,
+    pub nationality: Option<Country>,
     #[serde(rename = "thong_tin_dinh_danh")]
     pub identifications: Option<Vec<Identification>>,
 }
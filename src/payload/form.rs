@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::payload::{
@@ -7,13 +8,13 @@ use crate::payload::{
     section4::Section4, section5::Section5, section6::Section6,
 };
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Default, JsonSchema)]
 pub enum CreationStatus {
     #[default]
     #[serde(rename = "DANG_NHAP_LIEU")]
     InProgress,
 }
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Form {
     pub id: Option<i64>,
     #[serde(rename = "str_internal_number")]
@@ -26,20 +27,20 @@ pub struct Form {
     pub others: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Payload {
     #[serde(rename = "Thong_tin_chung")]
     pub general_info: GeneralInfo,
-    #[serde(rename = "Phan_1")]
-    pub section_1: Section1,
-    #[serde(rename = "Phan_2")]
-    pub section_2: Section2,
-    #[serde(rename = "Phan_3")]
-    pub section_3: Section3,
-    #[serde(rename = "Phan_4")]
-    pub section_4: Section4,
-    #[serde(rename = "Phan_5")]
-    pub section_5: Section5,
+    #[serde(rename = "Phan_1", skip_serializing_if = "Option::is_none")]
+    pub section_1: Option<Section1>,
+    #[serde(rename = "Phan_2", skip_serializing_if = "Option::is_none")]
+    pub section_2: Option<Section2>,
+    #[serde(rename = "Phan_3", skip_serializing_if = "Option::is_none")]
+    pub section_3: Option<Section3>,
+    #[serde(rename = "Phan_4", skip_serializing_if = "Option::is_none")]
+    pub section_4: Option<Section4>,
+    #[serde(rename = "Phan_5", skip_serializing_if = "Option::is_none")]
+    pub section_5: Option<Section5>,
     #[serde(skip)]
-    pub section_6: Section6,
+    pub section_6: Option<Section6>,
 }
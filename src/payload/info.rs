@@ -1,8 +1,12 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+use crate::payload::schema::{iso_date, iso_date_or_blank};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct GeneralInfo {
     #[serde(rename = "ngay_bao_cao")]
+    #[schemars(schema_with = "iso_date")]
     pub report_date: Option<String>,
     #[serde(rename = "so_bao_cao")]
     pub report_number: Option<String>,
@@ -16,12 +20,13 @@ pub struct GeneralInfo {
     pub report_form: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Amendment {
     #[serde(rename = "loai_thay_doi")]
     pub change_type: i32,
     #[serde(rename = "so_bao_cao")]
     pub report_number: String,
     #[serde(rename = "ngay_bao_cao")]
+    #[schemars(schema_with = "iso_date_or_blank")]
     pub report_date: String,
 }
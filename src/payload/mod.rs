@@ -1,6 +1,8 @@
+pub mod code;
 pub mod entities;
 pub mod form;
 pub mod info;
+pub mod schema;
 pub mod section1;
 pub mod section2;
 pub mod section3;
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use anyhow::Context;
+use schemars::{Schema, SchemaGenerator, json_schema};
+
+use crate::{
+    payload::{code::template_code_lists, form::Form},
+    template::{ReportForm, report_form_of},
+};
+
+const ISO_DATE_PATTERN: &str = r"^\d{4}-\d{2}-\d{2}$";
+const ISO_DATE_OR_BLANK_PATTERN: &str = r"^(\d{4}-\d{2}-\d{2})?$";
+
+pub fn iso_date(_generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": ["string", "null"],
+        "format": "date",
+        "pattern": ISO_DATE_PATTERN,
+    })
+}
+
+pub fn iso_date_or_blank(_generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "string",
+        "pattern": ISO_DATE_OR_BLANK_PATTERN,
+    })
+}
+
+/// Replaces the values allowed for the code list `name` wherever it appears in the schema.
+fn set_code_list(schema: &mut serde_json::Value, name: &str, codes: &[String]) {
+    match schema {
+        serde_json::Value::Object(object) => {
+            if object.get("description").and_then(|d| d.as_str()) == Some(name)
+                && let Some(serde_json::Value::Array(values)) = object.get_mut("enum")
+            {
+                let nullable = values.iter().any(|value| value.is_null());
+                *values = std::iter::once(String::new())
+                    .chain(codes.iter().cloned())
+                    .map(serde_json::Value::String)
+                    .chain(nullable.then_some(serde_json::Value::Null))
+                    .collect();
+            }
+            object
+                .values_mut()
+                .for_each(|value| set_code_list(value, name, codes));
+        }
+        serde_json::Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| set_code_list(value, name, codes)),
+        _ => {}
+    }
+}
+
+/// JSON Schema of the payload of `report_form`, with the code lists of its template.
+pub fn form_schema(report_form: &ReportForm) -> anyhow::Result<serde_json::Value> {
+    let mut schema = schemars::schema_for!(Form).to_value();
+    for (name, codes) in template_code_lists(report_form)? {
+        set_code_list(&mut schema, name, &codes);
+    }
+    Ok(schema)
+}
+
+static FORM_VALIDATORS: LazyLock<Mutex<HashMap<String, Arc<jsonschema::Validator>>>> =
+    LazyLock::new(Default::default);
+
+fn form_validator(report_form: &ReportForm) -> anyhow::Result<Arc<jsonschema::Validator>> {
+    let mut validators = FORM_VALIDATORS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    if let Some(validator) = validators.get(&report_form.code) {
+        return Ok(validator.clone());
+    }
+
+    let validator = jsonschema::options()
+        .should_validate_formats(true)
+        .build(&form_schema(report_form)?)
+        .map_err(|err| anyhow::anyhow!("{}", err))
+        .with_context(|| {
+            format!(
+                "Không thể tạo JSON Schema của mẫu báo cáo {}",
+                report_form.code
+            )
+        })?;
+    let validator = Arc::new(validator);
+    validators.insert(report_form.code.clone(), validator.clone());
+    Ok(validator)
+}
+
+/// Validates a report against the schema of the form named in its `str_type`.
+pub fn validate_form_value(instance: &serde_json::Value) -> anyhow::Result<()> {
+    let report_form = report_form_of(instance.get("str_type").and_then(|code| code.as_str()))?;
+    let errors = form_validator(report_form)?
+        .iter_errors(instance)
+        .map(|err| format!("{}: {}", err.instance_path, err))
+        .collect::<Vec<_>>();
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow::anyhow!(
+            "Dữ liệu báo cáo không khớp với JSON Schema của mẫu báo cáo {}:\n{}",
+            report_form.code,
+            errors.join("\n")
+        )),
+    }
+}
+
+pub fn validate_form(form: &Form) -> anyhow::Result<()> {
+    let instance = serde_json::to_value(form).context("Không thể chuyển báo cáo sang JSON")?;
+    validate_form_value(&instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::{ExcelParam, default_report_form};
+
+    fn enum_contains(schema: &serde_json::Value, code: &str) -> bool {
+        match schema {
+            serde_json::Value::Object(object) => {
+                object
+                    .get("enum")
+                    .and_then(|e| e.as_array())
+                    .is_some_and(|values| values.iter().any(|value| value.as_str() == Some(code)))
+                    || object.values().any(|value| enum_contains(value, code))
+            }
+            serde_json::Value::Array(values) => {
+                values.iter().any(|value| enum_contains(value, code))
+            }
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn schema_uses_the_code_lists_of_the_form() {
+        let mut report_form = default_report_form().clone();
+        report_form.code = "TEST".to_string();
+        report_form.template.insert(
+            "Phần IV: Dấu hiệu đáng ngờ".to_string(),
+            ExcelParam::Mapping(HashMap::from([(
+                "dau_hieu_rieng".to_string(),
+                "Dấu hiệu riêng của mẫu".to_string(),
+            )])),
+        );
+
+        let default_schema = form_schema(default_report_form()).unwrap();
+        let schema = form_schema(&report_form).unwrap();
+
+        assert!(enum_contains(&default_schema, "d27_k1"));
+        assert!(!enum_contains(&default_schema, "dau_hieu_rieng"));
+        assert!(enum_contains(&schema, "dau_hieu_rieng"));
+        assert!(!enum_contains(&schema, "d27_k1"));
+    }
+}
@@ -1,6 +1,9 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+use crate::payload::code::Country;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Section1 {
     #[serde(rename = "doi_tuong_bao_cao")]
     pub reporting_entity: ReportingEntity,
@@ -10,7 +13,7 @@ pub struct Section1 {
     pub report_preparer: ReportPreparer,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct ReportingEntity {
     #[serde(rename = "ten_doi_tuong_bao_cao")]
     pub name: Option<String>,
@@ -23,7 +26,7 @@ pub struct ReportingEntity {
     pub email: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Address {
     #[serde(rename = "so_nha")]
     pub street_address: String,
@@ -34,10 +37,10 @@ pub struct Address {
     #[serde(rename = "tinh_thanh")]
     pub city_province: String,
     #[serde(rename = "quoc_gia")]
-    pub country: String,
+    pub country: Country,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct TransactionLocation {
     #[serde(rename = "ten_diem_phat_sinh_giao_dich")]
     pub transaction_point_name: String,
@@ -50,10 +53,10 @@ pub struct TransactionLocation {
     #[serde(rename = "tinh_thanh")]
     pub city_province: String,
     #[serde(rename = "quoc_gia")]
-    pub country: String,
+    pub country: Country,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct ResponsiblePerson {
     #[serde(rename = "ho_ten")]
     pub full_name: String,
@@ -65,7 +68,7 @@ pub struct ResponsiblePerson {
     pub position: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct ReportPreparer {
     #[serde(rename = "ho_ten")]
     pub full_name: String,
@@ -1,7 +1,8 @@
 use crate::payload::entities::{BeneficialOwners, Individual, Organization};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Section2 {
     #[serde(rename = "ca_nhan_thuc_hien_giao_dich")]
     pub individuals: Option<Vec<Individual>>,
@@ -14,4 +15,9 @@ pub struct Section2 {
 
     #[serde(rename = "thong_tin_khac_bo_sung")]
     pub additional_info: Option<String>,
+
+    /// Account numbers of every row of the sheet Phần II. Tài khoản, including accounts whose
+    /// CIF matches no customer of the report.
+    #[serde(skip)]
+    pub declared_accounts: Vec<String>,
 }
@@ -1,7 +1,8 @@
 use crate::payload::entities::{Individual, Organization};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Section3 {
     #[serde(rename = "ca_nhan_lien_quan")]
     pub related_individuals: Option<Vec<Individual>>,
@@ -1,6 +1,12 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+use crate::payload::code::{
+    ClauseType, CrimeType, Currency, IndicatorType, LegalBasisType, TransactionStatus,
+};
+use crate::payload::schema::iso_date;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Section4 {
     #[serde(rename = "loai_bao_cao")]
     pub report_type: Option<ReportType>,
@@ -15,10 +21,11 @@ pub struct Section4 {
     pub conclusions: Option<Vec<ConclusionEntry>>,
 
     #[serde(rename = "ngay_phat_hien")]
+    #[schemars(schema_with = "iso_date")]
     pub detection_date: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct ReportType {
     #[serde(rename = "dieu_khoan_bao_cao")]
     pub clauses: Option<Vec<Clause>>,
@@ -27,28 +34,28 @@ pub struct ReportType {
     pub suspicious_indicators: Option<Vec<SuspiciousIndicator>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Clause {
     #[serde(rename = "ma_dieu_khoan")]
-    pub code: Option<String>,
+    pub code: Option<ClauseType>,
     #[serde(rename = "mo_ta")]
     pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct SuspiciousIndicator {
     #[serde(rename = "ma_dau_hieu")]
-    pub code: Option<String>,
+    pub code: Option<IndicatorType>,
     #[serde(rename = "mo_ta")]
     pub description: Option<String>,
     #[serde(rename = "noi_dung_khac_dang_ngo")]
     pub other_content: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct TransactionInfo {
     #[serde(rename = "hien_trang_giao_dich")]
-    pub status: Option<String>,
+    pub status: Option<TransactionStatus>,
 
     #[serde(rename = "thoi_gian_giao_dich")]
     pub time_range: Option<TimeRange>,
@@ -63,23 +70,25 @@ pub struct TransactionInfo {
     pub money_flows: Option<Vec<MoneyFlow>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct TimeRange {
     #[serde(rename = "tu_ngay")]
+    #[schemars(schema_with = "iso_date")]
     pub from: Option<String>,
     #[serde(rename = "den_ngay")]
+    #[schemars(schema_with = "iso_date")]
     pub to: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct AmountEntry {
     #[serde(rename = "loai_tien")]
-    pub currency: Option<String>,
+    pub currency: Option<Currency>,
     #[serde(rename = "so_tien")]
     pub amount: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct MoneyFlow {
     pub id: Option<String>,
     #[serde(rename = "ten_doi_tuong")]
@@ -106,7 +115,7 @@ pub struct MoneyFlow {
     pub outflows: Option<Vec<FlowEntryOut>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct FlowEntryIn {
     #[serde(rename = "ho_ten_nguon")]
     pub source_name: Option<String>,
@@ -125,16 +134,18 @@ pub struct FlowEntryIn {
     #[serde(rename = "tong_so_giao_dich")]
     pub total_transactions: Option<String>,
     #[serde(rename = "giao_dich_tu_ngay")]
+    #[schemars(schema_with = "iso_date")]
     pub tx_from: Option<String>,
     #[serde(rename = "giao_dich_den_ngay")]
+    #[schemars(schema_with = "iso_date")]
     pub tx_to: Option<String>,
     #[serde(rename = "loai_tien")]
-    pub currency: Option<String>,
+    pub currency: Option<Currency>,
     #[serde(rename = "noi_dung")]
     pub content: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct FlowEntryOut {
     #[serde(rename = "ho_ten_dich")]
     pub dest_name: Option<String>,
@@ -153,16 +164,18 @@ pub struct FlowEntryOut {
     #[serde(rename = "tong_so_giao_dich")]
     pub total_transactions: Option<String>,
     #[serde(rename = "giao_dich_tu_ngay")]
+    #[schemars(schema_with = "iso_date")]
     pub tx_from: Option<String>,
     #[serde(rename = "giao_dich_den_ngay")]
+    #[schemars(schema_with = "iso_date")]
     pub tx_to: Option<String>,
     #[serde(rename = "loai_tien")]
-    pub currency: Option<String>,
+    pub currency: Option<Currency>,
     #[serde(rename = "noi_dung")]
     pub content: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Analysis {
     #[serde(rename = "phan_tich_chi_tiet")]
     pub detail: Option<String>,
@@ -170,20 +183,20 @@ pub struct Analysis {
     pub legal_bases: Option<Vec<LegalBasis>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct LegalBasis {
     #[serde(rename = "loai_bao_cao")]
-    pub report_type: Option<String>,
+    pub report_type: Option<LegalBasisType>,
     #[serde(rename = "so_thong_bao")]
     pub notice_number: Option<String>,
     #[serde(rename = "co_so_nghi_ngo")]
     pub basis: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct ConclusionEntry {
     #[serde(rename = "ma_toi_pham")]
-    pub crime_code: Option<String>,
+    pub crime_code: Option<CrimeType>,
     #[serde(rename = "mo_ta")]
     pub description: Option<String>,
     #[serde(rename = "noi_dung_khac_toi_pham_dang_ngo")]
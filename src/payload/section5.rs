@@ -1,15 +1,18 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+use crate::payload::code::{DocumentDirection, ProcessedTaskType};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Section5 {
     #[serde(rename = "cong_viec_da_xu_ly")]
     pub processed_tasks: Option<Vec<ProcessedTask>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct ProcessedTask {
     #[serde(rename = "ma_cong_viec")]
-    pub code: Option<String>,
+    pub code: Option<ProcessedTaskType>,
     #[serde(rename = "mo_ta")]
     pub description: Option<String>,
     #[serde(rename = "cong_van")]
@@ -18,10 +21,10 @@ pub struct ProcessedTask {
     pub other_content: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Document {
     #[serde(rename = "loai_cong_van")]
-    pub doc_type: Option<String>,
+    pub doc_type: Option<DocumentDirection>,
     #[serde(rename = "so_cong_van")]
     pub doc_number: Option<String>,
     #[serde(rename = "ngay_cong_van")]
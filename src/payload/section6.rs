@@ -1,12 +1,17 @@
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+use crate::{attachments::generated::GeneratedAttachment, payload::code::AttachmentType};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct Attachment {
     #[serde(rename = "strId")]
     pub str_id: Option<i64>,
     pub status: Option<String>,
     #[serde(rename = "attachmentType")]
-    pub attachment_type: Option<String>,
+    pub attachment_type: Option<AttachmentType>,
     #[serde(rename = "pageCount")]
     pub page_count: Option<i32>,
     pub description: Option<String>,
@@ -19,12 +24,19 @@ pub struct Attachment {
     pub file: serde_json::Map<String, serde_json::Value>,
     #[serde(skip)]
     pub file_mime: Option<String>,
+    /// File on disk, read only while the attachment is uploaded.
     #[serde(skip)]
-    pub file_content: Option<Vec<u8>>,
+    pub file_path: Option<PathBuf>,
+    /// SHA-256 of the file content, in lowercase hex.
+    #[serde(skip)]
+    pub file_hash: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Section6 {
     #[serde(flatten)]
     pub attachments: Vec<Attachment>,
+    /// Attachments drawn from the report data, written and added when the report is submitted.
+    #[serde(skip)]
+    pub generated: Vec<GeneratedAttachment>,
 }
@@ -0,0 +1,1028 @@
+mod subset;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Read, Write},
+    path::Path,
+    sync::LazyLock,
+};
+
+use anyhow::Context;
+use lopdf::{
+    Dictionary, Document, Object, ObjectId, Stream, StringFormat,
+    content::{Content, Operation},
+    dictionary,
+};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::utils::setup::pdf_without_diacritics_from_args;
+
+pub const A4_WIDTH: f32 = 595.28;
+pub const A4_HEIGHT: f32 = 841.89;
+
+const LOCAL_FONT_FILE: &str = "input/font.ttf";
+
+/// Unicode fonts looked up in order, the first one whose licence allows embedding a subset is
+/// used so that Vietnamese text is rendered with its diacritics. Only the glyphs used by each
+/// document are embedded.
+const FONT_CANDIDATES: [&str; 8] = [
+    LOCAL_FONT_FILE,
+    "C:/Windows/Fonts/arial.ttf",
+    "C:/Windows/Fonts/tahoma.ttf",
+    "C:/Windows/Fonts/times.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+];
+
+static FONT_DATA: LazyLock<Option<Vec<u8>>> = LazyLock::new(|| {
+    let font = FONT_CANDIDATES.iter().find_map(|path| {
+        let data = std::fs::read(path).ok()?;
+        let face = ttf_parser::Face::parse(&data, 0).ok()?;
+        let embeddable = face.permissions() != Some(ttf_parser::Permissions::Restricted)
+            && face.is_subsetting_allowed()
+            && subset::subset_font(&data, &BTreeSet::new()).is_ok();
+        if !embeddable {
+            log::info!(
+                "Bỏ qua font {} do giấy phép không cho phép nhúng vào file PDF hoặc không phải font TrueType",
+                path
+            );
+            return None;
+        }
+        Some(data)
+    });
+    if font.is_none() {
+        log::warn!(
+            "Không tìm thấy font chữ Unicode được phép nhúng vào file PDF. Vui lòng đặt file font TrueType tại {}",
+            LOCAL_FONT_FILE
+        );
+    }
+    font
+});
+
+static FONT_FACE: LazyLock<Option<ttf_parser::Face<'static>>> = LazyLock::new(|| {
+    FONT_DATA
+        .as_ref()
+        .and_then(|data| ttf_parser::Face::parse(data, 0).ok())
+});
+
+/// Text without diacritics, for the built-in PDF font which only covers Latin-1.
+pub fn strip_diacritics(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+        .map(|c| match c {
+            'đ' => 'd',
+            'Đ' => 'D',
+            c => c,
+        })
+        .collect()
+}
+
+/// Width of `text` in points when written with the font used by [`PdfWriter`].
+pub fn text_width(text: &str, size: f32) -> f32 {
+    match FONT_FACE.as_ref() {
+        Some(face) => {
+            let units = face.units_per_em() as f32;
+            text.chars()
+                .map(|c| {
+                    face.glyph_index(c)
+                        .and_then(|gid| face.glyph_hor_advance(gid))
+                        .unwrap_or(0) as f32
+                })
+                .sum::<f32>()
+                * size
+                / units
+        }
+        // Average advance of Helvetica
+        None => strip_diacritics(text).chars().count() as f32 * size * 0.55,
+    }
+}
+
+/// Shortens `text` with an ellipsis so that it fits in `max_width`.
+pub fn fit_text(text: &str, size: f32, max_width: f32) -> String {
+    if text_width(text, size) <= max_width {
+        return text.to_string();
+    }
+    let mut fitted = text.chars().collect::<Vec<_>>();
+    while !fitted.is_empty() {
+        fitted.pop();
+        let candidate = format!("{}…", fitted.iter().collect::<String>());
+        if text_width(&candidate, size) <= max_width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Color(pub f32, pub f32, pub f32);
+
+impl Color {
+    pub const BLACK: Color = Color(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color(1.0, 1.0, 1.0);
+    pub const GRAY: Color = Color(0.6, 0.6, 0.6);
+    pub const LIGHT_GRAY: Color = Color(0.92, 0.92, 0.92);
+
+    /// `#rrggbb` notation, for SVG output.
+    pub fn to_hex(&self) -> String {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            channel(self.0),
+            channel(self.1),
+            channel(self.2)
+        )
+    }
+}
+
+/// Drawing operations of a page in PDF coordinates (origin at the bottom left, in points).
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    operations: Vec<Operation>,
+    images: Vec<(String, ObjectId)>,
+}
+
+fn reals(values: &[f32]) -> Vec<Object> {
+    values.iter().map(|v| Object::Real(*v)).collect()
+}
+
+impl Page {
+    pub fn new(width: f32, height: f32) -> Self {
+        Page {
+            width,
+            height,
+            operations: vec![],
+            images: vec![],
+        }
+    }
+
+    pub fn a4_portrait() -> Self {
+        Self::new(A4_WIDTH, A4_HEIGHT)
+    }
+
+    pub fn a4_landscape() -> Self {
+        Self::new(A4_HEIGHT, A4_WIDTH)
+    }
+
+    fn op(&mut self, operator: &str, operands: Vec<Object>) {
+        self.operations.push(Operation::new(operator, operands));
+    }
+
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Color) {
+        self.op("q", vec![]);
+        self.op("w", reals(&[width]));
+        self.op("RG", reals(&[color.0, color.1, color.2]));
+        self.op("m", reals(&[from.0, from.1]));
+        self.op("l", reals(&[to.0, to.1]));
+        self.op("S", vec![]);
+        self.op("Q", vec![]);
+    }
+
+    /// Rectangle with its bottom left corner at (`x`, `y`).
+    pub fn rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        fill: Option<Color>,
+        stroke: Option<Color>,
+    ) {
+        self.path(
+            &[
+                (x, y),
+                (x + width, y),
+                (x + width, y + height),
+                (x, y + height),
+            ],
+            true,
+            fill,
+            stroke,
+        );
+    }
+
+    pub fn path(
+        &mut self,
+        points: &[(f32, f32)],
+        close: bool,
+        fill: Option<Color>,
+        stroke: Option<Color>,
+    ) {
+        let Some((first, rest)) = points.split_first() else {
+            return;
+        };
+
+        self.op("q", vec![]);
+        self.op("w", reals(&[0.8]));
+        if let Some(fill) = fill {
+            self.op("rg", reals(&[fill.0, fill.1, fill.2]));
+        }
+        if let Some(stroke) = stroke {
+            self.op("RG", reals(&[stroke.0, stroke.1, stroke.2]));
+        }
+        self.op("m", reals(&[first.0, first.1]));
+        for point in rest {
+            self.op("l", reals(&[point.0, point.1]));
+        }
+        if close {
+            self.op("h", vec![]);
+        }
+        let paint = match (fill.is_some(), stroke.is_some()) {
+            (true, true) => "B",
+            (true, false) => "f",
+            (false, true) => "S",
+            (false, false) => "n",
+        };
+        self.op(paint, vec![]);
+        self.op("Q", vec![]);
+    }
+
+    /// Cubic Bézier curve from `from` to `to`.
+    pub fn curve(
+        &mut self,
+        from: (f32, f32),
+        control_1: (f32, f32),
+        control_2: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        color: Color,
+    ) {
+        self.op("q", vec![]);
+        self.op("w", reals(&[width]));
+        self.op("RG", reals(&[color.0, color.1, color.2]));
+        self.op("m", reals(&[from.0, from.1]));
+        self.op(
+            "c",
+            reals(&[
+                control_1.0,
+                control_1.1,
+                control_2.0,
+                control_2.1,
+                to.0,
+                to.1,
+            ]),
+        );
+        self.op("S", vec![]);
+        self.op("Q", vec![]);
+    }
+}
+
+/// An image ready to be placed on a page.
+pub struct PdfImage {
+    pub width: u32,
+    pub height: u32,
+    stream: Stream,
+}
+
+impl PdfImage {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content =
+            std::fs::read(path).with_context(|| format!("Không thể đọc file ảnh {:#?}", path))?;
+        let extension = path
+            .extension()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "jpg" | "jpeg" => Self::from_jpeg(content),
+            "png" => Self::from_png(&content),
+            _ => Err(anyhow::anyhow!(
+                "Định dạng ảnh {} chưa được hỗ trợ",
+                extension
+            )),
+        }
+        .with_context(|| format!("Không thể chuyển file ảnh {:#?} sang PDF", path))
+    }
+
+    /// JPEG data is embedded as is, only the frame header is read for the size.
+    pub fn from_jpeg(content: Vec<u8>) -> anyhow::Result<Self> {
+        let mut pos = 2;
+        while pos + 4 <= content.len() {
+            if content[pos] != 0xFF {
+                pos += 1;
+                continue;
+            }
+            let marker = content[pos + 1];
+            let length = u16::from_be_bytes([content[pos + 2], content[pos + 3]]) as usize;
+            let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_frame {
+                let header = content
+                    .get(pos + 4..pos + 10)
+                    .context("File JPEG không đầy đủ")?;
+                let height = u16::from_be_bytes([header[1], header[2]]) as u32;
+                let width = u16::from_be_bytes([header[3], header[4]]) as u32;
+                let color_space = match header[5] {
+                    1 => "DeviceGray",
+                    4 => "DeviceCMYK",
+                    _ => "DeviceRGB",
+                };
+                let mut dict = dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Image",
+                    "Width" => width as i64,
+                    "Height" => height as i64,
+                    "ColorSpace" => color_space,
+                    "BitsPerComponent" => 8,
+                    "Filter" => "DCTDecode",
+                };
+                if header[5] == 4 {
+                    // Adobe CMYK JPEGs are stored inverted
+                    dict.set("Decode", reals(&[1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0]));
+                }
+                let mut stream = Stream::new(dict, content);
+                stream.allows_compression = false;
+                return Ok(PdfImage {
+                    width,
+                    height,
+                    stream,
+                });
+            }
+            pos += 2 + length;
+        }
+
+        Err(anyhow::anyhow!(
+            "Không tìm thấy kích thước ảnh trong file JPEG"
+        ))
+    }
+
+    /// Non-interlaced PNG. Images without transparency keep their compressed data, the others
+    /// are decoded and flattened onto a white background.
+    pub fn from_png(content: &[u8]) -> anyhow::Result<Self> {
+        if !content.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Err(anyhow::anyhow!("File PNG không hợp lệ"));
+        }
+
+        let mut pos = 8;
+        let mut header = None;
+        let mut palette = vec![];
+        let mut data = vec![];
+        while pos + 8 <= content.len() {
+            let length = u32::from_be_bytes(content[pos..pos + 4].try_into()?) as usize;
+            let kind = &content[pos + 4..pos + 8];
+            let chunk = content
+                .get(pos + 8..pos + 8 + length)
+                .context("File PNG không đầy đủ")?;
+            match kind {
+                b"IHDR" => header = Some(chunk.to_vec()),
+                b"PLTE" => palette = chunk.to_vec(),
+                b"IDAT" => data.extend_from_slice(chunk),
+                b"IEND" => break,
+                _ => {}
+            }
+            pos += 12 + length;
+        }
+
+        let header = header.context("File PNG thiếu thông tin kích thước")?;
+        let width = u32::from_be_bytes(header[0..4].try_into()?);
+        let height = u32::from_be_bytes(header[4..8].try_into()?);
+        let bit_depth = header[8];
+        let color_type = header[9];
+        if header[12] != 0 {
+            return Err(anyhow::anyhow!("Ảnh PNG dạng interlaced chưa được hỗ trợ"));
+        }
+
+        let (color_space, colors): (Object, i64) = match color_type {
+            0 | 4 => ("DeviceGray".into(), 1),
+            2 | 6 => ("DeviceRGB".into(), 3),
+            3 => (
+                vec![
+                    "Indexed".into(),
+                    "DeviceRGB".into(),
+                    Object::Integer((palette.len() / 3).saturating_sub(1) as i64),
+                    Object::String(palette, StringFormat::Hexadecimal),
+                ]
+                .into(),
+                1,
+            ),
+            _ => return Err(anyhow::anyhow!("Kiểu màu PNG {} không hợp lệ", color_type)),
+        };
+
+        let mut dict = dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width as i64,
+            "Height" => height as i64,
+            "ColorSpace" => color_space,
+            "Filter" => "FlateDecode",
+        };
+
+        if matches!(color_type, 4 | 6) {
+            let pixels = flatten_alpha(&data, width, height, bit_depth, colors as usize)?;
+            dict.set("BitsPerComponent", 8);
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+            encoder.write_all(&pixels)?;
+            let mut stream = Stream::new(dict, encoder.finish()?);
+            stream.allows_compression = false;
+            return Ok(PdfImage {
+                width,
+                height,
+                stream,
+            });
+        }
+
+        dict.set("BitsPerComponent", bit_depth as i64);
+        dict.set(
+            "DecodeParms",
+            dictionary! {
+                "Predictor" => 15,
+                "Colors" => colors,
+                "BitsPerComponent" => bit_depth as i64,
+                "Columns" => width as i64,
+            },
+        );
+        let mut stream = Stream::new(dict, data);
+        stream.allows_compression = false;
+        Ok(PdfImage {
+            width,
+            height,
+            stream,
+        })
+    }
+}
+
+/// Decodes PNG scanlines with an alpha channel into 8-bit samples composited onto white.
+fn flatten_alpha(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    colors: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut raw = vec![];
+    flate2::read::ZlibDecoder::new(data).read_to_end(&mut raw)?;
+
+    let sample_bytes = (bit_depth as usize).div_ceil(8);
+    let bpp = (colors + 1) * sample_bytes;
+    let stride = width as usize * bpp;
+    let mut previous = vec![0u8; stride];
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * colors);
+
+    for row in 0..height as usize {
+        let start = row * (stride + 1);
+        let line = raw
+            .get(start..start + stride + 1)
+            .context("Dữ liệu ảnh PNG không đầy đủ")?;
+        let filter = line[0];
+        let mut current = line[1..].to_vec();
+
+        for i in 0..stride {
+            let left = if i >= bpp { current[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+            current[i] = match filter {
+                0 => current[i],
+                1 => current[i].wrapping_add(left),
+                2 => current[i].wrapping_add(up),
+                3 => current[i].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                4 => {
+                    let p = left as i16 + up as i16 - up_left as i16;
+                    let (pa, pb, pc) = (
+                        (p - left as i16).abs(),
+                        (p - up as i16).abs(),
+                        (p - up_left as i16).abs(),
+                    );
+                    let predictor = if pa <= pb && pa <= pc {
+                        left
+                    } else if pb <= pc {
+                        up
+                    } else {
+                        up_left
+                    };
+                    current[i].wrapping_add(predictor)
+                }
+                _ => return Err(anyhow::anyhow!("Bộ lọc PNG {} không hợp lệ", filter)),
+            };
+        }
+
+        for pixel in current.chunks(bpp) {
+            // The most significant byte is enough for 16-bit samples
+            let alpha = pixel[colors * sample_bytes] as u16;
+            for channel in 0..colors {
+                let value = pixel[channel * sample_bytes] as u16;
+                pixels.push(((value * alpha + 255 * (255 - alpha)) / 255) as u8);
+            }
+        }
+        previous = current;
+    }
+
+    Ok(pixels)
+}
+
+/// Builds a PDF document page by page. Text is written with the embedded Unicode font when one is
+/// available.
+pub struct PdfWriter {
+    doc: Document,
+    pages_id: ObjectId,
+    font_id: ObjectId,
+    page_ids: Vec<ObjectId>,
+    used_glyphs: BTreeMap<u16, char>,
+    /// Text written without its diacritics because no Unicode font was found
+    stripped_text: Option<String>,
+}
+
+impl Default for PdfWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfWriter {
+    pub fn new() -> Self {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.new_object_id();
+        PdfWriter {
+            doc,
+            pages_id,
+            font_id,
+            page_ids: vec![],
+            used_glyphs: BTreeMap::new(),
+            stripped_text: None,
+        }
+    }
+
+    fn encode_text(&mut self, text: &str) -> Object {
+        match FONT_FACE.as_ref() {
+            Some(face) => {
+                let mut bytes = vec![];
+                for c in text.chars() {
+                    let gid = face.glyph_index(c).map(|g| g.0).unwrap_or(0);
+                    self.used_glyphs.entry(gid).or_insert(c);
+                    bytes.extend_from_slice(&gid.to_be_bytes());
+                }
+                Object::String(bytes, StringFormat::Hexadecimal)
+            }
+            None => {
+                let stripped = strip_diacritics(text);
+                if self.stripped_text.is_none()
+                    && (stripped != text || stripped.chars().any(|c| c as u32 > 0xFF))
+                {
+                    self.stripped_text = Some(text.to_string());
+                }
+                let bytes = stripped
+                    .chars()
+                    .map(|c| match c as u32 {
+                        0x20..=0xFF => c as u8,
+                        _ => b'?',
+                    })
+                    .collect();
+                Object::String(bytes, StringFormat::Literal)
+            }
+        }
+    }
+
+    /// Writes `text` with its baseline starting at (`x`, `y`).
+    pub fn text(&mut self, page: &mut Page, x: f32, y: f32, size: f32, color: Color, text: &str) {
+        let encoded = self.encode_text(text);
+        page.op("BT", vec![]);
+        page.op("rg", reals(&[color.0, color.1, color.2]));
+        page.op("Tf", vec!["F1".into(), Object::Real(size)]);
+        page.op("Td", reals(&[x, y]));
+        page.op("Tj", vec![encoded]);
+        page.op("ET", vec![]);
+    }
+
+    /// Draws `image` in the box with its bottom left corner at (`x`, `y`).
+    pub fn image(
+        &mut self,
+        page: &mut Page,
+        image: PdfImage,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    ) {
+        let image_id = self.doc.add_object(image.stream);
+        let name = format!("Im{}", page.images.len() + 1);
+        page.images.push((name.clone(), image_id));
+        page.op("q", vec![]);
+        page.op("cm", reals(&[width, 0.0, 0.0, height, x, y]));
+        page.op("Do", vec![Object::Name(name.into_bytes())]);
+        page.op("Q", vec![]);
+    }
+
+    /// Writes a table on as many A4 landscape pages as needed, repeating the header row on each
+    /// page. Columns listed in `right_aligned` (amounts) are aligned to the right of their cell.
+    pub fn table(
+        &mut self,
+        title: &str,
+        header: &[String],
+        body: &[Vec<String>],
+        right_aligned: &[usize],
+    ) -> anyhow::Result<()> {
+        const MARGIN: f32 = 30.0;
+        const FONT_SIZE: f32 = 7.0;
+        const ROW_HEIGHT: f32 = 11.0;
+        const PADDING: f32 = 3.0;
+        const MAX_COLUMN_WIDTH: f32 = 220.0;
+
+        let available_width = Page::a4_landscape().width - 2.0 * MARGIN;
+        let mut widths = vec![0f32; header.len()];
+        for row in std::iter::once(header).chain(body.iter().map(|row| row.as_slice())) {
+            for (idx, cell) in row.iter().enumerate().take(widths.len()) {
+                let width = text_width(cell, FONT_SIZE) + 2.0 * PADDING;
+                widths[idx] = widths[idx].max(width.min(MAX_COLUMN_WIDTH));
+            }
+        }
+        let total_width = widths.iter().sum::<f32>();
+        if total_width > available_width {
+            widths
+                .iter_mut()
+                .for_each(|w| *w *= available_width / total_width);
+        }
+
+        let rows_per_page =
+            ((Page::a4_landscape().height - 2.0 * MARGIN - 30.0) / ROW_HEIGHT) as usize - 1;
+        let page_chunks = match body.is_empty() {
+            true => vec![body],
+            false => body.chunks(rows_per_page.max(1)).collect(),
+        };
+        let page_total = page_chunks.len();
+
+        for (page_idx, chunk) in page_chunks.into_iter().enumerate() {
+            let mut page = Page::a4_landscape();
+            let (top, right) = (page.height - MARGIN, page.width - MARGIN);
+            self.text(&mut page, MARGIN, top - 10.0, 10.0, Color::BLACK, title);
+            self.text(
+                &mut page,
+                right - 60.0,
+                MARGIN / 2.0,
+                FONT_SIZE,
+                Color::GRAY,
+                &format!("Trang {}/{}", page_idx + 1, page_total),
+            );
+
+            let mut y = top - 24.0;
+            let rows = std::iter::once(header).chain(chunk.iter().map(|row| row.as_slice()));
+            for (row_idx, row) in rows.enumerate() {
+                let fill = (row_idx == 0).then_some(Color::LIGHT_GRAY);
+                let mut x = MARGIN;
+                for (idx, width) in widths.iter().enumerate() {
+                    page.rect(
+                        x,
+                        y - ROW_HEIGHT,
+                        *width,
+                        ROW_HEIGHT,
+                        fill,
+                        Some(Color::GRAY),
+                    );
+                    let cell = row.get(idx).map(|s| s.as_str()).unwrap_or_default();
+                    let text = fit_text(cell, FONT_SIZE, width - 2.0 * PADDING);
+                    let text_x = match row_idx > 0 && right_aligned.contains(&idx) {
+                        true => x + width - PADDING - text_width(&text, FONT_SIZE),
+                        false => x + PADDING,
+                    };
+                    self.text(
+                        &mut page,
+                        text_x,
+                        y - ROW_HEIGHT + 3.0,
+                        FONT_SIZE,
+                        Color::BLACK,
+                        &text,
+                    );
+                    x += width;
+                }
+                y -= ROW_HEIGHT;
+            }
+            self.add_page(page)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn add_page(&mut self, page: Page) -> anyhow::Result<()> {
+        let content = Content {
+            operations: page.operations,
+        };
+        let content_id = self
+            .doc
+            .add_object(Stream::new(dictionary! {}, content.encode()?));
+
+        let mut x_objects = Dictionary::new();
+        for (name, image_id) in page.images {
+            x_objects.set(name, image_id);
+        }
+
+        let page_id = self.doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => self.pages_id,
+            "Contents" => content_id,
+            "MediaBox" => reals(&[0.0, 0.0, page.width, page.height]),
+            "Resources" => dictionary! {
+                "Font" => dictionary! { "F1" => self.font_id },
+                "XObject" => x_objects,
+            },
+        });
+        self.page_ids.push(page_id);
+        Ok(())
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.page_ids.len()
+    }
+
+    fn write_font(&mut self) -> anyhow::Result<()> {
+        let (Some(face), Some(data)) = (FONT_FACE.as_ref(), FONT_DATA.as_ref()) else {
+            self.doc.objects.insert(
+                self.font_id,
+                Object::Dictionary(dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                    "Encoding" => "WinAnsiEncoding",
+                }),
+            );
+            return Ok(());
+        };
+
+        let scale = 1000.0 / face.units_per_em() as f32;
+        let font_name = face
+            .names()
+            .into_iter()
+            .find(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .and_then(|name| name.to_string())
+            .unwrap_or_else(|| "EmbeddedFont".to_string())
+            .replace(' ', "");
+        let bbox = face.global_bounding_box();
+
+        let glyphs = self.used_glyphs.keys().copied().collect::<BTreeSet<_>>();
+        let font_data = subset::subset_font(data, &glyphs)?;
+        // Tag marking a subset font, as required for embedded subsets
+        let font_name = format!("AMLSUB+{}", font_name);
+        let mut font_file = Stream::new(
+            dictionary! { "Length1" => font_data.len() as i64 },
+            font_data,
+        );
+        font_file.compress()?;
+        let font_file_id = self.doc.add_object(font_file);
+
+        let descriptor_id = self.doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => Object::Name(font_name.clone().into_bytes()),
+            "Flags" => 32,
+            "FontBBox" => reals(&[
+                bbox.x_min as f32 * scale,
+                bbox.y_min as f32 * scale,
+                bbox.x_max as f32 * scale,
+                bbox.y_max as f32 * scale,
+            ]),
+            "ItalicAngle" => 0,
+            "Ascent" => Object::Real(face.ascender() as f32 * scale),
+            "Descent" => Object::Real(face.descender() as f32 * scale),
+            "CapHeight" => Object::Real(face.capital_height().unwrap_or(face.ascender()) as f32 * scale),
+            "StemV" => 80,
+            "FontFile2" => font_file_id,
+        });
+
+        let widths = self
+            .used_glyphs
+            .keys()
+            .flat_map(|gid| {
+                let advance = face
+                    .glyph_hor_advance(ttf_parser::GlyphId(*gid))
+                    .unwrap_or(0) as f32
+                    * scale;
+                vec![
+                    Object::Integer(*gid as i64),
+                    Object::Array(vec![Object::Real(advance)]),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let cid_font_id = self.doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => Object::Name(font_name.clone().into_bytes()),
+            "CIDSystemInfo" => dictionary! {
+                "Registry" => Object::string_literal("Adobe"),
+                "Ordering" => Object::string_literal("Identity"),
+                "Supplement" => 0,
+            },
+            "FontDescriptor" => descriptor_id,
+            "W" => widths,
+            "CIDToGIDMap" => "Identity",
+        });
+
+        let to_unicode_id = self.doc.add_object(Stream::new(
+            dictionary! {},
+            self.to_unicode_cmap().into_bytes(),
+        ));
+
+        self.doc.objects.insert(
+            self.font_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Font",
+                "Subtype" => "Type0",
+                "BaseFont" => Object::Name(font_name.into_bytes()),
+                "Encoding" => "Identity-H",
+                "DescendantFonts" => vec![cid_font_id.into()],
+                "ToUnicode" => to_unicode_id,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Maps the glyph ids back to text so that the PDF stays searchable.
+    fn to_unicode_cmap(&self) -> String {
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        let glyphs = self.used_glyphs.iter().collect::<Vec<_>>();
+        for block in glyphs.chunks(100) {
+            cmap.push_str(&format!("{} beginbfchar\n", block.len()));
+            for (gid, c) in block {
+                let unicode = c
+                    .encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("{:04X}", unit))
+                    .collect::<String>();
+                cmap.push_str(&format!("<{:04X}> <{}>\n", gid, unicode));
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        cmap
+    }
+
+    pub fn finish(mut self) -> anyhow::Result<Vec<u8>> {
+        if let Some(text) = self.stripped_text.as_ref()
+            && !pdf_without_diacritics_from_args()
+        {
+            return Err(anyhow::anyhow!(
+                "Không tìm thấy font chữ Unicode để ghi '{}' vào file PDF. Vui lòng đặt file font TrueType tại {}, hoặc chạy lại với tham số --pdf-without-diacritics để tạo file PDF không có dấu tiếng Việt",
+                text,
+                LOCAL_FONT_FILE
+            ));
+        }
+        self.write_font()?;
+
+        let kids = self
+            .page_ids
+            .iter()
+            .map(|id| Object::Reference(*id))
+            .collect::<Vec<_>>();
+        self.doc.objects.insert(
+            self.pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => kids.len() as i64,
+                "Kids" => kids,
+            }),
+        );
+        let catalog_id = self.doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => self.pages_id,
+        });
+        self.doc.trailer.set("Root", catalog_id);
+        self.doc.compress();
+
+        let mut buffer = vec![];
+        self.doc.save_to(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// Concatenates the pages of several PDF files into one document.
+pub fn merge_pdfs(files: &[std::path::PathBuf]) -> anyhow::Result<Vec<u8>> {
+    let mut merged = Document::with_version("1.5");
+    let pages_id = merged.new_object_id();
+    let mut kids = vec![];
+    let mut max_id = merged.max_id + 1;
+
+    for file in files {
+        let mut doc = Document::load(file)
+            .with_context(|| format!("Không thể đọc được file PDF {:#?}", file))?;
+        doc.renumber_objects_with(max_id);
+        max_id = doc.max_id + 1;
+
+        // Attributes a page may inherit from the page tree it leaves behind
+        const INHERITED: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+        let pages = doc.get_pages();
+        let mut page_dicts = vec![];
+        for page_id in pages.values() {
+            let mut page = doc.get_dictionary(*page_id)?.clone();
+            let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+            while let Some(parent_id) = parent {
+                let parent_dict = doc.get_dictionary(parent_id)?;
+                for key in INHERITED {
+                    if !page.has(key)
+                        && let Ok(value) = parent_dict.get(key)
+                    {
+                        page.set(key, value.clone());
+                    }
+                }
+                parent = parent_dict
+                    .get(b"Parent")
+                    .and_then(Object::as_reference)
+                    .ok();
+            }
+            page.set("Parent", pages_id);
+            page_dicts.push((*page_id, page));
+        }
+
+        for (object_id, object) in doc.objects.into_iter() {
+            match object.type_name().unwrap_or(b"") {
+                b"Catalog" | b"Pages" | b"Outlines" | b"Outline" => {}
+                _ => {
+                    merged.objects.insert(object_id, object);
+                }
+            }
+        }
+        for (page_id, page) in page_dicts {
+            merged.objects.insert(page_id, Object::Dictionary(page));
+            kids.push(Object::Reference(page_id));
+        }
+    }
+
+    merged.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+        }),
+    );
+    // The source objects were inserted directly, the catalog must be numbered after them
+    merged.max_id = max_id - 1;
+    let catalog_id = merged.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    merged.trailer.set("Root", catalog_id);
+    merged.compress();
+
+    let mut buffer = vec![];
+    merged.save_to(&mut buffer)?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use lopdf::Stream;
+
+    use super::*;
+
+    fn single_page_pdf(name: &str, text: &str) -> std::path::PathBuf {
+        let mut doc = Document::with_version("1.5");
+        // The content stream takes the lowest object number, as in many generated files
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text).into_bytes(),
+        ));
+        let pages_id = doc.new_object_id();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => 1,
+                "Kids" => vec![Object::Reference(page_id)],
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let path =
+            std::env::temp_dir().join(format!("aml_merge_{}_{}.pdf", std::process::id(), name));
+        doc.save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn merged_pages_keep_their_content_streams() {
+        let files = [
+            single_page_pdf("first", "Trang 1"),
+            single_page_pdf("second", "Trang 2"),
+        ];
+        let merged = Document::load_mem(&merge_pdfs(&files).unwrap()).unwrap();
+        files
+            .iter()
+            .for_each(|file| std::fs::remove_file(file).unwrap());
+
+        let pages = merged.get_pages();
+        assert_eq!(pages.len(), 2);
+        for page_id in pages.values() {
+            let page = merged.get_dictionary(*page_id).unwrap();
+            assert!(page.has(b"MediaBox"));
+            let contents = page
+                .get(b"Contents")
+                .and_then(Object::as_reference)
+                .unwrap();
+            assert!(merged.get_object(contents).unwrap().as_stream().is_ok());
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! TrueType subsetting for the embedded font. Glyphs that are not used are emptied rather than
+//! removed so that the glyph ids written in the content streams stay valid.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+
+/// Tables a TrueType font embedded in a PDF needs, the character map is not used with
+/// `Identity-H` encoded text.
+const KEPT_TABLES: [&[u8; 4]; 9] = [
+    b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"prep",
+];
+
+// Flags of a composite glyph component
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+fn malformed() -> anyhow::Error {
+    anyhow::anyhow!("File font không hợp lệ")
+}
+
+fn read_u16(data: &[u8], offset: usize) -> anyhow::Result<u16> {
+    let bytes = data.get(offset..offset + 2).ok_or_else(malformed)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> anyhow::Result<u32> {
+    let bytes = data.get(offset..offset + 4).ok_or_else(malformed)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+struct FontTables<'a> {
+    tables: Vec<([u8; 4], &'a [u8])>,
+}
+
+impl<'a> FontTables<'a> {
+    fn parse(data: &'a [u8]) -> anyhow::Result<Self> {
+        let n_tables = read_u16(data, 4)? as usize;
+        let tables = (0..n_tables)
+            .map(|idx| {
+                let record = 12 + idx * 16;
+                let tag = data.get(record..record + 4).ok_or_else(malformed)?;
+                let offset = read_u32(data, record + 8)? as usize;
+                let length = read_u32(data, record + 12)? as usize;
+                let table = offset
+                    .checked_add(length)
+                    .and_then(|end| data.get(offset..end))
+                    .ok_or_else(malformed)?;
+                Ok(([tag[0], tag[1], tag[2], tag[3]], table))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(FontTables { tables })
+    }
+
+    fn get(&self, tag: &[u8; 4]) -> Option<&'a [u8]> {
+        self.tables
+            .iter()
+            .find(|(t, _)| t == tag)
+            .map(|(_, table)| *table)
+    }
+}
+
+/// Byte range of every glyph in `glyf`, from the `loca` table.
+fn glyph_ranges(tables: &FontTables) -> anyhow::Result<Vec<(usize, usize)>> {
+    let head = tables
+        .get(b"head")
+        .context("File font không có bảng head")?;
+    let maxp = tables
+        .get(b"maxp")
+        .context("File font không có bảng maxp")?;
+    let loca = tables
+        .get(b"loca")
+        .context("File font không có bảng loca")?;
+    let long_offsets = read_u16(head, 50)? == 1;
+    let n_glyphs = read_u16(maxp, 4)? as usize;
+
+    let offset = |idx: usize| -> anyhow::Result<usize> {
+        match long_offsets {
+            true => Ok(read_u32(loca, idx * 4)? as usize),
+            false => Ok(read_u16(loca, idx * 2)? as usize * 2),
+        }
+    };
+    (0..n_glyphs)
+        .map(|gid| Ok((offset(gid)?, offset(gid + 1)?)))
+        .collect()
+}
+
+/// Glyphs referenced by a composite glyph.
+fn components(glyph: &[u8]) -> anyhow::Result<Vec<u16>> {
+    if glyph.len() < 10 || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(vec![]);
+    }
+
+    let mut components = vec![];
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)?);
+        offset += 4;
+        offset += match flags & ARG_1_AND_2_ARE_WORDS {
+            0 => 2,
+            _ => 4,
+        };
+        offset += match flags {
+            f if f & WE_HAVE_A_SCALE != 0 => 2,
+            f if f & WE_HAVE_AN_X_AND_Y_SCALE != 0 => 4,
+            f if f & WE_HAVE_A_TWO_BY_TWO != 0 => 8,
+            _ => 0,
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    Ok(components)
+}
+
+fn checksum(table: &[u8]) -> u32 {
+    table.chunks(4).fold(0u32, |acc, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        acc.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Font with only the outlines of `used` (and the glyphs they are built from) and only the
+/// tables needed to render them.
+pub fn subset_font(data: &[u8], used: &BTreeSet<u16>) -> anyhow::Result<Vec<u8>> {
+    let tables = FontTables::parse(data)?;
+    let glyf = tables
+        .get(b"glyf")
+        .context("File font không có bảng glyf")?;
+    let ranges = glyph_ranges(&tables)?;
+    let glyph = |gid: u16| -> anyhow::Result<&[u8]> {
+        let (start, end) = *ranges.get(gid as usize).ok_or_else(malformed)?;
+        glyf.get(start..end).ok_or_else(malformed)
+    };
+
+    // The notdef glyph is always kept
+    let mut kept = BTreeSet::new();
+    let mut pending = std::iter::once(0)
+        .chain(used.iter().copied())
+        .collect::<Vec<_>>();
+    while let Some(gid) = pending.pop() {
+        if (gid as usize) < ranges.len() && kept.insert(gid) {
+            pending.extend(components(glyph(gid)?)?);
+        }
+    }
+
+    let mut new_glyf = vec![];
+    let mut new_loca = vec![];
+    for gid in 0..ranges.len() {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if kept.contains(&(gid as u16)) {
+            new_glyf.extend_from_slice(glyph(gid as u16)?);
+            new_glyf.resize(new_glyf.len().next_multiple_of(4), 0);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    // Long loca offsets, and no whole-file checksum since it is not checked by PDF readers
+    let mut new_head = tables.get(b"head").ok_or_else(malformed)?.to_vec();
+    new_head
+        .get_mut(8..12)
+        .ok_or_else(malformed)?
+        .copy_from_slice(&[0; 4]);
+    new_head
+        .get_mut(50..52)
+        .ok_or_else(malformed)?
+        .copy_from_slice(&1u16.to_be_bytes());
+
+    let mut output_tables = KEPT_TABLES
+        .iter()
+        .filter_map(|tag| {
+            let table = match *tag {
+                b"glyf" => new_glyf.clone(),
+                b"loca" => new_loca.clone(),
+                b"head" => new_head.clone(),
+                _ => tables.get(tag)?.to_vec(),
+            };
+            Some((**tag, table))
+        })
+        .collect::<Vec<_>>();
+    output_tables.sort_by_key(|(tag, _)| *tag);
+
+    let n_tables = output_tables.len() as u16;
+    let entry_selector = 15 - n_tables.leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    let mut font = vec![];
+    font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    font.extend_from_slice(&n_tables.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&(n_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + output_tables.len() * 16;
+    let mut body = vec![];
+    for (tag, table) in output_tables.iter() {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(table).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+
+        body.extend_from_slice(table);
+        body.resize(body.len().next_multiple_of(4), 0);
+        offset = 12 + output_tables.len() * 16 + body.len();
+    }
+    font.extend_from_slice(&body);
+
+    Ok(font)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_glyphs_list_their_components() {
+        let mut glyph = vec![0xFF, 0xFF, 0, 0, 0, 0, 0, 0, 0, 0];
+        // Word arguments and a scale, followed by another component
+        glyph.extend_from_slice(
+            &(ARG_1_AND_2_ARE_WORDS | WE_HAVE_A_SCALE | MORE_COMPONENTS).to_be_bytes(),
+        );
+        glyph.extend_from_slice(&36u16.to_be_bytes());
+        glyph.extend_from_slice(&[0; 6]);
+        glyph.extend_from_slice(&0u16.to_be_bytes());
+        glyph.extend_from_slice(&612u16.to_be_bytes());
+        glyph.extend_from_slice(&[0; 2]);
+
+        assert_eq!(components(&glyph).unwrap(), vec![36, 612]);
+    }
+
+    #[test]
+    fn simple_glyphs_have_no_components() {
+        let glyph = [0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3];
+        assert!(components(&glyph).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,532 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::{
+    payload::{
+        code::Currency,
+        form::Payload,
+        section4::{AmountEntry, MoneyFlow, TransactionInfo},
+    },
+    utils::number,
+};
+
+// Converted amounts are typed in VND, anything below 1 VND is rounding noise
+const AMOUNT_TOLERANCE: f64 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct Discrepancy {
+    pub location: String,
+    pub field: &'static str,
+    pub declared: String,
+    pub computed: String,
+}
+
+impl std::fmt::Display for Discrepancy {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} - {}: giá trị khai báo '{}', giá trị tính từ chi tiết giao dịch '{}'",
+            self.location, self.field, self.declared, self.computed
+        )
+    }
+}
+
+struct FlowEntry<'a> {
+    total_amount: Option<&'a String>,
+    total_converted: Option<&'a String>,
+    total_transactions: Option<&'a String>,
+    tx_from: Option<&'a String>,
+    tx_to: Option<&'a String>,
+    currency: Option<&'a Currency>,
+}
+
+fn inflow_entries(flow: &MoneyFlow) -> Vec<FlowEntry<'_>> {
+    flow.inflows
+        .iter()
+        .flatten()
+        .map(|e| FlowEntry {
+            total_amount: e.total_amount.as_ref(),
+            total_converted: e.total_converted.as_ref(),
+            total_transactions: e.total_transactions.as_ref(),
+            tx_from: e.tx_from.as_ref(),
+            tx_to: e.tx_to.as_ref(),
+            currency: e.currency.as_ref(),
+        })
+        .collect()
+}
+
+fn outflow_entries(flow: &MoneyFlow) -> Vec<FlowEntry<'_>> {
+    flow.outflows
+        .iter()
+        .flatten()
+        .map(|e| FlowEntry {
+            total_amount: e.total_amount.as_ref(),
+            total_converted: e.total_converted.as_ref(),
+            total_transactions: e.total_transactions.as_ref(),
+            tx_from: e.tx_from.as_ref(),
+            tx_to: e.tx_to.as_ref(),
+            currency: e.currency.as_ref(),
+        })
+        .collect()
+}
+
+// Same parsers as the Section IV reader, so the totals are computed the same way on both sides
+pub(crate) fn parse_amount(value: Option<&String>) -> Option<f64> {
+    value.and_then(|v| number::parse_amount(v))
+}
+
+pub(crate) fn parse_count(value: Option<&String>) -> Option<i64> {
+    value.and_then(|v| number::parse_count(v))
+}
+
+pub(crate) fn parse_date(value: Option<&String>) -> Option<NaiveDate> {
+    value.and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+}
+
+fn display_declared(value: Option<&String>) -> String {
+    value.cloned().unwrap_or_else(|| "(trống)".to_string())
+}
+
+fn flow_location(flow: &MoneyFlow) -> String {
+    format!(
+        "Dòng tiền của tài khoản {} ({})",
+        flow.account_number.clone().unwrap_or_default(),
+        flow.subject_name.clone().unwrap_or_default()
+    )
+}
+
+fn check_amount(
+    location: &str,
+    field: &'static str,
+    declared: &mut Option<String>,
+    computed: f64,
+    fill: bool,
+    discrepancies: &mut Vec<Discrepancy>,
+) {
+    let matched = parse_amount(declared.as_ref())
+        .is_some_and(|declared| (declared - computed).abs() < AMOUNT_TOLERANCE);
+
+    if !matched {
+        discrepancies.push(Discrepancy {
+            location: location.to_string(),
+            field,
+            declared: display_declared(declared.as_ref()),
+            computed: computed.to_string(),
+        });
+        if fill {
+            *declared = computed.to_string().into();
+        }
+    }
+}
+
+fn check_count(
+    location: &str,
+    field: &'static str,
+    declared: &mut Option<String>,
+    computed: i64,
+    fill: bool,
+    discrepancies: &mut Vec<Discrepancy>,
+) {
+    if parse_count(declared.as_ref()) != Some(computed) {
+        discrepancies.push(Discrepancy {
+            location: location.to_string(),
+            field,
+            declared: display_declared(declared.as_ref()),
+            computed: computed.to_string(),
+        });
+        if fill {
+            *declared = computed.to_string().into();
+        }
+    }
+}
+
+/// Flags the amount and count cells of the transaction detail that are filled but are not
+/// numbers. They would otherwise count as 0 in every total.
+fn check_entry_cells(flow: &MoneyFlow, discrepancies: &mut Vec<Discrepancy>) {
+    let location = flow_location(flow);
+    let entries = inflow_entries(flow)
+        .into_iter()
+        .chain(outflow_entries(flow))
+        .collect::<Vec<_>>();
+
+    for entry in entries {
+        let cells = [
+            (
+                "Tổng số tiền nguyên tệ",
+                entry.total_amount,
+                parse_amount(entry.total_amount).is_some(),
+            ),
+            (
+                "Tổng số tiền quy đổi (VND)",
+                entry.total_converted,
+                parse_amount(entry.total_converted).is_some(),
+            ),
+            (
+                "Tổng số lượng giao dịch",
+                entry.total_transactions,
+                parse_count(entry.total_transactions).is_some(),
+            ),
+        ];
+        for (field, value, parsed) in cells {
+            if value.is_some_and(|v| !v.trim().is_empty()) && !parsed {
+                discrepancies.push(Discrepancy {
+                    location: location.clone(),
+                    field,
+                    declared: display_declared(value),
+                    computed: "không phải là số".to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn reconcile_flow_totals(flow: &mut MoneyFlow, fill: bool, discrepancies: &mut Vec<Discrepancy>) {
+    check_entry_cells(flow, discrepancies);
+    let location = flow_location(flow);
+
+    let sum_converted = |entries: &[FlowEntry]| {
+        entries
+            .iter()
+            .filter_map(|e| parse_amount(e.total_converted))
+            .sum::<f64>()
+    };
+    let sum_count = |entries: &[FlowEntry]| {
+        entries
+            .iter()
+            .filter_map(|e| parse_count(e.total_transactions))
+            .sum::<i64>()
+    };
+
+    let (converted_in, count_in) = {
+        let entries = inflow_entries(flow);
+        (sum_converted(&entries), sum_count(&entries))
+    };
+    let (converted_out, count_out) = {
+        let entries = outflow_entries(flow);
+        (sum_converted(&entries), sum_count(&entries))
+    };
+
+    check_amount(
+        &location,
+        "Tổng số tiền quy đổi vào",
+        &mut flow.total_converted_in,
+        converted_in,
+        fill,
+        discrepancies,
+    );
+    check_count(
+        &location,
+        "Tổng số giao dịch vào",
+        &mut flow.total_transactions_in,
+        count_in,
+        fill,
+        discrepancies,
+    );
+    check_amount(
+        &location,
+        "Tổng số tiền quy đổi ra",
+        &mut flow.total_converted_out,
+        converted_out,
+        fill,
+        discrepancies,
+    );
+    check_count(
+        &location,
+        "Tổng số giao dịch ra",
+        &mut flow.total_transactions_out,
+        count_out,
+        fill,
+        discrepancies,
+    );
+}
+
+fn reconcile_time_range(
+    info: &mut TransactionInfo,
+    fill: bool,
+    discrepancies: &mut Vec<Discrepancy>,
+) {
+    let flows = info.money_flows.iter().flatten().collect::<Vec<_>>();
+    let entries = flows
+        .iter()
+        .flat_map(|flow| {
+            inflow_entries(flow)
+                .into_iter()
+                .chain(outflow_entries(flow))
+                .map(move |entry| (flow_location(flow), entry))
+        })
+        .collect::<Vec<_>>();
+
+    let time_range = info.time_range.clone().unwrap_or_default();
+    let range_from = parse_date(time_range.from.as_ref());
+    let range_to = parse_date(time_range.to.as_ref());
+
+    for (location, entry) in entries.iter() {
+        let (tx_from, tx_to) = (parse_date(entry.tx_from), parse_date(entry.tx_to));
+
+        if let (Some(from), Some(to)) = (tx_from, tx_to)
+            && from > to
+        {
+            discrepancies.push(Discrepancy {
+                location: location.clone(),
+                field: "Giao dịch từ ngày - đến ngày",
+                declared: format!("{} - {}", from, to),
+                computed: "Giao dịch từ ngày không được sau Giao dịch đến ngày".to_string(),
+            });
+        }
+
+        let before_range = matches!((tx_from, range_from), (Some(tx), Some(range)) if tx < range);
+        let after_range = matches!((tx_to, range_to), (Some(tx), Some(range)) if tx > range);
+        if before_range || after_range {
+            discrepancies.push(Discrepancy {
+                location: location.clone(),
+                field: "Giao dịch từ ngày - đến ngày",
+                declared: format!(
+                    "{} - {}",
+                    display_declared(entry.tx_from),
+                    display_declared(entry.tx_to)
+                ),
+                computed: format!(
+                    "nằm ngoài thời gian giao dịch {} - {} của Phần IV",
+                    display_declared(time_range.from.as_ref()),
+                    display_declared(time_range.to.as_ref())
+                ),
+            });
+        }
+    }
+
+    let computed_from = entries
+        .iter()
+        .filter_map(|(_, e)| parse_date(e.tx_from))
+        .min();
+    let computed_to = entries
+        .iter()
+        .filter_map(|(_, e)| parse_date(e.tx_to))
+        .max();
+
+    let mut time_range = time_range;
+    for (field, declared, computed) in [
+        ("Từ ngày", &mut time_range.from, computed_from),
+        ("Đến ngày", &mut time_range.to, computed_to),
+    ] {
+        if let Some(computed) = computed
+            && parse_date(declared.as_ref()) != Some(computed)
+        {
+            discrepancies.push(Discrepancy {
+                location: "Thời gian giao dịch đáng ngờ".to_string(),
+                field,
+                declared: display_declared(declared.as_ref()),
+                computed: computed.format("%Y-%m-%d").to_string(),
+            });
+            if fill {
+                *declared = computed.format("%Y-%m-%d").to_string().into();
+            }
+        }
+    }
+
+    if fill {
+        info.time_range = time_range.into();
+    }
+}
+
+// The reader builds the per-currency amounts from the same cells, the check matters for payloads
+// read from JSON and keeps `--fill-computed` consistent with the detail
+fn reconcile_amounts(info: &mut TransactionInfo, fill: bool, discrepancies: &mut Vec<Discrepancy>) {
+    let flows = info.money_flows.iter().flatten().collect::<Vec<_>>();
+    let location = "Số tiền giao dịch đáng ngờ";
+
+    let computed_amounts = flows
+        .iter()
+        .flat_map(|flow| {
+            inflow_entries(flow)
+                .into_iter()
+                .chain(outflow_entries(flow))
+        })
+        .fold(BTreeMap::<String, f64>::new(), |mut acc, entry| {
+            let currency = entry.currency.map(|c| c.to_string()).unwrap_or_default();
+            *acc.entry(currency).or_insert(0.0) += parse_amount(entry.total_amount).unwrap_or(0.0);
+            acc
+        });
+
+    let computed_converted = flows
+        .iter()
+        .flat_map(|flow| {
+            inflow_entries(flow)
+                .into_iter()
+                .chain(outflow_entries(flow))
+        })
+        .filter_map(|entry| parse_amount(entry.total_converted))
+        .sum::<f64>();
+
+    let declared_amounts =
+        info.amounts
+            .iter()
+            .flatten()
+            .fold(BTreeMap::<String, f64>::new(), |mut acc, entry| {
+                let currency = entry
+                    .currency
+                    .as_ref()
+                    .map(|c| c.to_string())
+                    .unwrap_or_default();
+                *acc.entry(currency).or_insert(0.0) += entry.amount.unwrap_or(0.0);
+                acc
+            });
+
+    let currencies = computed_amounts
+        .keys()
+        .chain(declared_amounts.keys())
+        .collect::<std::collections::BTreeSet<_>>();
+
+    let mut amounts_matched = true;
+    for currency in currencies {
+        let declared = declared_amounts.get(currency);
+        let computed = computed_amounts.get(currency).copied().unwrap_or(0.0);
+
+        if declared.is_none_or(|declared| (declared - computed).abs() >= AMOUNT_TOLERANCE) {
+            amounts_matched = false;
+            discrepancies.push(Discrepancy {
+                location: location.to_string(),
+                field: "Số tiền nguyên tệ theo loại tiền",
+                declared: format!(
+                    "{} {}",
+                    declared
+                        .map(|d| d.to_string())
+                        .unwrap_or_else(|| "(trống)".to_string()),
+                    currency
+                ),
+                computed: format!("{} {}", computed, currency),
+            });
+        }
+    }
+
+    if fill && !amounts_matched {
+        info.amounts = computed_amounts
+            .into_iter()
+            .map(|(currency, amount)| AmountEntry {
+                currency: Currency::new(currency).ok(),
+                amount: amount.into(),
+            })
+            .collect::<Vec<_>>()
+            .into();
+    }
+
+    let converted_matched = info
+        .total_converted_amount
+        .is_some_and(|declared| (declared - computed_converted).abs() < AMOUNT_TOLERANCE);
+    if !converted_matched {
+        discrepancies.push(Discrepancy {
+            location: location.to_string(),
+            field: "Tổng số tiền giao dịch quy đổi",
+            declared: info
+                .total_converted_amount
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "(trống)".to_string()),
+            computed: computed_converted.to_string(),
+        });
+        if fill {
+            info.total_converted_amount = computed_converted.into();
+        }
+    }
+}
+
+/// Recomputes the Section IV totals from the money flow rows. When `fill` is set, the declared
+/// values are replaced by the computed ones.
+pub fn reconcile_transaction_info(info: &mut TransactionInfo, fill: bool) -> Vec<Discrepancy> {
+    let mut discrepancies = vec![];
+
+    for flow in info.money_flows.iter_mut().flatten() {
+        reconcile_flow_totals(flow, fill, &mut discrepancies);
+    }
+    reconcile_time_range(info, fill, &mut discrepancies);
+    reconcile_amounts(info, fill, &mut discrepancies);
+
+    discrepancies
+}
+
+pub fn reconcile_payload(payload: &mut Payload, fill: bool) -> Vec<Discrepancy> {
+    payload
+        .section_4
+        .as_mut()
+        .and_then(|s| s.transaction_info.as_mut())
+        .map(|info| reconcile_transaction_info(info, fill))
+        .unwrap_or_default()
+}
+
+/// Logs every discrepancy between the declared Section IV totals and the transaction detail.
+pub fn check_money_flows(payload: &mut Payload, fill: bool) {
+    let discrepancies = reconcile_payload(payload, fill);
+
+    discrepancies.iter().for_each(|d| log::warn!("{}", d));
+    if fill && !discrepancies.is_empty() {
+        log::info!("Đã cập nhật các giá trị tổng hợp tại Phần IV theo chi tiết giao dịch");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::section4::{FlowEntryIn, TimeRange};
+
+    fn info_with_inflow(amount: &str, from: &str, to: &str) -> TransactionInfo {
+        let entry = FlowEntryIn {
+            total_amount: amount.to_string().into(),
+            total_converted: amount.to_string().into(),
+            total_transactions: "1".to_string().into(),
+            tx_from: "2025-01-05".to_string().into(),
+            tx_to: "2025-01-20".to_string().into(),
+            ..Default::default()
+        };
+        TransactionInfo {
+            time_range: TimeRange {
+                from: from.to_string().into(),
+                to: to.to_string().into(),
+            }
+            .into(),
+            amounts: vec![AmountEntry {
+                currency: None,
+                amount: 1000.0.into(),
+            }]
+            .into(),
+            total_converted_amount: 1000.0.into(),
+            money_flows: vec![MoneyFlow {
+                total_converted_in: "1000".to_string().into(),
+                total_transactions_in: "1".to_string().into(),
+                total_converted_out: "0".to_string().into(),
+                total_transactions_out: "0".to_string().into(),
+                inflows: vec![entry].into(),
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn amounts_with_thousands_separators_match() {
+        let mut info = info_with_inflow("1,000", "2025-01-05", "2025-01-20");
+        assert!(reconcile_transaction_info(&mut info, false).is_empty());
+    }
+
+    #[test]
+    fn amount_cells_that_are_not_numbers_are_flagged() {
+        let mut info = info_with_inflow("một nghìn", "2025-01-05", "2025-01-20");
+        let discrepancies = reconcile_transaction_info(&mut info, false);
+        assert!(
+            discrepancies
+                .iter()
+                .any(|d| d.field == "Tổng số tiền nguyên tệ")
+        );
+    }
+
+    #[test]
+    fn time_range_that_disagrees_with_the_entries_is_flagged() {
+        let mut info = info_with_inflow("1000", "2025-01-01", "2025-01-20");
+        let discrepancies = reconcile_transaction_info(&mut info, true);
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].field, "Từ ngày");
+        assert_eq!(
+            info.time_range.and_then(|range| range.from).as_deref(),
+            Some("2025-01-05")
+        );
+    }
+}
@@ -10,3 +10,41 @@ pub struct ErrorResponse {
 pub struct SuccessResponse {
     pub id: Option<i64>,
 }
+
+/// Attachment as listed by the portal after an upload. The hash is only present when the portal
+/// computes one.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UploadedAttachment {
+    #[serde(rename = "fileName", default)]
+    pub file_name: Option<String>,
+    #[serde(rename = "fileSize", default)]
+    pub file_size: Option<i64>,
+    #[serde(
+        rename = "sha256",
+        alias = "checksum",
+        alias = "fileHash",
+        alias = "hash",
+        default
+    )]
+    pub hash: Option<String>,
+}
+
+/// Attachment list of an upload response, either the response itself or under `attachments`
+/// or `data`. `None` when the portal did not return attachment metadata, the upload is then not
+/// verified and the caller has to say so.
+pub fn uploaded_attachments(resp_text: &str) -> Option<Vec<UploadedAttachment>> {
+    let value = serde_json::from_str::<serde_json::Value>(resp_text).ok()?;
+    let candidates = [
+        Some(&value),
+        value.get("attachments"),
+        value.get("data"),
+        value.get("data").and_then(|data| data.get("attachments")),
+    ];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|candidate| candidate.as_array())
+        .find(|items| !items.is_empty() && items.iter().all(|item| item.get("fileName").is_some()))
+        .and_then(|items| serde_json::from_value(serde_json::Value::Array(items.clone())).ok())
+}
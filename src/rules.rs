@@ -0,0 +1,533 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+
+use crate::{
+    narrative::{missing_checklist_items, narrative_length_limit},
+    payload::form::Payload,
+    template::report_form_of,
+};
+
+const OTHER_INDICATOR_CODES: [&str; 1] = ["khac"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(formatter, "Lỗi"),
+            Severity::Warning => write!(formatter, "Cảnh báo"),
+        }
+    }
+}
+
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+    pub check: fn(&Payload) -> Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub rule_id: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "[{}] {}: {}",
+            self.rule_id, self.severity, self.message
+        )
+    }
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "STR-001",
+        severity: Severity::Error,
+        description: "Từ ngày của giao dịch không được sau Đến ngày",
+        check: check_transaction_time_range,
+    },
+    Rule {
+        id: "STR-002",
+        severity: Severity::Error,
+        description: "Ngày phát hiện không được trước các giao dịch đáng ngờ",
+        check: check_detection_after_transactions,
+    },
+    Rule {
+        id: "STR-003",
+        severity: Severity::Error,
+        description: "Ngày phát hiện không được sau ngày báo cáo",
+        check: check_detection_before_report,
+    },
+    Rule {
+        id: "STR-004",
+        severity: Severity::Error,
+        description: "Phần II phải có ít nhất một khách hàng",
+        check: check_has_customer,
+    },
+    Rule {
+        id: "STR-005",
+        severity: Severity::Error,
+        description: "Dấu hiệu đáng ngờ khác phải có nội dung mô tả",
+        check: check_other_indicator_content,
+    },
+    Rule {
+        id: "STR-006",
+        severity: Severity::Warning,
+        description: "Tài khoản trong dòng tiền phải có trong sheet Phần II. Tài khoản",
+        check: check_flow_accounts_declared,
+    },
+    Rule {
+        id: "STR-007",
+        severity: Severity::Error,
+        description: "Mô tả, phân tích chi tiết không được vượt quá số ký tự tối đa của website NHNN",
+        check: check_narrative_length,
+    },
+    Rule {
+        id: "STR-008",
+        severity: Severity::Warning,
+        description: "Mô tả, phân tích chi tiết nên nêu đủ ai, cái gì, khi nào, ở đâu, tại sao",
+        check: check_narrative_checklist,
+    },
+];
+
+fn parse_date(date: &Option<String>) -> Option<NaiveDate> {
+    date.as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+}
+
+fn check_transaction_time_range(payload: &Payload) -> Vec<String> {
+    let time_range = payload
+        .section_4
+        .as_ref()
+        .and_then(|s| s.transaction_info.as_ref())
+        .and_then(|t| t.time_range.as_ref());
+
+    match time_range.map(|t| (parse_date(&t.from), parse_date(&t.to))) {
+        Some((Some(from), Some(to))) if from > to => vec![format!(
+            "Từ ngày {} sau Đến ngày {} tại Phần IV - Thông tin về giao dịch đáng ngờ",
+            from.format("%d/%m/%Y"),
+            to.format("%d/%m/%Y")
+        )],
+        _ => vec![],
+    }
+}
+
+fn check_detection_after_transactions(payload: &Payload) -> Vec<String> {
+    let Some(section_4) = payload.section_4.as_ref() else {
+        return vec![];
+    };
+
+    let time_range = section_4
+        .transaction_info
+        .as_ref()
+        .and_then(|t| t.time_range.as_ref());
+
+    // The transactions are detected once they happened, up to the end date when it is known
+    let last_date = time_range.and_then(|t| match parse_date(&t.to) {
+        Some(to) => Some((to, "kết thúc")),
+        None => parse_date(&t.from).map(|from| (from, "bắt đầu")),
+    });
+
+    match (parse_date(&section_4.detection_date), last_date) {
+        (Some(detection), Some((date, bound))) if detection < date => vec![format!(
+            "Ngày phát hiện {} trước ngày {} giao dịch {}",
+            detection.format("%d/%m/%Y"),
+            bound,
+            date.format("%d/%m/%Y")
+        )],
+        _ => vec![],
+    }
+}
+
+fn check_detection_before_report(payload: &Payload) -> Vec<String> {
+    let detection_date = payload
+        .section_4
+        .as_ref()
+        .and_then(|s| parse_date(&s.detection_date));
+
+    match (
+        detection_date,
+        parse_date(&payload.general_info.report_date),
+    ) {
+        (Some(detection), Some(report)) if detection > report => vec![format!(
+            "Ngày phát hiện {} sau ngày báo cáo {}",
+            detection.format("%d/%m/%Y"),
+            report.format("%d/%m/%Y")
+        )],
+        _ => vec![],
+    }
+}
+
+fn check_has_customer(payload: &Payload) -> Vec<String> {
+    let Some(section_2) = payload.section_2.as_ref() else {
+        return vec![];
+    };
+
+    let n_individuals = section_2.individuals.as_ref().map_or(0, |v| v.len());
+    let n_organizations = section_2.organizations.as_ref().map_or(0, |v| v.len());
+
+    match n_individuals + n_organizations {
+        0 => vec![
+            "Không có khách hàng cá nhân hoặc tổ chức nào tại Phần II - Thông tin khách hàng"
+                .to_string(),
+        ],
+        _ => vec![],
+    }
+}
+
+fn check_other_indicator_content(payload: &Payload) -> Vec<String> {
+    payload
+        .section_4
+        .as_ref()
+        .and_then(|s| s.report_type.as_ref())
+        .and_then(|r| r.suspicious_indicators.as_ref())
+        .into_iter()
+        .flatten()
+        .filter(|indicator| {
+            indicator
+                .code
+                .as_deref()
+                .is_some_and(|code| OTHER_INDICATOR_CODES.contains(&code))
+        })
+        .filter(|indicator| {
+            indicator
+                .other_content
+                .as_deref()
+                .is_none_or(|content| content.trim().is_empty())
+        })
+        .map(|indicator| {
+            format!(
+                "Đã chọn \"{}\" nhưng chưa nhập nội dung mô tả",
+                indicator.description.clone().unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+fn check_flow_accounts_declared(payload: &Payload) -> Vec<String> {
+    let Some(section_2) = payload.section_2.as_ref() else {
+        return vec![];
+    };
+
+    let individual_accounts = section_2
+        .individuals
+        .iter()
+        .flatten()
+        .flat_map(|individual| individual.accounts.iter().flatten());
+    let organization_accounts = section_2
+        .organizations
+        .iter()
+        .flatten()
+        .flat_map(|organization| organization.accounts.iter().flatten());
+
+    let declared_accounts = individual_accounts
+        .chain(organization_accounts)
+        .filter_map(|account| account.account_number.as_deref())
+        .chain(section_2.declared_accounts.iter().map(String::as_str))
+        .map(str::trim)
+        .collect::<HashSet<_>>();
+
+    payload
+        .section_4
+        .as_ref()
+        .and_then(|s| s.transaction_info.as_ref())
+        .and_then(|t| t.money_flows.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|flow| flow.account_number.as_deref().map(str::trim))
+        .filter(|account| !account.is_empty() && !declared_accounts.contains(account))
+        .map(|account| {
+            format!(
+                "Tài khoản {} trong dòng tiền không có trong sheet Phần II. Tài khoản",
+                account
+            )
+        })
+        .collect()
+}
+
+fn narrative(payload: &Payload) -> Option<&str> {
+    payload
+        .section_4
+        .as_ref()
+        .and_then(|s| s.analysis.as_ref())
+        .and_then(|a| a.detail.as_deref())
+}
+
+fn check_narrative_length(payload: &Payload) -> Vec<String> {
+    let n_chars = narrative(payload).map_or(0, |text| text.chars().count());
+    let report_form = match report_form_of(payload.general_info.report_form.as_deref()) {
+        Ok(report_form) => report_form,
+        Err(err) => return vec![format!("{:#}", err)],
+    };
+
+    match narrative_length_limit(report_form) {
+        Some(limit) if n_chars > limit => vec![format!(
+            "Mô tả, phân tích chi tiết dài {} ký tự, vượt quá giới hạn {} ký tự",
+            n_chars, limit
+        )],
+        _ => vec![],
+    }
+}
+
+fn check_narrative_checklist(payload: &Payload) -> Vec<String> {
+    if payload.section_4.is_none() {
+        return vec![];
+    }
+
+    match narrative(payload).map(str::trim) {
+        None | Some("") => vec!["Chưa có nội dung mô tả, phân tích chi tiết".to_string()],
+        Some(text) => missing_checklist_items(text)
+            .into_iter()
+            .map(|item| format!("Mô tả, phân tích chi tiết có thể còn thiếu {}", item))
+            .collect(),
+    }
+}
+
+pub fn validate_payload(payload: &Payload) -> Vec<Violation> {
+    RULES
+        .iter()
+        .flat_map(|rule| {
+            (rule.check)(payload).into_iter().map(|message| Violation {
+                rule_id: rule.id,
+                severity: rule.severity,
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Logs the warnings and fails with every error, listed under `summary`.
+pub fn report_violations(violations: &[Violation], summary: &str) -> anyhow::Result<()> {
+    violations
+        .iter()
+        .filter(|v| v.severity == Severity::Warning)
+        .for_each(|v| log::warn!("{}", v));
+
+    let errors = violations
+        .iter()
+        .filter(|v| v.severity == Severity::Error)
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>();
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(anyhow::anyhow!("{}:\n{}", summary, errors.join("\n"))),
+    }
+}
+
+/// Logs warnings and fails with every error found by the business rules.
+pub fn check_payload_rules(payload: &Payload) -> anyhow::Result<()> {
+    report_violations(
+        &validate_payload(payload),
+        "Báo cáo không đạt các quy tắc kiểm tra",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        payload::{
+            code::IndicatorType,
+            entities::Individual,
+            info::GeneralInfo,
+            section2::Section2,
+            section4::{
+                Analysis, MoneyFlow, ReportType, Section4, SuspiciousIndicator, TimeRange,
+                TransactionInfo,
+            },
+        },
+        template::default_report_form,
+    };
+
+    fn date(date: &str) -> Option<String> {
+        Some(date.to_string())
+    }
+
+    fn payload_with_dates(
+        from: Option<String>,
+        to: Option<String>,
+        detection_date: Option<String>,
+        report_date: Option<String>,
+    ) -> Payload {
+        Payload {
+            general_info: GeneralInfo {
+                report_date,
+                ..Default::default()
+            },
+            section_4: Some(Section4 {
+                transaction_info: Some(TransactionInfo {
+                    time_range: Some(TimeRange { from, to }),
+                    ..Default::default()
+                }),
+                detection_date,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn payload_with_indicator(code: &str, other_content: Option<&str>) -> Payload {
+        Payload {
+            section_4: Some(Section4 {
+                report_type: Some(ReportType {
+                    suspicious_indicators: Some(vec![SuspiciousIndicator {
+                        code: Some(IndicatorType::for_form(default_report_form(), code).unwrap()),
+                        description: Some("Dấu hiệu đáng ngờ khác".to_string()),
+                        other_content: other_content.map(str::to_string),
+                    }]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn payload_with_narrative(detail: String) -> Payload {
+        Payload {
+            section_4: Some(Section4 {
+                analysis: Some(Analysis {
+                    detail: Some(detail),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn payload_with_flow(account: &str, declared_accounts: Vec<String>) -> Payload {
+        Payload {
+            section_2: Some(Section2 {
+                declared_accounts,
+                ..Default::default()
+            }),
+            section_4: Some(Section4 {
+                transaction_info: Some(TransactionInfo {
+                    money_flows: Some(vec![MoneyFlow {
+                        account_number: Some(account.to_string()),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accounts_sheet_rows_are_declared() {
+        let payload = payload_with_flow(" 0123456789 ", vec!["0123456789".to_string()]);
+        assert!(check_flow_accounts_declared(&payload).is_empty());
+
+        let payload = payload_with_flow("999", vec!["0123456789".to_string()]);
+        assert_eq!(check_flow_accounts_declared(&payload).len(), 1);
+    }
+
+    #[test]
+    fn time_range_must_be_in_order() {
+        let payload = payload_with_dates(date("2025-01-01"), date("2025-01-31"), None, None);
+        assert!(check_transaction_time_range(&payload).is_empty());
+
+        let payload = payload_with_dates(date("2025-02-01"), date("2025-01-31"), None, None);
+        assert_eq!(check_transaction_time_range(&payload).len(), 1);
+    }
+
+    #[test]
+    fn detection_is_not_before_the_transactions() {
+        let payload = payload_with_dates(
+            date("2025-01-01"),
+            date("2025-01-31"),
+            date("2025-01-31"),
+            None,
+        );
+        assert!(check_detection_after_transactions(&payload).is_empty());
+
+        let payload = payload_with_dates(
+            date("2025-01-01"),
+            date("2025-01-31"),
+            date("2025-01-15"),
+            None,
+        );
+        assert_eq!(check_detection_after_transactions(&payload).len(), 1);
+
+        // Without an end date the start date is the bound
+        let payload = payload_with_dates(date("2025-01-01"), None, date("2025-01-15"), None);
+        assert!(check_detection_after_transactions(&payload).is_empty());
+
+        let payload = payload_with_dates(date("2025-01-01"), None, date("2024-12-31"), None);
+        assert_eq!(check_detection_after_transactions(&payload).len(), 1);
+    }
+
+    #[test]
+    fn detection_is_not_after_the_report() {
+        let payload = payload_with_dates(None, None, date("2025-02-01"), date("2025-02-01"));
+        assert!(check_detection_before_report(&payload).is_empty());
+
+        let payload = payload_with_dates(None, None, date("2025-02-02"), date("2025-02-01"));
+        assert_eq!(check_detection_before_report(&payload).len(), 1);
+    }
+
+    #[test]
+    fn section_2_needs_a_customer() {
+        let payload = Payload {
+            section_2: Some(Section2 {
+                individuals: Some(vec![Individual::default()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(check_has_customer(&payload).is_empty());
+
+        let payload = Payload {
+            section_2: Some(Section2::default()),
+            ..Default::default()
+        };
+        assert_eq!(check_has_customer(&payload).len(), 1);
+    }
+
+    #[test]
+    fn other_indicator_needs_content() {
+        let payload = payload_with_indicator("khac", Some("Giao dịch vòng tròn"));
+        assert!(check_other_indicator_content(&payload).is_empty());
+
+        let payload = payload_with_indicator("khac", Some(" "));
+        assert_eq!(check_other_indicator_content(&payload).len(), 1);
+    }
+
+    #[test]
+    fn narrative_fits_the_portal_limit() {
+        let limit = narrative_length_limit(default_report_form()).unwrap();
+
+        let payload = payload_with_narrative("a".repeat(limit));
+        assert!(check_narrative_length(&payload).is_empty());
+
+        let payload = payload_with_narrative("a".repeat(limit + 1));
+        assert_eq!(check_narrative_length(&payload).len(), 1);
+    }
+
+    #[test]
+    fn narrative_covers_the_checklist() {
+        let payload = payload_with_narrative(
+            "Ngày 15/01/2025 khách hàng Nguyễn Văn A chuyển khoản 2 tỷ đồng tại chi nhánh Hà Nội, \
+             đáng ngờ vì không phù hợp với thu nhập"
+                .to_string(),
+        );
+        assert!(check_narrative_checklist(&payload).is_empty());
+
+        let payload = payload_with_narrative("Khách hàng chuyển khoản".to_string());
+        assert!(!check_narrative_checklist(&payload).is_empty());
+    }
+}
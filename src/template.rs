@@ -27,7 +27,7 @@ pub struct LegalBasis {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum ExcelParam {
-    Address(CellAddress),
+    Address(#[serde(deserialize_with = "cell_address")] CellAddress),
     Value(String),
     Table(Table),
     LegalBasis(HashMap<String, LegalBasis>),
@@ -35,6 +35,27 @@ pub enum ExcelParam {
     List(Vec<String>),
 }
 
+/// A sheet name and a single cell such as `["STR", "G1"]`, so that lists of two values are not
+/// taken for an address.
+fn cell_address<'de, D>(deserializer: D) -> Result<CellAddress, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let address = CellAddress::deserialize(deserializer)?;
+    let column_end = address
+        .cell
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(address.cell.len());
+    let (column, row) = address.cell.split_at(column_end);
+    match !column.is_empty() && !row.is_empty() && row.chars().all(|c| c.is_ascii_digit()) {
+        true => Ok(address),
+        false => Err(serde::de::Error::custom(format!(
+            "'{}' is not a cell reference",
+            address.cell
+        ))),
+    }
+}
+
 pub fn load_template() -> anyhow::Result<HashMap<String, ExcelParam>> {
     const TEMPLATE: &str = include_str!("report_template.json");
     let parsed_result: HashMap<String, ExcelParam> = serde_json::from_str(TEMPLATE)?;
@@ -44,60 +65,163 @@ pub fn load_template() -> anyhow::Result<HashMap<String, ExcelParam>> {
 pub static REPORT_TEMPLATE: LazyLock<HashMap<String, ExcelParam>> =
     LazyLock::new(|| load_template().expect("Failed to load report template"));
 
+const DEFAULT_REPORT_FORM: &str = "M1";
+const REPORT_FORM_KEY: &str = "Mẫu báo cáo";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportForm {
+    #[serde(rename = "mã")]
+    pub code: String,
+    #[serde(rename = "tên")]
+    pub name: String,
+    #[serde(rename = "phần")]
+    pub sections: Vec<u8>,
+    #[serde(rename = "mẫu", default)]
+    pub template: HashMap<String, ExcelParam>,
+}
+
+impl ReportForm {
+    pub fn has_section(&self, section: u8) -> bool {
+        self.sections.contains(&section)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ReportForms {
+    #[serde(rename = "biểu mẫu")]
+    forms: Vec<ReportForm>,
+}
+
+pub fn load_report_forms() -> anyhow::Result<Vec<ReportForm>> {
+    const REPORT_FORMS: &str = include_str!("report_forms.json");
+    let parsed_result: ReportForms = serde_json::from_str(REPORT_FORMS)?;
+    Ok(parsed_result.forms)
+}
+
+pub static REPORT_FORMS: LazyLock<Vec<ReportForm>> =
+    LazyLock::new(|| load_report_forms().expect("Failed to load report forms"));
+
+pub fn find_report_form(code: &str) -> anyhow::Result<&'static ReportForm> {
+    REPORT_FORMS
+        .iter()
+        .find(|form| form.code.eq_ignore_ascii_case(code.trim()))
+        .with_context(|| {
+            format!(
+                "Mẫu báo cáo '{}' không được hỗ trợ. Các mẫu báo cáo hợp lệ: {}",
+                code,
+                REPORT_FORMS
+                    .iter()
+                    .map(|form| form.code.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+pub fn default_report_form() -> &'static ReportForm {
+    find_report_form(DEFAULT_REPORT_FORM).expect("Default report form not found")
+}
+
+/// Form of a report given its code, M1 when the code is blank.
+pub fn report_form_of(code: Option<&str>) -> anyhow::Result<&'static ReportForm> {
+    match code.map(str::trim) {
+        None | Some("") => Ok(default_report_form()),
+        Some(code) => find_report_form(code),
+    }
+}
+
+/// Selects the form used to read the workbook: the form selected on the command line first,
+/// then the form named in the workbook, then M1.
+pub fn select_report_form(
+    workbook: &mut calamine::Xlsx<impl Seek + Read>,
+    form_override: Option<&'static ReportForm>,
+) -> anyhow::Result<&'static ReportForm> {
+    if let Some(form) = form_override {
+        return Ok(form);
+    }
+
+    let workbook_code = match REPORT_TEMPLATE.get(REPORT_FORM_KEY) {
+        Some(ExcelParam::Address(addr)) => {
+            crate::utils::excel::read_cell_value(workbook, &addr.sheet, &addr.cell)?
+        }
+        _ => String::new(),
+    };
+    report_form_of(Some(&workbook_code))
+}
+
+fn template_param(form: &ReportForm, key: &str) -> Option<ExcelParam> {
+    form.template
+        .get(key)
+        .or_else(|| REPORT_TEMPLATE.get(key))
+        .cloned()
+}
+
 pub fn cell_value_from_key(
+    form: &ReportForm,
     key: &str,
     workbook: &mut calamine::Xlsx<impl Seek + Read>,
 ) -> anyhow::Result<String> {
-    _cell_value_from_key(key, workbook).with_context(|| format!("Lỗi khi tìm thông tin {}", key))
+    _cell_value_from_key(form, key, workbook)
+        .with_context(|| format!("Lỗi khi tìm thông tin {}", key))
 }
 
 fn _cell_value_from_key(
+    form: &ReportForm,
     key: &str,
     workbook: &mut calamine::Xlsx<impl Seek + Read>,
 ) -> anyhow::Result<String> {
-    let cell_addr = match REPORT_TEMPLATE
-        .get(key)
-        .with_context(|| format!("Cell `{}` not found", key))?
-    {
-        ExcelParam::Address(addr) => addr,
-        ExcelParam::Value(val) => return Ok(val.clone()),
-        ExcelParam::LegalBasis(_) => {
-            return Err(anyhow::anyhow!(
-                "Expected cell address for key `{}`, found legal basis definition",
-                key
-            ));
-        }
-        ExcelParam::Table(_) => {
-            return Err(anyhow::anyhow!(
-                "Expected cell address for key `{}`, found table definition",
-                key
-            ));
-        }
-        ExcelParam::Mapping(_) => {
-            return Err(anyhow::anyhow!(
-                "Expected cell address for key `{}`, found mapping definition",
-                key
-            ));
-        }
-        ExcelParam::List(_) => {
-            return Err(anyhow::anyhow!(
-                "Expected cell address for key `{}`, found list definition",
-                key
-            ));
-        }
-    };
+    let cell_addr =
+        match template_param(form, key).with_context(|| format!("Cell `{}` not found", key))? {
+            ExcelParam::Address(addr) => addr,
+            ExcelParam::Value(val) => return Ok(val),
+            ExcelParam::LegalBasis(_) => {
+                return Err(anyhow::anyhow!(
+                    "Expected cell address for key `{}`, found legal basis definition",
+                    key
+                ));
+            }
+            ExcelParam::Table(_) => {
+                return Err(anyhow::anyhow!(
+                    "Expected cell address for key `{}`, found table definition",
+                    key
+                ));
+            }
+            ExcelParam::Mapping(_) => {
+                return Err(anyhow::anyhow!(
+                    "Expected cell address for key `{}`, found mapping definition",
+                    key
+                ));
+            }
+            ExcelParam::List(_) => {
+                return Err(anyhow::anyhow!(
+                    "Expected cell address for key `{}`, found list definition",
+                    key
+                ));
+            }
+        };
 
     let cell_value =
         crate::utils::excel::read_cell_value(workbook, &cell_addr.sheet, &cell_addr.cell)?;
     Ok(cell_value)
 }
 
-pub fn table_config_from_key(key: &str) -> anyhow::Result<Table> {
-    match REPORT_TEMPLATE
-        .get(key)
-        .expect(format!("Table `{}` not found", key).as_str())
-    {
-        ExcelParam::Table(table) => Ok(table.clone()),
+pub fn cell_address_from_key(form: &ReportForm, key: &str) -> anyhow::Result<CellAddress> {
+    match template_param(form, key).with_context(|| format!("Cell `{}` not found", key))? {
+        ExcelParam::Address(addr) => Ok(addr),
+        _ => Err(anyhow::anyhow!("Expected cell address for key `{}`", key)),
+    }
+}
+
+pub fn value_from_key(form: &ReportForm, key: &str) -> anyhow::Result<String> {
+    match template_param(form, key).with_context(|| format!("Value `{}` not found", key))? {
+        ExcelParam::Value(value) => Ok(value),
+        _ => Err(anyhow::anyhow!("Expected value for key `{}`", key)),
+    }
+}
+
+pub fn table_config_from_key(form: &ReportForm, key: &str) -> anyhow::Result<Table> {
+    match template_param(form, key).with_context(|| format!("Table `{}` not found", key))? {
+        ExcelParam::Table(table) => Ok(table),
         _ => Err(anyhow::anyhow!(
             "Expected table definition for key `{}`",
             key
@@ -105,12 +229,9 @@ pub fn table_config_from_key(key: &str) -> anyhow::Result<Table> {
     }
 }
 
-pub fn mapping_from_key(key: &str) -> anyhow::Result<HashMap<String, String>> {
-    match REPORT_TEMPLATE
-        .get(key)
-        .expect(format!("Mapping `{}` not found", key).as_str())
-    {
-        ExcelParam::Mapping(mapping) => Ok(mapping.clone()),
+pub fn mapping_from_key(form: &ReportForm, key: &str) -> anyhow::Result<HashMap<String, String>> {
+    match template_param(form, key).with_context(|| format!("Mapping `{}` not found", key))? {
+        ExcelParam::Mapping(mapping) => Ok(mapping),
         _ => Err(anyhow::anyhow!(
             "Expected mapping definition for key `{}`",
             key
@@ -118,12 +239,12 @@ pub fn mapping_from_key(key: &str) -> anyhow::Result<HashMap<String, String>> {
     }
 }
 
-pub fn legal_basis_mapping_from_key(key: &str) -> anyhow::Result<HashMap<String, LegalBasis>> {
-    match REPORT_TEMPLATE
-        .get(key)
-        .expect(format!("Legal basis `{}` not found", key).as_str())
-    {
-        ExcelParam::LegalBasis(mapping) => Ok(mapping.clone()),
+pub fn legal_basis_mapping_from_key(
+    form: &ReportForm,
+    key: &str,
+) -> anyhow::Result<HashMap<String, LegalBasis>> {
+    match template_param(form, key).with_context(|| format!("Legal basis `{}` not found", key))? {
+        ExcelParam::LegalBasis(mapping) => Ok(mapping),
         _ => Err(anyhow::anyhow!(
             "Expected legal basis definition for key `{}`",
             key
@@ -131,15 +252,72 @@ pub fn legal_basis_mapping_from_key(key: &str) -> anyhow::Result<HashMap<String,
     }
 }
 
-pub fn value_list_from_key(key: &str) -> anyhow::Result<Vec<String>> {
-    match REPORT_TEMPLATE
-        .get(key)
-        .expect(format!("List `{}` not found", key).as_str())
-    {
-        ExcelParam::List(list) => Ok(list.clone()),
+pub fn value_list_from_key(form: &ReportForm, key: &str) -> anyhow::Result<Vec<String>> {
+    match template_param(form, key).with_context(|| format!("List `{}` not found", key))? {
+        ExcelParam::List(list) => Ok(list),
         _ => Err(anyhow::anyhow!(
             "Expected list definition for key `{}`",
             key
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUIRED_TYPES_KEY: &str = "Phần VI. Tài liệu đính kèm - Tài liệu bắt buộc";
+
+    #[test]
+    fn form_template_overrides_the_report_template() {
+        let m1 = find_report_form("m1").unwrap();
+        let form: ReportForm = serde_json::from_str(
+            r#"{
+                "mã": "TEST",
+                "tên": "Test",
+                "phần": [1, 2, 4, 5, 6],
+                "mẫu": {
+                    "Phần VI. Tài liệu đính kèm - Tài liệu bắt buộc": ["Bảng kê", "Minh họa dòng tiền"]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value_list_from_key(m1, REQUIRED_TYPES_KEY).unwrap().len(),
+            3
+        );
+        assert_eq!(
+            value_list_from_key(&form, REQUIRED_TYPES_KEY).unwrap(),
+            vec!["Bảng kê", "Minh họa dòng tiền"]
+        );
+        // Keys the form does not override come from the report template
+        assert_eq!(
+            table_config_from_key(&form, "Phần II. KHCN").unwrap().sheet,
+            "Phần II. KHCN"
+        );
+        assert!(!form.has_section(3));
+    }
+
+    #[test]
+    fn only_cell_references_are_addresses() {
+        let address: ExcelParam = serde_json::from_str(r#"["STR", "AB12"]"#).unwrap();
+        assert!(matches!(address, ExcelParam::Address(addr) if addr.cell == "AB12"));
+
+        for list in [
+            r#"["Bảng kê", "Hồ sơ"]"#,
+            r#"["STR", "12"]"#,
+            r#"["STR", "B1:B2"]"#,
+        ] {
+            let param: ExcelParam = serde_json::from_str(list).unwrap();
+            assert!(matches!(param, ExcelParam::List(_)), "{}", list);
+        }
+    }
+
+    #[test]
+    fn blank_form_code_is_m1() {
+        assert_eq!(report_form_of(None).unwrap().code, "M1");
+        assert_eq!(report_form_of(Some(" ")).unwrap().code, "M1");
+        assert!(report_form_of(Some("M9")).is_err());
+    }
+}
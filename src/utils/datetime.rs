@@ -67,3 +67,20 @@ impl ConvertDateFormat for Option<String> {
         }
     }
 }
+
+pub fn ensure_not_after(date: &Option<String>, limit: &Option<String>) -> anyhow::Result<()> {
+    let parse_fn = |value: &Option<String>| {
+        value
+            .as_ref()
+            .and_then(|v| chrono::NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d").ok())
+    };
+
+    match (parse_fn(date), parse_fn(limit)) {
+        (Some(date_value), Some(limit_value)) if date_value > limit_value => Err(anyhow::anyhow!(
+            "Ngày '{}' sau ngày '{}'",
+            date_value.format("%d/%m/%Y"),
+            limit_value.format("%d/%m/%Y")
+        )),
+        _ => Ok(()),
+    }
+}
@@ -8,6 +8,12 @@ pub struct CellAddress {
     pub cell: String,
 }
 
+impl std::fmt::Display for CellAddress {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "'{}'!{}", self.sheet, self.cell)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ExcelCoord {
     pub row: u32,
@@ -153,3 +159,90 @@ where
         .unwrap_or_default();
     Ok(cell_value)
 }
+
+/// Reads the non-empty cells of an A1 style reference, either a single cell (`B84`) or a
+/// rectangular range (`B84:B200`), row by row.
+pub fn read_range_values<RS>(
+    workbook: &mut calamine::Xlsx<RS>,
+    sheet_name: &str,
+    range_ref: &str,
+) -> anyhow::Result<Vec<String>>
+where
+    RS: Seek + Read,
+{
+    let (start, end) = range_bounds(range_ref)?;
+
+    let range = workbook.worksheet_range(sheet_name)?;
+    let values = (start.row.min(end.row)..=start.row.max(end.row))
+        .flat_map(|row| {
+            (start.col.min(end.col)..=start.col.max(end.col)).map(move |col| (row, col))
+        })
+        .filter_map(|coord| range.get_value(coord).map(|v| v.to_string()))
+        .filter(|v| !v.trim().is_empty())
+        .collect();
+
+    Ok(values)
+}
+
+fn range_bounds(range_ref: &str) -> anyhow::Result<(ExcelCoord, ExcelCoord)> {
+    let range_ref = range_ref.replace('$', "");
+    let (start, end) = range_ref
+        .split_once(':')
+        .unwrap_or((range_ref.as_str(), range_ref.as_str()));
+    ExcelCoord::from_a1_style(start)
+        .zip(ExcelCoord::from_a1_style(end))
+        .ok_or_else(|| anyhow::anyhow!("Vùng dữ liệu '{}' không hợp lệ", range_ref))
+}
+
+/// Non-empty cells right below a range whose last row is filled, in its columns and down to the
+/// first empty row, such as text that went on in rows inserted after the range.
+pub fn cells_below_range<RS>(
+    workbook: &mut calamine::Xlsx<RS>,
+    sheet_name: &str,
+    range_ref: &str,
+) -> anyhow::Result<Vec<String>>
+where
+    RS: Seek + Read,
+{
+    let (start, end) = range_bounds(range_ref)?;
+    let columns = start.col.min(end.col)..=start.col.max(end.col);
+
+    let range = workbook.worksheet_range(sheet_name)?;
+    let filled_cells = |row: u32| {
+        columns
+            .clone()
+            .filter(|&col| {
+                range
+                    .get_value((row, col))
+                    .is_some_and(|value| !value.to_string().trim().is_empty())
+            })
+            .map(|col| ExcelCoord::new(row, col).to_a1_with_base(ExcelCoord::default()))
+            .collect::<Vec<_>>()
+    };
+
+    let last_row = start.row.max(end.row);
+    if filled_cells(last_row).is_empty() {
+        return Ok(vec![]);
+    }
+    let cells = (last_row + 1..)
+        .map(filled_cells)
+        .take_while(|row_cells| !row_cells.is_empty())
+        .flatten()
+        .collect();
+    Ok(cells)
+}
+
+/// Looks up a workbook defined name and returns the sheet and the A1 style range it refers to.
+pub fn defined_name_range<RS>(workbook: &calamine::Xlsx<RS>, name: &str) -> Option<(String, String)>
+where
+    RS: Seek + Read,
+{
+    let (_, formula) = workbook
+        .defined_names()
+        .iter()
+        .find(|(defined_name, _)| defined_name.eq_ignore_ascii_case(name))?;
+
+    let (sheet, range_ref) = formula.trim_start_matches('=').rsplit_once('!')?;
+    let sheet = sheet.trim_matches('\'').replace("''", "'");
+    Some((sheet, range_ref.replace('$', "")))
+}
@@ -1,3 +1,4 @@
 pub mod datetime;
 pub mod excel;
+pub mod number;
 pub mod setup;
@@ -0,0 +1,106 @@
+/// `percent_format` tells whether the cell is formatted as a percentage, Excel then stores the
+/// fraction, 25% is read as 0.25. Otherwise the value is the typed percentage.
+pub trait ConvertPercentage {
+    fn to_percentage(&self, percent_format: bool) -> anyhow::Result<Option<f64>>;
+}
+
+impl ConvertPercentage for &str {
+    fn to_percentage(&self, percent_format: bool) -> anyhow::Result<Option<f64>> {
+        let value = self.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        let percentage = value
+            .trim_end_matches('%')
+            .trim()
+            .replace(',', ".")
+            .parse::<f64>()
+            .map_err(|_| anyhow::anyhow!("Tỷ lệ '{}' không phải là số", self))?;
+
+        let percentage = match percent_format && !value.ends_with('%') {
+            true => percentage * 100.0,
+            false => percentage,
+        };
+
+        match (0.0..=100.0).contains(&percentage) {
+            true => Ok(Some(percentage)),
+            false => Err(anyhow::anyhow!(
+                "Tỷ lệ '{}' phải nằm trong khoảng từ 0 đến 100",
+                self
+            )),
+        }
+    }
+}
+
+impl ConvertPercentage for Option<String> {
+    fn to_percentage(&self, percent_format: bool) -> anyhow::Result<Option<f64>> {
+        match self {
+            Some(s) => s.as_str().to_percentage(percent_format),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Amount typed in a cell, with or without commas between thousands.
+pub fn parse_amount(value: &str) -> Option<f64> {
+    value.replace(',', "").trim().parse::<f64>().ok()
+}
+
+pub fn parse_count(value: &str) -> Option<i64> {
+    value.replace(',', "").trim().parse::<i64>().ok()
+}
+
+/// Amount with dots between thousands and a decimal comma, as written in Vietnamese documents.
+pub fn format_amount(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    let integer = rounded.abs().trunc() as u64;
+    let fraction = ((rounded.abs() - integer as f64) * 100.0).round() as u64;
+
+    let digits = integer.to_string();
+    let mut grouped = String::new();
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if rounded < 0.0 { "-" } else { "" };
+    match fraction {
+        0 => format!("{}{}", sign, grouped),
+        _ => format!("{}{},{:02}", sign, grouped, fraction)
+            .trim_end_matches('0')
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_formatted_cells_hold_fractions() {
+        assert_eq!("0.25".to_percentage(true).unwrap(), Some(25.0));
+        assert_eq!("1".to_percentage(true).unwrap(), Some(100.0));
+        assert_eq!("25%".to_percentage(true).unwrap(), Some(25.0));
+        assert!("1.5".to_percentage(true).is_err());
+    }
+
+    #[test]
+    fn typed_values_are_percentages() {
+        assert_eq!("1".to_percentage(false).unwrap(), Some(1.0));
+        assert_eq!("0.5".to_percentage(false).unwrap(), Some(0.5));
+        assert_eq!("25".to_percentage(false).unwrap(), Some(25.0));
+        assert_eq!("0,5%".to_percentage(false).unwrap(), Some(0.5));
+        assert_eq!(" ".to_percentage(false).unwrap(), None);
+        assert!("150".to_percentage(false).is_err());
+    }
+
+    #[test]
+    fn amounts_accept_thousands_separators() {
+        assert_eq!(parse_amount("1,000.5"), Some(1000.5));
+        assert_eq!(parse_count(" 1,200 "), Some(1200));
+        assert_eq!(parse_amount("abc"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::fs::DirEntry;
+use std::{fs::DirEntry, path::PathBuf};
 
 #[cfg(feature = "packaging")]
 use anyhow::Context;
@@ -6,6 +6,16 @@ use anyhow::Context;
 use indicatif::{ProgressBar, ProgressStyle};
 use indicatif_log_bridge::LogWrapper;
 
+use crate::{
+    attachments::{
+        convert::conversion_config, policy::attachment_policy, scan::scan_config,
+        statement::statement_columns,
+    },
+    codes::bank::bank_directory,
+    deadline::holiday_calendar,
+    template::{ReportForm, find_report_form},
+};
+
 #[cfg(feature = "packaging")]
 fn set_current_dir() -> anyhow::Result<()> {
     let current_dir = std::env::current_exe()
@@ -37,6 +47,13 @@ pub fn initial_setup() -> anyhow::Result<ProgressBar> {
     let multi_progress = indicatif::MultiProgress::new();
     LogWrapper::new(multi_progress.clone(), logger).try_init()?;
 
+    holiday_calendar()?;
+    attachment_policy()?;
+    conversion_config()?;
+    statement_columns()?;
+    scan_config()?;
+    bank_directory()?;
+
     let progress_bar = multi_progress
         .add(indicatif::ProgressBar::new_spinner())
         .with_message("Processing file...")
@@ -47,6 +64,87 @@ pub fn initial_setup() -> anyhow::Result<ProgressBar> {
     Ok(progress_bar)
 }
 
+/// Form selected with `--form`, used for every workbook instead of the form named in it.
+pub fn select_report_form_from_args() -> anyhow::Result<Option<&'static ReportForm>> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let form_code = args
+        .iter()
+        .position(|arg| arg == "--form")
+        .map(|idx| {
+            args.get(idx + 1)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Thiếu mã mẫu báo cáo sau tham số --form"))
+        })
+        .transpose()?;
+
+    let Some(form_code) = form_code else {
+        return Ok(None);
+    };
+
+    let form = find_report_form(&form_code)?;
+    log::info!("Sử dụng mẫu báo cáo {} cho tất cả các file", form.code);
+    Ok(Some(form))
+}
+
+pub fn fill_computed_from_args() -> bool {
+    std::env::args().any(|arg| arg == "--fill-computed")
+}
+
+pub fn convert_attachments_from_args() -> bool {
+    std::env::args().any(|arg| arg == "--convert-attachments")
+}
+
+pub fn scan_attachments_from_args() -> bool {
+    std::env::args().any(|arg| arg == "--scan-attachments")
+}
+
+pub fn pdf_without_diacritics_from_args() -> bool {
+    std::env::args().any(|arg| arg == "--pdf-without-diacritics")
+}
+
+pub fn late_reason_from_args() -> anyhow::Result<Option<String>> {
+    let args = std::env::args().collect::<Vec<_>>();
+    args.iter()
+        .position(|arg| arg == "--late-reason")
+        .map(|idx| {
+            args.get(idx + 1)
+                .map(|reason| reason.trim().to_string())
+                .filter(|reason| !reason.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Thiếu lý do nộp muộn sau tham số --late-reason"))
+        })
+        .transpose()
+}
+
+/// Report payloads given with `--json`, each a JSON file or a folder of JSON files.
+pub fn json_inputs_from_args() -> anyhow::Result<Vec<PathBuf>> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let mut json_files = vec![];
+
+    for (idx, _) in args.iter().enumerate().filter(|(_, arg)| *arg == "--json") {
+        let path = args
+            .get(idx + 1)
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow::anyhow!("Thiếu đường dẫn file JSON sau tham số --json"))?;
+
+        match path.is_dir() {
+            true => {
+                let mut files = std::fs::read_dir(&path)
+                    .map_err(|err| anyhow::anyhow!("Không thể đọc folder {:#?}: {}", path, err))?
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|file| file.extension().and_then(|s| s.to_str()) == Some("json"))
+                    .collect::<Vec<_>>();
+                files.sort();
+                json_files.extend(files);
+            }
+            false if path.is_file() => json_files.push(path),
+            false => return Err(anyhow::anyhow!("Không tìm thấy file JSON {:#?}", path)),
+        }
+    }
+
+    Ok(json_files)
+}
+
 pub fn get_input_excel_files() -> anyhow::Result<Vec<DirEntry>> {
     let excel_files = std::fs::read_dir("input")
         .map_err(|_err| {